@@ -0,0 +1,114 @@
+use super::JwtVerifier;
+use crate::service::UserIdNormalizer;
+use tonic::{Request, Status, metadata::MetadataMap, service::Interceptor};
+use tracing::debug;
+
+/// Who is making the call, as established by [`AuthInterceptor`]. Requests
+/// that never passed through the interceptor carry no `Caller`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Caller {
+    /// No bearer token was sent
+    Anonymous,
+    /// The subject of a verified token
+    User(String),
+}
+
+/// Verifies the bearer JWT in `authorization` and records the caller in the
+/// request extensions for handlers to check against the `user_id` they act
+/// on. Without a verifier every call passes through untouched.
+#[derive(Debug, Clone, Default)]
+pub struct AuthInterceptor {
+    verifier: Option<JwtVerifier>,
+    // Reject calls without a token instead of marking them anonymous
+    require_token: bool,
+}
+
+impl AuthInterceptor {
+    pub fn new(verifier: Option<JwtVerifier>) -> Self {
+        Self {
+            verifier,
+            require_token: false,
+        }
+    }
+
+    /// Require a valid token on every call, not only on writes
+    pub fn require_token(mut self, require: bool) -> Self {
+        self.require_token = require;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.verifier.is_some()
+    }
+
+    /// The caller identified by `metadata`, or the status to reject it with
+    pub fn authenticate(&self, metadata: &MetadataMap) -> Result<Option<Caller>, Status> {
+        let Some(verifier) = &self.verifier else {
+            return Ok(None);
+        };
+
+        match bearer_token(metadata)? {
+            Some(token) => match verifier.verify(token) {
+                Ok(subject) => Ok(Some(Caller::User(subject))),
+                Err(e) => {
+                    debug!("Rejected bearer token: {}", e);
+                    Err(Status::unauthenticated("Invalid bearer token"))
+                }
+            },
+            None if self.require_token => Err(Status::unauthenticated("Missing bearer token")),
+            None => Ok(Some(Caller::Anonymous)),
+        }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(caller) = self.authenticate(request.metadata())? {
+            request.extensions_mut().insert(caller);
+        }
+        Ok(request)
+    }
+}
+
+// `authorization: Bearer <token>`; any other scheme is rejected
+fn bearer_token(metadata: &MetadataMap) -> Result<Option<&str>, Status> {
+    let Some(value) = metadata.get("authorization") else {
+        return Ok(None);
+    };
+
+    value
+        .to_str()
+        .ok()
+        .and_then(|value| {
+            value
+                .strip_prefix("Bearer ")
+                .or_else(|| value.strip_prefix("bearer "))
+        })
+        .map(|token| Some(token.trim()))
+        .ok_or_else(|| Status::unauthenticated("Authorization must be a bearer token"))
+}
+
+/// Allow a write on behalf of `user_id` only when the verified caller is
+/// that user, comparing both as normalized by `user_ids`. Requests that did
+/// not pass through an enabled [`AuthInterceptor`] are allowed, so auth
+/// stays optional.
+pub fn authorize<T>(
+    request: &Request<T>,
+    user_id: &str,
+    user_ids: &UserIdNormalizer,
+) -> Result<(), Status> {
+    match request.extensions().get::<Caller>() {
+        None => Ok(()),
+        Some(Caller::User(subject))
+            if user_ids.normalize(subject) == user_ids.normalize(user_id) =>
+        {
+            Ok(())
+        }
+        Some(Caller::User(_)) => Err(Status::permission_denied(
+            "Token subject does not match user_id",
+        )),
+        Some(Caller::Anonymous) => Err(Status::unauthenticated(
+            "A bearer token is required to act on behalf of a user",
+        )),
+    }
+}
@@ -0,0 +1,236 @@
+use crate::config::Config;
+use anyhow::{Result, anyhow};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// Minimum time between JWKS fetches triggered by tokens with unknown key IDs
+const JWKS_REFRESH_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("invalid token: {0}")]
+    Invalid(#[from] jsonwebtoken::errors::Error),
+
+    #[error("token has no key id")]
+    MissingKeyId,
+
+    #[error("unknown signing key '{0}'")]
+    UnknownKey(String),
+
+    #[error("token has no subject")]
+    MissingSubject,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    #[serde(default)]
+    sub: String,
+}
+
+/// Verifies bearer JWTs and extracts their subject. Keys come either from a
+/// single configured key or from a JWKS endpoint, refetched when a token
+/// names a key that is not known yet.
+#[derive(Clone)]
+pub struct JwtVerifier {
+    keys: Arc<Keys>,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+enum Keys {
+    Static {
+        key: DecodingKey,
+        algorithm: Algorithm,
+    },
+    Jwks(Jwks),
+}
+
+struct Jwks {
+    url: String,
+    http: reqwest::Client,
+    // Shared with background refreshes
+    keys: Arc<RwLock<HashMap<String, DecodingKey>>>,
+    last_refresh: Mutex<Instant>,
+}
+
+impl fmt::Debug for JwtVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = match &*self.keys {
+            Keys::Static { algorithm, .. } => format!("static {:?}", algorithm),
+            Keys::Jwks(jwks) => format!("jwks {}", jwks.url),
+        };
+        f.debug_struct("JwtVerifier")
+            .field("keys", &keys)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .finish()
+    }
+}
+
+impl JwtVerifier {
+    /// Verify HS256 tokens signed with a shared secret
+    pub fn with_secret(secret: &[u8]) -> Self {
+        Self::with_key(DecodingKey::from_secret(secret), Algorithm::HS256)
+    }
+
+    /// Verify RS256 tokens against a PEM-encoded RSA public key
+    pub fn with_rsa_pem(pem: &[u8]) -> Result<Self> {
+        let key =
+            DecodingKey::from_rsa_pem(pem).map_err(|e| anyhow!("Invalid RSA public key: {}", e))?;
+        Ok(Self::with_key(key, Algorithm::RS256))
+    }
+
+    fn with_key(key: DecodingKey, algorithm: Algorithm) -> Self {
+        Self {
+            keys: Arc::new(Keys::Static { key, algorithm }),
+            issuer: None,
+            audience: None,
+        }
+    }
+
+    /// Verify tokens against the keys published at `url`, fetched now
+    pub async fn with_jwks(url: String) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        let keys = fetch_jwks(&http, &url).await?;
+        info!("Loaded {} signing keys from {}", keys.len(), url);
+
+        Ok(Self {
+            keys: Arc::new(Keys::Jwks(Jwks {
+                url,
+                http,
+                keys: Arc::new(RwLock::new(keys)),
+                last_refresh: Mutex::new(Instant::now()),
+            })),
+            issuer: None,
+            audience: None,
+        })
+    }
+
+    /// Only accept tokens whose `iss` claim is `issuer`
+    pub fn with_issuer(mut self, issuer: String) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    /// Only accept tokens whose `aud` claim includes `audience`
+    pub fn with_audience(mut self, audience: String) -> Self {
+        self.audience = Some(audience);
+        self
+    }
+
+    /// Build the verifier described by the configuration; `None` when
+    /// neither `JWT_SIGNING_KEY` nor `JWT_JWKS_URL` is set
+    pub async fn from_config(config: &Config) -> Result<Option<Self>> {
+        let verifier = match (&config.jwt_signing_key, &config.jwt_jwks_url) {
+            (Some(key), _) if key.trim_start().starts_with("-----BEGIN") => {
+                Self::with_rsa_pem(key.as_bytes())?
+            }
+            (Some(key), _) => Self::with_secret(key.as_bytes()),
+            (None, Some(url)) => Self::with_jwks(url.clone()).await?,
+            (None, None) => return Ok(None),
+        };
+
+        let verifier = match &config.jwt_issuer {
+            Some(issuer) => verifier.with_issuer(issuer.clone()),
+            None => verifier,
+        };
+        Ok(Some(match &config.jwt_audience {
+            Some(audience) => verifier.with_audience(audience.clone()),
+            None => verifier,
+        }))
+    }
+
+    /// Check the token's signature, expiry, issuer and audience, and return
+    /// its subject
+    pub fn verify(&self, token: &str) -> Result<String, AuthError> {
+        let (key, algorithm) = match &*self.keys {
+            Keys::Static { key, algorithm } => (key.clone(), *algorithm),
+            Keys::Jwks(jwks) => {
+                // The key type must match the algorithm, so a token cannot
+                // pass an RSA public key off as an HMAC secret
+                let header = decode_header(token)?;
+                let kid = header.kid.ok_or(AuthError::MissingKeyId)?;
+                let key = jwks.key(&kid).ok_or(AuthError::UnknownKey(kid))?;
+                (key, header.alg)
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let claims = decode::<Claims>(token, &key, &validation)?.claims;
+        if claims.sub.is_empty() {
+            return Err(AuthError::MissingSubject);
+        }
+        Ok(claims.sub)
+    }
+}
+
+impl Jwks {
+    // A miss schedules a refetch, so keys rotated in after startup are
+    // picked up by the next request
+    fn key(&self, kid: &str) -> Option<DecodingKey> {
+        if let Some(key) = self.keys.read().unwrap().get(kid) {
+            return Some(key.clone());
+        }
+        self.refresh_in_background();
+        None
+    }
+
+    fn refresh_in_background(&self) {
+        {
+            let mut last_refresh = self.last_refresh.lock().unwrap();
+            if last_refresh.elapsed() < JWKS_REFRESH_COOLDOWN {
+                return;
+            }
+            *last_refresh = Instant::now();
+        }
+
+        let http = self.http.clone();
+        let url = self.url.clone();
+        let keys = self.keys.clone();
+        tokio::spawn(async move {
+            match fetch_jwks(&http, &url).await {
+                Ok(fetched) => {
+                    info!("Refreshed {} signing keys from {}", fetched.len(), url);
+                    *keys.write().unwrap() = fetched;
+                }
+                Err(e) => warn!("Failed to refresh signing keys from {}: {}", url, e),
+            }
+        });
+    }
+}
+
+async fn fetch_jwks(http: &reqwest::Client, url: &str) -> Result<HashMap<String, DecodingKey>> {
+    let set: JwkSet = http
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(set
+        .keys
+        .iter()
+        .filter_map(|jwk| {
+            let kid = jwk.common.key_id.clone()?;
+            DecodingKey::from_jwk(jwk).ok().map(|key| (kid, key))
+        })
+        .collect())
+}
@@ -0,0 +1,5 @@
+pub mod interceptor;
+pub mod jwt;
+
+pub use interceptor::{AuthInterceptor, Caller, authorize};
+pub use jwt::{AuthError, JwtVerifier};
@@ -0,0 +1,166 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use tonic::{Code, Status};
+use tracing::{info, warn};
+
+/// Whether a client error means the dependency itself is unhealthy (circuit
+/// open, unreachable, timed out, failing), as opposed to a genuine answer
+/// such as NOT_FOUND
+pub fn is_dependency_failure(error: &anyhow::Error) -> bool {
+    error.is::<CircuitOpenError>()
+        || error
+            .downcast_ref::<Status>()
+            .is_some_and(CircuitBreaker::is_failure)
+}
+
+/// Returned instead of calling a dependency while its circuit is open
+#[derive(Debug, Error)]
+#[error("{service} service is unavailable, failing fast after repeated errors")]
+pub struct CircuitOpenError {
+    pub service: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerSettings {
+    /// Consecutive failures within `window` that open the circuit
+    pub failure_threshold: u32,
+    pub window: Duration,
+    /// How long the circuit stays open before a probe call is let through
+    pub cooldown: Duration,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed {
+        failures: u32,
+        first_failure: Option<Instant>,
+    },
+    Open {
+        until: Instant,
+    },
+    // A single probe call is in flight; everyone else keeps failing fast
+    HalfOpen {
+        probe_started: Instant,
+    },
+}
+
+/// Consecutive-failure circuit breaker shared by all clones of a client
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    service: &'static str,
+    settings: CircuitBreakerSettings,
+    state: Arc<Mutex<State>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(service: &'static str, settings: CircuitBreakerSettings) -> Self {
+        Self {
+            service,
+            settings,
+            state: Arc::new(Mutex::new(State::Closed {
+                failures: 0,
+                first_failure: None,
+            })),
+        }
+    }
+
+    /// Check whether a call may go out right now
+    pub fn acquire(&self) -> Result<(), CircuitOpenError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now >= until => {
+                info!("{} service circuit half-open, probing", self.service);
+                *state = State::HalfOpen { probe_started: now };
+                Ok(())
+            }
+            // A probe that never reported back (e.g. was cancelled) is replaced
+            State::HalfOpen { probe_started }
+                if now.duration_since(probe_started) >= self.settings.cooldown =>
+            {
+                *state = State::HalfOpen { probe_started: now };
+                Ok(())
+            }
+            State::Open { .. } | State::HalfOpen { .. } => Err(CircuitOpenError {
+                service: self.service,
+            }),
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if matches!(*state, State::HalfOpen { .. }) {
+            info!("{} service recovered, circuit closed", self.service);
+        }
+
+        *state = State::Closed {
+            failures: 0,
+            first_failure: None,
+        };
+    }
+
+    /// Record a call that ended with `status`. Only errors that point at an
+    /// unhealthy dependency count as failures; e.g. NOT_FOUND is a healthy answer.
+    pub fn record_error(&self, status: &Status) {
+        if !Self::is_failure(status) {
+            self.record_success();
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        let (failures, first_failure) = match *state {
+            State::Closed {
+                failures,
+                first_failure: Some(first_failure),
+            } if now.duration_since(first_failure) < self.settings.window => {
+                (failures + 1, first_failure)
+            }
+            State::Closed { .. } => (1, now),
+            State::HalfOpen { .. } => {
+                warn!("{} service probe failed, circuit re-opened", self.service);
+                *state = State::Open {
+                    until: now + self.settings.cooldown,
+                };
+                return;
+            }
+            State::Open { .. } => return,
+        };
+
+        *state = if failures >= self.settings.failure_threshold {
+            warn!(
+                "{} service failed {} times in a row, circuit open for {}s",
+                self.service,
+                failures,
+                self.settings.cooldown.as_secs()
+            );
+            State::Open {
+                until: now + self.settings.cooldown,
+            }
+        } else {
+            State::Closed {
+                failures,
+                first_failure: Some(first_failure),
+            }
+        };
+    }
+
+    pub(crate) fn is_failure(status: &Status) -> bool {
+        matches!(
+            status.code(),
+            Code::Unavailable
+                | Code::DeadlineExceeded
+                | Code::Cancelled
+                | Code::Unknown
+                | Code::Internal
+                | Code::ResourceExhausted
+        )
+    }
+}
@@ -1,5 +1,11 @@
+pub mod circuit_breaker;
 pub mod post_client;
 pub mod user_client;
+pub mod validator;
 
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitBreakerSettings, CircuitOpenError, is_dependency_failure,
+};
 pub use post_client::{PostClient, PostClientPool, PostMetadata};
 pub use user_client::{UserClient, UserClientPool};
+pub use validator::{PostValidator, UserValidator};
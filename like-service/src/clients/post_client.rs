@@ -1,21 +1,53 @@
+use super::{CircuitBreaker, CircuitBreakerSettings, CircuitOpenError, is_dependency_failure};
 use crate::proto::post::{GetPostRequest, GetPostResponse, post_service_client::PostServiceClient};
+use crate::telemetry::{inject_request_id, inject_trace_context, record_cache_lookup};
 use anyhow::{Result, anyhow};
-use tonic::transport::{Channel, Endpoint};
+use futures::{StreamExt, stream};
+use moka::future::Cache;
+use std::time::Duration;
+use tonic::{
+    codec::CompressionEncoding,
+    transport::{Channel, ClientTlsConfig, Endpoint},
+};
 use tracing::{debug, error, info, warn};
 
+// Post Service has no batch RPC; posts of a batch are fetched this many at a time
+const POST_FETCH_CONCURRENCY: usize = 16;
+
 #[derive(Debug, Clone)]
 pub struct PostClient {
     client: PostServiceClient<Channel>,
+    // Post IDs recently confirmed to exist; shared by all clones
+    exists_cache: Option<Cache<u32, ()>>,
+    // Upper bound for each outbound call, e.g. the caller's remaining deadline
+    call_timeout: Option<Duration>,
+    // Fails calls fast while the service is unhealthy; shared by all clones
+    breaker: Option<CircuitBreaker>,
 }
 
 impl PostClient {
     /// Create a new PostClient with the given service URL
     pub async fn new(service_url: String) -> Result<Self> {
-        info!("Connecting to post service at: {}", service_url);
+        Self::new_with_tls(service_url, None).await
+    }
 
-        let endpoint = Endpoint::from_shared(service_url)
+    /// Create a new PostClient, optionally connecting over TLS
+    pub async fn new_with_tls(service_url: String, tls: Option<ClientTlsConfig>) -> Result<Self> {
+        info!(
+            "Connecting to post service at: {} (tls: {})",
+            service_url,
+            tls.is_some()
+        );
+
+        let mut endpoint = Endpoint::from_shared(service_url)
             .map_err(|e| anyhow!("Invalid endpoint URL: {}", e))?;
 
+        if let Some(tls) = tls {
+            endpoint = endpoint
+                .tls_config(tls)
+                .map_err(|e| anyhow!("Invalid TLS configuration for post service: {}", e))?;
+        }
+
         let channel = endpoint
             .connect()
             .await
@@ -24,7 +56,12 @@ impl PostClient {
         let client = PostServiceClient::new(channel);
 
         info!("Successfully connected to post service");
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            exists_cache: None,
+            call_timeout: None,
+            breaker: None,
+        })
     }
 
     /// Create a new PostClient with custom channel configuration
@@ -46,7 +83,80 @@ impl PostClient {
         let client = PostServiceClient::new(channel);
 
         info!("Successfully connected to post service with custom config");
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            exists_cache: None,
+            call_timeout: None,
+            breaker: None,
+        })
+    }
+
+    /// Memoize positive `post_exists` results for `ttl`.
+    /// Misses are never cached, so newly created posts are seen immediately.
+    pub fn with_existence_cache(mut self, ttl: Duration, capacity: u64) -> Self {
+        self.exists_cache = Some(
+            Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(ttl)
+                .build(),
+        );
+        self
+    }
+
+    /// Stop calling the post service for a while after repeated failures
+    pub fn with_circuit_breaker(mut self, settings: CircuitBreakerSettings) -> Self {
+        self.breaker = Some(CircuitBreaker::new("post", settings));
+        self
+    }
+
+    fn acquire(&self) -> Result<(), CircuitOpenError> {
+        match &self.breaker {
+            Some(breaker) => breaker.acquire(),
+            None => Ok(()),
+        }
+    }
+
+    fn record<T>(&self, result: &std::result::Result<T, tonic::Status>) {
+        if let Some(breaker) = &self.breaker {
+            match result {
+                Ok(_) => breaker.record_success(),
+                Err(status) => breaker.record_error(status),
+            }
+        }
+    }
+
+    /// Gzip requests to the post service and accept gzipped responses.
+    /// The post service must support gzip, or calls fail with `UNIMPLEMENTED`.
+    pub fn with_compression(mut self) -> Self {
+        self.client = self
+            .client
+            .send_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Gzip);
+        self
+    }
+
+    /// Bound every call made through this client by `timeout`.
+    /// The timeout is sent downstream as `grpc-timeout` and the call is cancelled once it passes.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.call_timeout = timeout;
+        self
+    }
+
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(timeout) = self.call_timeout {
+            request.set_timeout(timeout);
+        }
+        inject_trace_context(request.metadata_mut());
+        inject_request_id(request.metadata_mut());
+        request
+    }
+
+    /// Drop a cached existence result, e.g. after the post was deleted
+    pub async fn invalidate_post(&self, post_id: u32) {
+        if let Some(cache) = &self.exists_cache {
+            cache.invalidate(&post_id).await;
+        }
     }
 
     /// Get post by ID
@@ -57,9 +167,13 @@ impl PostClient {
             return Err(anyhow!("Post ID must be a positive integer"));
         }
 
-        let request = tonic::Request::new(GetPostRequest { post_id });
+        let request = self.request(GetPostRequest { post_id });
+
+        self.acquire()?;
+        let result = self.client.get_post(request).await;
+        self.record(&result);
 
-        match self.client.get_post(request).await {
+        match result {
             Ok(response) => {
                 let post_response = response.into_inner();
 
@@ -77,17 +191,35 @@ impl PostClient {
             }
             Err(status) => {
                 error!("gRPC error while fetching post {}: {:?}", post_id, status);
-                Err(anyhow!("Failed to get post: {}", status.message()))
+                // Keep the status so callers can tell an outage from a missing post
+                let message = format!("Failed to get post: {}", status.message());
+                Err(anyhow::Error::new(status).context(message))
             }
         }
     }
 
     /// Check if post exists (convenience method)
     pub async fn post_exists(&mut self, post_id: u32) -> Result<bool> {
-        match self.get_post(post_id).await {
-            Ok(response) => Ok(response.success),
-            Err(_) => Ok(false),
+        if let Some(cache) = &self.exists_cache {
+            let hit = cache.contains_key(&post_id);
+            record_cache_lookup("post", hit);
+            if hit {
+                debug!("Post {} found in existence cache", post_id);
+                return Ok(true);
+            }
         }
+
+        let exists = match self.get_post(post_id).await {
+            Ok(response) => response.success,
+            Err(e) if is_dependency_failure(&e) => return Err(e),
+            Err(_) => false, // e.g. NOT_FOUND: the post doesn't exist
+        };
+
+        if let Some(cache) = self.exists_cache.as_ref().filter(|_| exists) {
+            cache.insert(post_id, ()).await;
+        }
+
+        Ok(exists)
     }
 
     /// Get post safely with error handling
@@ -123,9 +255,11 @@ impl PostClient {
 
     /// Health check method to verify connection
     pub async fn health_check(&mut self) -> bool {
-        // Try to make a request with a dummy post ID to test connectivity
+        // Try to make a request with a dummy post ID to test connectivity;
+        // NOT_FOUND still means the service answered
         match self.get_post(1).await {
             Ok(_) => true,
+            Err(e) if !is_dependency_failure(&e) => true,
             Err(e) => {
                 error!("Health check failed: {}", e);
                 false
@@ -133,19 +267,30 @@ impl PostClient {
         }
     }
 
+    /// Fetch many posts concurrently. Results follow the order of `post_ids`,
+    /// with `None` for any post that is missing or could not be fetched.
+    pub async fn get_posts(&self, post_ids: &[u32]) -> Vec<Option<crate::proto::post::Post>> {
+        let mut results = vec![None; post_ids.len()];
+
+        let mut fetches = stream::iter(post_ids.iter().copied().enumerate())
+            .map(|(index, post_id)| {
+                let mut client = self.clone();
+                async move { (index, client.get_post_safe(post_id).await) }
+            })
+            .buffer_unordered(POST_FETCH_CONCURRENCY);
+        while let Some((index, post)) = fetches.next().await {
+            results[index] = post;
+        }
+
+        results
+    }
+
     /// Batch get posts (if you need to fetch multiple posts)
     pub async fn get_posts_batch(
         &mut self,
         post_ids: Vec<u32>,
     ) -> Vec<Option<crate::proto::post::Post>> {
-        let mut results = Vec::new();
-
-        for post_id in post_ids {
-            let post = self.get_post_safe(post_id).await;
-            results.push(post);
-        }
-
-        results
+        self.get_posts(&post_ids).await
     }
 }
 
@@ -189,19 +334,7 @@ impl PostClientPool {
 impl PostClient {
     /// Extract post metadata without full content (useful for listings)
     pub async fn get_post_metadata(&mut self, post_id: u32) -> Option<PostMetadata> {
-        match self.get_post_safe(post_id).await {
-            Some(post) => Some(PostMetadata {
-                id: post.id,
-                title: post.title,
-                author_id: post.user_id,
-                content_preview: if post.content.len() > 100 {
-                    format!("{}...", &post.content[..100])
-                } else {
-                    post.content
-                },
-            }),
-            None => None,
-        }
+        self.get_post_safe(post_id).await.map(PostMetadata::from)
     }
 }
 
@@ -213,3 +346,18 @@ pub struct PostMetadata {
     pub author_id: String,
     pub content_preview: String,
 }
+
+impl From<crate::proto::post::Post> for PostMetadata {
+    fn from(post: crate::proto::post::Post) -> Self {
+        Self {
+            id: post.id,
+            title: post.title,
+            author_id: post.user_id,
+            content_preview: if post.content.len() > 100 {
+                format!("{}...", &post.content[..100])
+            } else {
+                post.content
+            },
+        }
+    }
+}
@@ -1,21 +1,49 @@
+use super::{CircuitBreaker, CircuitBreakerSettings, CircuitOpenError, is_dependency_failure};
 use crate::proto::user::{GetUserRequest, GetUserResponse, user_service_client::UserServiceClient};
+use crate::telemetry::{inject_request_id, inject_trace_context, record_cache_lookup};
 use anyhow::{Result, anyhow};
-use tonic::transport::{Channel, Endpoint};
+use moka::future::Cache;
+use std::time::Duration;
+use tonic::{
+    codec::CompressionEncoding,
+    transport::{Channel, ClientTlsConfig, Endpoint},
+};
 use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone)]
 pub struct UserClient {
     client: UserServiceClient<Channel>,
+    // User IDs recently confirmed to exist; shared by all clones
+    exists_cache: Option<Cache<String, ()>>,
+    // Upper bound for each outbound call, e.g. the caller's remaining deadline
+    call_timeout: Option<Duration>,
+    // Fails calls fast while the service is unhealthy; shared by all clones
+    breaker: Option<CircuitBreaker>,
 }
 
 impl UserClient {
     /// Create a new UserClient with the given service URL
     pub async fn new(service_url: String) -> Result<Self> {
-        info!("Connecting to user service at: {}", service_url);
+        Self::new_with_tls(service_url, None).await
+    }
 
-        let endpoint = Endpoint::from_shared(service_url)
+    /// Create a new UserClient, optionally connecting over TLS
+    pub async fn new_with_tls(service_url: String, tls: Option<ClientTlsConfig>) -> Result<Self> {
+        info!(
+            "Connecting to user service at: {} (tls: {})",
+            service_url,
+            tls.is_some()
+        );
+
+        let mut endpoint = Endpoint::from_shared(service_url)
             .map_err(|e| anyhow!("Invalid endpoint URL: {}", e))?;
 
+        if let Some(tls) = tls {
+            endpoint = endpoint
+                .tls_config(tls)
+                .map_err(|e| anyhow!("Invalid TLS configuration for user service: {}", e))?;
+        }
+
         let channel = endpoint
             .connect()
             .await
@@ -24,7 +52,12 @@ impl UserClient {
         let client = UserServiceClient::new(channel);
 
         info!("Successfully connected to user service");
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            exists_cache: None,
+            call_timeout: None,
+            breaker: None,
+        })
     }
 
     /// Create a new UserClient with custom channel configuration
@@ -46,7 +79,80 @@ impl UserClient {
         let client = UserServiceClient::new(channel);
 
         info!("Successfully connected to user service with custom config");
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            exists_cache: None,
+            call_timeout: None,
+            breaker: None,
+        })
+    }
+
+    /// Memoize positive `user_exists` results for `ttl`.
+    /// Misses are never cached, so newly created users are seen immediately.
+    pub fn with_existence_cache(mut self, ttl: Duration, capacity: u64) -> Self {
+        self.exists_cache = Some(
+            Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(ttl)
+                .build(),
+        );
+        self
+    }
+
+    /// Stop calling the user service for a while after repeated failures
+    pub fn with_circuit_breaker(mut self, settings: CircuitBreakerSettings) -> Self {
+        self.breaker = Some(CircuitBreaker::new("user", settings));
+        self
+    }
+
+    fn acquire(&self) -> Result<(), CircuitOpenError> {
+        match &self.breaker {
+            Some(breaker) => breaker.acquire(),
+            None => Ok(()),
+        }
+    }
+
+    fn record<T>(&self, result: &std::result::Result<T, tonic::Status>) {
+        if let Some(breaker) = &self.breaker {
+            match result {
+                Ok(_) => breaker.record_success(),
+                Err(status) => breaker.record_error(status),
+            }
+        }
+    }
+
+    /// Gzip requests to the user service and accept gzipped responses.
+    /// The user service must support gzip, or calls fail with `UNIMPLEMENTED`.
+    pub fn with_compression(mut self) -> Self {
+        self.client = self
+            .client
+            .send_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Gzip);
+        self
+    }
+
+    /// Bound every call made through this client by `timeout`.
+    /// The timeout is sent downstream as `grpc-timeout` and the call is cancelled once it passes.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.call_timeout = timeout;
+        self
+    }
+
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(timeout) = self.call_timeout {
+            request.set_timeout(timeout);
+        }
+        inject_trace_context(request.metadata_mut());
+        inject_request_id(request.metadata_mut());
+        request
+    }
+
+    /// Drop a cached existence result, e.g. after the user was deleted
+    pub async fn invalidate_user(&self, user_id: &str) {
+        if let Some(cache) = &self.exists_cache {
+            cache.invalidate(user_id).await;
+        }
     }
 
     /// Get user by ID
@@ -57,11 +163,15 @@ impl UserClient {
             return Err(anyhow!("User ID cannot be empty"));
         }
 
-        let request = tonic::Request::new(GetUserRequest {
+        let request = self.request(GetUserRequest {
             user_id: user_id.clone(),
         });
 
-        match self.client.get_user(request).await {
+        self.acquire()?;
+        let result = self.client.get_user(request).await;
+        self.record(&result);
+
+        match result {
             Ok(response) => {
                 let user_response = response.into_inner();
 
@@ -79,7 +189,9 @@ impl UserClient {
             }
             Err(status) => {
                 error!("gRPC error while fetching user {}: {:?}", user_id, status);
-                Err(anyhow!("Failed to get user: {}", status.message()))
+                // Keep the status so callers can tell an outage from a missing user
+                let message = format!("Failed to get user: {}", status.message());
+                Err(anyhow::Error::new(status).context(message))
             }
         }
     }
@@ -91,11 +203,15 @@ impl UserClient {
             return Err(anyhow!("User ID cannot be empty"));
         }
 
-        let request = tonic::Request::new(GetUserRequest {
+        let request = self.request(GetUserRequest {
             user_id: user_id.clone(),
         });
 
-        match self.client.get_local_user(request).await {
+        self.acquire()?;
+        let result = self.client.get_local_user(request).await;
+        self.record(&result);
+
+        match result {
             Ok(response) => {
                 let user_response = response.into_inner();
 
@@ -116,27 +232,45 @@ impl UserClient {
                     "gRPC error while fetching local user {}: {:?}",
                     user_id, status
                 );
-                Err(anyhow!("Failed to get local user: {}", status.message()))
+                let message = format!("Failed to get local user: {}", status.message());
+                Err(anyhow::Error::new(status).context(message))
             }
         }
     }
 
     /// Check if user exists (convenience method)
     pub async fn user_exists(&mut self, user_id: String) -> Result<bool> {
+        if let Some(cache) = &self.exists_cache {
+            let hit = cache.contains_key(&user_id);
+            record_cache_lookup("user", hit);
+            if hit {
+                debug!("User {} found in existence cache", user_id);
+                return Ok(true);
+            }
+        }
+
         // Check if it's a Clerk ID
-        if user_id.starts_with("user_") {
+        let exists = if user_id.starts_with("user_") {
             // Remote fetch by Clerk ID
-            match self.get_user(user_id).await {
-                Ok(response) => Ok(response.success && response.user.is_some()),
-                Err(_) => Ok(false), // Assume user doesn't exist if error
+            match self.get_user(user_id.clone()).await {
+                Ok(response) => response.success && response.user.is_some(),
+                Err(e) if is_dependency_failure(&e) => return Err(e),
+                Err(_) => false, // e.g. NOT_FOUND: the user doesn't exist
             }
         } else {
             // Local DB fetch by _id
-            match self.get_local_user(user_id).await {
-                Ok(response) => Ok(response.success && response.user.is_some()),
-                Err(_) => Ok(false), // Assume user doesn't exist if error
+            match self.get_local_user(user_id.clone()).await {
+                Ok(response) => response.success && response.user.is_some(),
+                Err(e) if is_dependency_failure(&e) => return Err(e),
+                Err(_) => false, // e.g. NOT_FOUND: the user doesn't exist
             }
+        };
+
+        if let Some(cache) = self.exists_cache.as_ref().filter(|_| exists) {
+            cache.insert(user_id, ()).await;
         }
+
+        Ok(exists)
     }
 
     /// Get user safely with error handling
@@ -156,9 +290,11 @@ impl UserClient {
 
     /// Health check method to verify connection
     pub async fn health_check(&mut self) -> bool {
-        // Try to make a request with a dummy user ID to test connectivity
+        // Try to make a request with a dummy user ID to test connectivity;
+        // NOT_FOUND still means the service answered
         match self.get_user("health_check".to_string()).await {
             Ok(_) => true,
+            Err(e) if !is_dependency_failure(&e) => true,
             Err(e) => {
                 error!("Health check failed: {}", e);
                 false
@@ -0,0 +1,119 @@
+use super::{PostClient, PostMetadata, UserClient};
+use crate::proto::{post::Post, user::GetUserResponse};
+use anyhow::Result;
+use std::time::Duration;
+
+// Each call takes the time the caller has left; `None` leaves the call
+// bounded only by the client's own configuration.
+
+/// What the likes service needs from the User Service
+#[tonic::async_trait]
+pub trait UserValidator: std::fmt::Debug + Send + Sync {
+    /// Whether the user exists, by Clerk or database ID. Fails only when the
+    /// User Service could not answer.
+    async fn user_exists(&self, user_id: &str, timeout: Option<Duration>) -> Result<bool>;
+
+    /// Look up a user by Clerk ID
+    async fn get_user(&self, user_id: &str, timeout: Option<Duration>) -> Result<GetUserResponse>;
+
+    /// Forget anything cached about the user, e.g. after it was deleted
+    async fn invalidate_user(&self, user_id: &str);
+
+    /// Whether the User Service is reachable and answering
+    async fn health_check(&self, timeout: Option<Duration>) -> bool;
+}
+
+/// What the likes service needs from the Post Service
+#[tonic::async_trait]
+pub trait PostValidator: std::fmt::Debug + Send + Sync {
+    /// Whether the post exists. Fails only when the Post Service could not answer.
+    async fn post_exists(&self, post_id: u32, timeout: Option<Duration>) -> Result<bool>;
+
+    /// Whether `user_id` (a database ID) authored the post
+    async fn is_post_owner(
+        &self,
+        post_id: u32,
+        user_id: &str,
+        timeout: Option<Duration>,
+    ) -> Result<bool>;
+
+    /// Title and author of a post; `None` when it cannot be fetched
+    async fn get_post_metadata(
+        &self,
+        post_id: u32,
+        timeout: Option<Duration>,
+    ) -> Option<PostMetadata>;
+
+    /// Fetch many posts, one result per ID in order; `None` for any that cannot be fetched
+    async fn get_posts_batch(
+        &self,
+        post_ids: Vec<u32>,
+        timeout: Option<Duration>,
+    ) -> Vec<Option<Post>>;
+
+    /// Whether the Post Service is reachable and answering
+    async fn health_check(&self, timeout: Option<Duration>) -> bool;
+}
+
+#[tonic::async_trait]
+impl UserValidator for UserClient {
+    async fn user_exists(&self, user_id: &str, timeout: Option<Duration>) -> Result<bool> {
+        let mut client = self.clone().with_timeout(timeout);
+        UserClient::user_exists(&mut client, user_id.to_string()).await
+    }
+
+    async fn get_user(&self, user_id: &str, timeout: Option<Duration>) -> Result<GetUserResponse> {
+        let mut client = self.clone().with_timeout(timeout);
+        UserClient::get_user(&mut client, user_id.to_string()).await
+    }
+
+    async fn invalidate_user(&self, user_id: &str) {
+        UserClient::invalidate_user(self, user_id).await
+    }
+
+    async fn health_check(&self, timeout: Option<Duration>) -> bool {
+        let mut client = self.clone().with_timeout(timeout);
+        UserClient::health_check(&mut client).await
+    }
+}
+
+#[tonic::async_trait]
+impl PostValidator for PostClient {
+    async fn post_exists(&self, post_id: u32, timeout: Option<Duration>) -> Result<bool> {
+        let mut client = self.clone().with_timeout(timeout);
+        PostClient::post_exists(&mut client, post_id).await
+    }
+
+    async fn is_post_owner(
+        &self,
+        post_id: u32,
+        user_id: &str,
+        timeout: Option<Duration>,
+    ) -> Result<bool> {
+        let mut client = self.clone().with_timeout(timeout);
+        PostClient::is_post_owner(&mut client, post_id, user_id).await
+    }
+
+    async fn get_post_metadata(
+        &self,
+        post_id: u32,
+        timeout: Option<Duration>,
+    ) -> Option<PostMetadata> {
+        let mut client = self.clone().with_timeout(timeout);
+        PostClient::get_post_metadata(&mut client, post_id).await
+    }
+
+    async fn get_posts_batch(
+        &self,
+        post_ids: Vec<u32>,
+        timeout: Option<Duration>,
+    ) -> Vec<Option<Post>> {
+        let client = self.clone().with_timeout(timeout);
+        PostClient::get_posts(&client, &post_ids).await
+    }
+
+    async fn health_check(&self, timeout: Option<Duration>) -> bool {
+        let mut client = self.clone().with_timeout(timeout);
+        PostClient::health_check(&mut client).await
+    }
+}
@@ -1,16 +1,94 @@
-use anyhow::Result;
+use super::ClientTlsPaths;
+use crate::{database::AuthScope, repository::MAX_DEMO_LIKES};
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::env;
+use std::{collections::HashMap, env};
+use tonic::transport::Uri;
+
+// Database URL prefixes understood by `Database::new`; empty selects in-memory
+const DATABASE_URL_PREFIXES: [&str; 3] = ["ws://", "wss://", "rocksdb://"];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub host: String,
     pub port: u16,
+    pub metrics_port: u16,
+    pub gateway_port: Option<u16>,
     pub database_url: String,
+    pub database_read_url: Option<String>,
+    pub db_namespace: String,
+    pub db_name: String,
+    pub db_pool_size: usize,
+    pub db_connect_retries: u32,
+    pub db_connect_retry_delay_seconds: u64,
+    pub db_reconnect_attempts: u32,
+    pub db_query_timeout_ms: u64,
+    pub db_read_your_writes: bool,
+    // `root`, `ns` or `db`: the level DB_USER is defined at
+    pub db_auth_scope: String,
+    pub allow_inmemory_db: bool,
+    pub rocksdb_block_cache_mb: Option<u64>,
+    pub rocksdb_write_buffer_mb: Option<u64>,
+    pub rocksdb_max_write_buffers: Option<u32>,
+    pub rocksdb_compression: Option<String>,
     pub environment: String,
     pub log_level: String,
+    // `json` or `text`; unset picks by environment
+    pub log_format: Option<String>,
     pub user_service_url: String,
     pub post_service_url: String,
+    pub kafka_brokers: Option<String>,
+    pub kafka_topic: String,
+    pub shutdown_grace_seconds: u64,
+    pub deleted_like_retention_days: i64,
+    pub seed_data: bool,
+    pub seed_data_count: usize,
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    pub tls_client_ca_path: Option<String>,
+    pub jwt_signing_key: Option<String>,
+    pub jwt_jwks_url: Option<String>,
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
+    pub auth_require_for_reads: bool,
+    pub user_service_tls: ClientTlsPaths,
+    pub post_service_tls: ClientTlsPaths,
+    pub existence_cache_ttl_seconds: u64,
+    pub existence_cache_capacity: u64,
+    pub user_id_cache_ttl_seconds: u64,
+    pub user_id_cache_capacity: u64,
+    pub post_likes_cache_ttl_seconds: u64,
+    pub post_likes_cache_capacity: u64,
+    pub write_buffer_window_ms: u64,
+    pub write_buffer_max_batch: usize,
+    pub watch_channel_capacity: usize,
+    pub validation_concurrency: usize,
+    pub lowercase_user_ids: bool,
+    pub lenient_post_validation: bool,
+    pub strict_startup: bool,
+    pub circuit_breaker_failure_threshold: u32,
+    pub circuit_breaker_window_seconds: u64,
+    pub circuit_breaker_cooldown_seconds: u64,
+    pub user_likes_default_limit: i32,
+    pub user_likes_max_limit: i32,
+    pub post_likes_default_limit: i32,
+    pub post_likes_max_limit: i32,
+    pub max_pagination_offset: i32,
+    pub idempotency_ttl_seconds: i64,
+    pub otel_exporter_otlp_endpoint: Option<String>,
+    pub rate_limit_per_second: f64,
+    pub rate_limit_burst: u32,
+    pub max_decoding_message_size: usize,
+    pub max_encoding_message_size: usize,
+    pub grpc_compression: bool,
+    pub tcp_keepalive_seconds: u64,
+    pub tcp_nodelay: bool,
+    pub http2_keepalive_interval_seconds: u64,
+    pub http2_keepalive_timeout_seconds: u64,
+    pub max_concurrent_requests: usize,
+    pub concurrency_queue_timeout_ms: u64,
+    pub default_method_timeout_ms: u64,
+    pub method_timeouts_ms: HashMap<String, u64>,
 }
 
 impl Config {
@@ -20,14 +98,510 @@ impl Config {
             port: env::var("PORT")
                 .unwrap_or_else(|_| "50053".to_string())
                 .parse()?,
+            metrics_port: env::var("METRICS_PORT")
+                .unwrap_or_else(|_| "9053".to_string())
+                .parse()?,
+            gateway_port: env::var("GATEWAY_PORT")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse())
+                .transpose()?,
             database_url: env::var("DATABASE_URL")
                 .unwrap_or_else(|_| "rocksdb://./data/likes.db".to_string()),
+            database_read_url: env::var("DATABASE_READ_URL").ok().filter(|v| !v.is_empty()),
+            db_namespace: env::var("DB_NAMESPACE").unwrap_or_else(|_| "likes_service".to_string()),
+            db_name: env::var("DB_NAME").unwrap_or_else(|_| "likes".to_string()),
+            db_pool_size: env::var("DB_POOL_SIZE")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()?,
+            db_connect_retries: env::var("DB_CONNECT_RETRIES")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
+            db_connect_retry_delay_seconds: env::var("DB_CONNECT_RETRY_DELAY")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()?,
+            db_reconnect_attempts: env::var("DB_RECONNECT_ATTEMPTS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()?,
+            db_query_timeout_ms: env::var("DB_QUERY_TIMEOUT_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()?,
+            db_read_your_writes: env::var("DB_READ_YOUR_WRITES")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()?,
+            db_auth_scope: env::var("DB_AUTH_SCOPE").unwrap_or_else(|_| "root".to_string()),
+            allow_inmemory_db: env::var("ALLOW_INMEMORY_DB")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            rocksdb_block_cache_mb: env::var("ROCKSDB_BLOCK_CACHE_MB")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse())
+                .transpose()?,
+            rocksdb_write_buffer_mb: env::var("ROCKSDB_WRITE_BUFFER_MB")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse())
+                .transpose()?,
+            rocksdb_max_write_buffers: env::var("ROCKSDB_MAX_WRITE_BUFFERS")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse())
+                .transpose()?,
+            rocksdb_compression: env::var("ROCKSDB_COMPRESSION")
+                .ok()
+                .filter(|v| !v.is_empty()),
             environment: env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string()),
             log_level: env::var("LOG_LEVEL").unwrap_or_else(|_| "debug".to_string()),
+            log_format: env::var("LOG_FORMAT").ok().filter(|v| !v.is_empty()),
             user_service_url: env::var("USER_SERVICE_URL")
                 .unwrap_or_else(|_| "http://localhost:50051".to_string()),
             post_service_url: env::var("POST_SERVICE_URL")
                 .unwrap_or_else(|_| "http://localhost:50052".to_string()),
+            kafka_brokers: env::var("KAFKA_BROKERS").ok().filter(|v| !v.is_empty()),
+            kafka_topic: env::var("KAFKA_TOPIC").unwrap_or_else(|_| "likes.events".to_string()),
+            shutdown_grace_seconds: env::var("SHUTDOWN_GRACE_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            deleted_like_retention_days: env::var("DELETED_LIKE_RETENTION_DAYS")
+                .unwrap_or_else(|_| "90".to_string())
+                .parse()?,
+            seed_data: env::var("SEED_DATA")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            seed_data_count: env::var("SEED_DATA_COUNT")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()?,
+            tls_cert_path: env::var("TLS_CERT_PATH").ok().filter(|v| !v.is_empty()),
+            tls_key_path: env::var("TLS_KEY_PATH").ok().filter(|v| !v.is_empty()),
+            tls_client_ca_path: env::var("TLS_CLIENT_CA_PATH")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            jwt_signing_key: env::var("JWT_SIGNING_KEY").ok().filter(|v| !v.is_empty()),
+            jwt_jwks_url: env::var("JWT_JWKS_URL").ok().filter(|v| !v.is_empty()),
+            jwt_issuer: env::var("JWT_ISSUER").ok().filter(|v| !v.is_empty()),
+            jwt_audience: env::var("JWT_AUDIENCE").ok().filter(|v| !v.is_empty()),
+            auth_require_for_reads: env::var("AUTH_REQUIRE_FOR_READS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            user_service_tls: ClientTlsPaths::from_env("USER_SERVICE"),
+            post_service_tls: ClientTlsPaths::from_env("POST_SERVICE"),
+            existence_cache_ttl_seconds: env::var("EXISTENCE_CACHE_TTL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            existence_cache_capacity: env::var("EXISTENCE_CACHE_CAPACITY")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()?,
+            user_id_cache_ttl_seconds: env::var("USER_ID_CACHE_TTL_SECONDS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()?,
+            user_id_cache_capacity: env::var("USER_ID_CACHE_CAPACITY")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()?,
+            post_likes_cache_ttl_seconds: env::var("POST_LIKES_CACHE_TTL_SECONDS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()?,
+            post_likes_cache_capacity: env::var("POST_LIKES_CACHE_CAPACITY")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()?,
+            write_buffer_window_ms: env::var("WRITE_BUFFER_WINDOW_MS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()?,
+            write_buffer_max_batch: env::var("WRITE_BUFFER_MAX_BATCH")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()?,
+            watch_channel_capacity: env::var("WATCH_CHANNEL_CAPACITY")
+                .unwrap_or_else(|_| "256".to_string())
+                .parse()?,
+            validation_concurrency: env::var("VALIDATION_CONCURRENCY")
+                .unwrap_or_else(|_| "16".to_string())
+                .parse()?,
+            lowercase_user_ids: env::var("LOWERCASE_USER_IDS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            lenient_post_validation: env::var("LENIENT_POST_VALIDATION")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            strict_startup: env::var("STRICT_STARTUP")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            circuit_breaker_failure_threshold: env::var("CIRCUIT_BREAKER_FAILURE_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
+            circuit_breaker_window_seconds: env::var("CIRCUIT_BREAKER_WINDOW_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            circuit_breaker_cooldown_seconds: env::var("CIRCUIT_BREAKER_COOLDOWN_SECONDS")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()?,
+            user_likes_default_limit: env::var("USER_LIKES_DEFAULT_LIMIT")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()?,
+            user_likes_max_limit: env::var("USER_LIKES_MAX_LIMIT")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()?,
+            post_likes_default_limit: env::var("POST_LIKES_DEFAULT_LIMIT")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()?,
+            post_likes_max_limit: env::var("POST_LIKES_MAX_LIMIT")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()?,
+            max_pagination_offset: env::var("MAX_PAGINATION_OFFSET")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()?,
+            idempotency_ttl_seconds: env::var("IDEMPOTENCY_TTL_SECONDS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()?,
+            otel_exporter_otlp_endpoint: env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            rate_limit_per_second: env::var("RATE_LIMIT_PER_SECOND")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
+            rate_limit_burst: env::var("RATE_LIMIT_BURST")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()?,
+            max_decoding_message_size: env::var("MAX_DECODING_MESSAGE_SIZE")
+                .unwrap_or_else(|_| "4194304".to_string())
+                .parse()?,
+            max_encoding_message_size: env::var("MAX_ENCODING_MESSAGE_SIZE")
+                .unwrap_or_else(|_| "4194304".to_string())
+                .parse()?,
+            grpc_compression: env::var("GRPC_COMPRESSION")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            tcp_keepalive_seconds: env::var("TCP_KEEPALIVE_SECONDS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()?,
+            tcp_nodelay: env::var("TCP_NODELAY")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()?,
+            http2_keepalive_interval_seconds: env::var("HTTP2_KEEPALIVE_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            http2_keepalive_timeout_seconds: env::var("HTTP2_KEEPALIVE_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()?,
+            max_concurrent_requests: env::var("MAX_CONCURRENT_REQUESTS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()?,
+            concurrency_queue_timeout_ms: env::var("CONCURRENCY_QUEUE_TIMEOUT_MS")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()?,
+            default_method_timeout_ms: env::var("DEFAULT_METHOD_TIMEOUT_MS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()?,
+            method_timeouts_ms: Self::parse_method_timeouts(
+                &env::var("METHOD_TIMEOUTS_MS").unwrap_or_default(),
+            )?,
         })
     }
+
+    pub fn is_development(&self) -> bool {
+        self.environment.eq_ignore_ascii_case("development")
+    }
+
+    /// Whether logs are written as JSON lines: as `LOG_FORMAT` says, else
+    /// only in production
+    pub fn json_logs(&self) -> bool {
+        match &self.log_format {
+            Some(format) => format.eq_ignore_ascii_case("json"),
+            None => self.environment.eq_ignore_ascii_case("production"),
+        }
+    }
+
+    /// Check every field and report all invalid ones at once
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if self.port == 0 {
+            errors.push("PORT must be non-zero".to_string());
+        }
+
+        if self.metrics_port == 0 {
+            errors.push("METRICS_PORT must be non-zero".to_string());
+        } else if self.metrics_port == self.port {
+            errors.push("METRICS_PORT must differ from PORT".to_string());
+        }
+
+        match self.gateway_port {
+            Some(0) => errors.push("GATEWAY_PORT must be non-zero".to_string()),
+            Some(port) if port == self.port || port == self.metrics_port => {
+                errors.push("GATEWAY_PORT must differ from PORT and METRICS_PORT".to_string())
+            }
+            _ => {}
+        }
+
+        if !self.database_url.is_empty()
+            && !DATABASE_URL_PREFIXES
+                .iter()
+                .any(|prefix| self.database_url.starts_with(prefix))
+        {
+            errors.push(format!(
+                "DATABASE_URL '{}' must start with one of {:?}, or be empty for in-memory",
+                self.database_url, DATABASE_URL_PREFIXES
+            ));
+        }
+
+        if let Some(read_url) = &self.database_read_url {
+            if !["ws://", "wss://"]
+                .iter()
+                .any(|prefix| read_url.starts_with(prefix))
+            {
+                errors.push(format!(
+                    "DATABASE_READ_URL '{}' must start with ws:// or wss://",
+                    read_url
+                ));
+            } else if !self.database_url.starts_with("ws") {
+                errors.push(
+                    "DATABASE_READ_URL requires a remote DATABASE_URL (ws:// or wss://)"
+                        .to_string(),
+                );
+            }
+        }
+
+        if self.log_level.parse::<tracing::Level>().is_err() {
+            errors.push(format!(
+                "LOG_LEVEL '{}' must be one of trace, debug, info, warn, error",
+                self.log_level
+            ));
+        }
+
+        if let Some(format) = &self.log_format {
+            if !["json", "text"].contains(&format.to_ascii_lowercase().as_str()) {
+                errors.push(format!("LOG_FORMAT '{}' must be one of json, text", format));
+            }
+        }
+
+        if self.db_namespace.trim().is_empty() {
+            errors.push("DB_NAMESPACE cannot be empty".to_string());
+        }
+
+        if self.db_name.trim().is_empty() {
+            errors.push("DB_NAME cannot be empty".to_string());
+        }
+
+        if self.db_pool_size == 0 {
+            errors.push("DB_POOL_SIZE must be at least 1".to_string());
+        }
+
+        if AuthScope::parse(&self.db_auth_scope).is_none() {
+            errors.push(format!(
+                "DB_AUTH_SCOPE '{}' must be one of root, ns, db",
+                self.db_auth_scope
+            ));
+        }
+
+        for (name, value) in [
+            ("ROCKSDB_BLOCK_CACHE_MB", self.rocksdb_block_cache_mb),
+            ("ROCKSDB_WRITE_BUFFER_MB", self.rocksdb_write_buffer_mb),
+            (
+                "ROCKSDB_MAX_WRITE_BUFFERS",
+                self.rocksdb_max_write_buffers.map(u64::from),
+            ),
+        ] {
+            if value == Some(0) {
+                errors.push(format!("{} must be non-zero when set", name));
+            }
+        }
+
+        if (self.db_connect_retries > 0 || self.db_reconnect_attempts > 0)
+            && self.db_connect_retry_delay_seconds == 0
+        {
+            errors.push(
+                "DB_CONNECT_RETRY_DELAY must be non-zero when retries are enabled".to_string(),
+            );
+        }
+
+        if self.tls_cert_path.is_some() != self.tls_key_path.is_some() {
+            errors.push("TLS_CERT_PATH and TLS_KEY_PATH must be set together".to_string());
+        }
+
+        if self.tls_client_ca_path.is_some() && self.tls_cert_path.is_none() {
+            errors.push("TLS_CLIENT_CA_PATH requires TLS_CERT_PATH and TLS_KEY_PATH".to_string());
+        }
+
+        if self.jwt_signing_key.is_some() && self.jwt_jwks_url.is_some() {
+            errors.push("Set only one of JWT_SIGNING_KEY and JWT_JWKS_URL".to_string());
+        }
+
+        if self.auth_require_for_reads
+            && self.jwt_signing_key.is_none()
+            && self.jwt_jwks_url.is_none()
+        {
+            errors.push(
+                "AUTH_REQUIRE_FOR_READS requires JWT_SIGNING_KEY or JWT_JWKS_URL".to_string(),
+            );
+        }
+
+        if self.circuit_breaker_failure_threshold > 0 {
+            if self.circuit_breaker_window_seconds == 0 {
+                errors.push("CIRCUIT_BREAKER_WINDOW_SECONDS must be non-zero".to_string());
+            }
+
+            if self.circuit_breaker_cooldown_seconds == 0 {
+                errors.push("CIRCUIT_BREAKER_COOLDOWN_SECONDS must be non-zero".to_string());
+            }
+        }
+
+        // Demo data must never reach a real database
+        if self.seed_data && !self.is_development() {
+            errors.push("SEED_DATA is only allowed with ENVIRONMENT=development".to_string());
+        }
+
+        if self.seed_data && !(1..=MAX_DEMO_LIKES).contains(&self.seed_data_count) {
+            errors.push(format!(
+                "SEED_DATA_COUNT must be between 1 and {}",
+                MAX_DEMO_LIKES
+            ));
+        }
+
+        if !self.rate_limit_per_second.is_finite() || self.rate_limit_per_second < 0.0 {
+            errors.push("RATE_LIMIT_PER_SECOND must be a non-negative number".to_string());
+        } else if self.rate_limit_per_second > 0.0 && self.rate_limit_burst == 0 {
+            errors.push("RATE_LIMIT_BURST must be at least 1".to_string());
+        }
+
+        if !(1..=64).contains(&self.validation_concurrency) {
+            errors.push("VALIDATION_CONCURRENCY must be between 1 and 64".to_string());
+        }
+
+        if self.write_buffer_window_ms > 1000 {
+            errors.push("WRITE_BUFFER_WINDOW_MS must be at most 1000".to_string());
+        }
+
+        if self.write_buffer_window_ms > 0 && !(1..=1000).contains(&self.write_buffer_max_batch) {
+            errors.push("WRITE_BUFFER_MAX_BATCH must be between 1 and 1000".to_string());
+        }
+
+        if !(1..=65536).contains(&self.watch_channel_capacity) {
+            errors.push("WATCH_CHANNEL_CAPACITY must be between 1 and 65536".to_string());
+        }
+
+        if self.max_decoding_message_size == 0 {
+            errors.push("MAX_DECODING_MESSAGE_SIZE must be non-zero".to_string());
+        }
+
+        if self.max_encoding_message_size == 0 {
+            errors.push("MAX_ENCODING_MESSAGE_SIZE must be non-zero".to_string());
+        }
+
+        for (name, seconds) in [
+            ("TCP_KEEPALIVE_SECONDS", self.tcp_keepalive_seconds),
+            (
+                "HTTP2_KEEPALIVE_INTERVAL_SECONDS",
+                self.http2_keepalive_interval_seconds,
+            ),
+            (
+                "HTTP2_KEEPALIVE_TIMEOUT_SECONDS",
+                self.http2_keepalive_timeout_seconds,
+            ),
+        ] {
+            if seconds == 0 {
+                errors.push(format!("{} must be positive", name));
+            }
+        }
+
+        if self.max_concurrent_requests > 0 && self.concurrency_queue_timeout_ms == 0 {
+            errors.push("CONCURRENCY_QUEUE_TIMEOUT_MS must be positive".to_string());
+        }
+
+        if self.idempotency_ttl_seconds < 1 {
+            errors.push("IDEMPOTENCY_TTL_SECONDS must be at least 1".to_string());
+        }
+
+        for (name, default_limit, max_limit) in [
+            (
+                "USER_LIKES",
+                self.user_likes_default_limit,
+                self.user_likes_max_limit,
+            ),
+            (
+                "POST_LIKES",
+                self.post_likes_default_limit,
+                self.post_likes_max_limit,
+            ),
+        ] {
+            if default_limit < 1 || default_limit > max_limit {
+                errors.push(format!(
+                    "{0}_DEFAULT_LIMIT must be between 1 and {0}_MAX_LIMIT ({1})",
+                    name, max_limit
+                ));
+            }
+        }
+
+        if self.max_pagination_offset < 0 {
+            errors.push("MAX_PAGINATION_OFFSET cannot be negative".to_string());
+        }
+
+        for (name, url, tls) in [
+            (
+                "USER_SERVICE",
+                &self.user_service_url,
+                &self.user_service_tls,
+            ),
+            (
+                "POST_SERVICE",
+                &self.post_service_url,
+                &self.post_service_tls,
+            ),
+        ] {
+            if let Err(reason) = Self::validate_service_url(url) {
+                errors.push(format!("{}_URL '{}' {}", name, url, reason));
+            }
+
+            if tls.cert_path.is_some() != tls.key_path.is_some() {
+                errors.push(format!(
+                    "{0}_CLIENT_CERT_PATH and {0}_CLIENT_KEY_PATH must be set together",
+                    name
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Invalid configuration:\n  - {}",
+                errors.join("\n  - ")
+            ))
+        }
+    }
+
+    // `GetLikesCount=200,ExportUserLikes=0`: milliseconds by RPC method name
+    fn parse_method_timeouts(value: &str) -> Result<HashMap<String, u64>> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (method, millis) = entry.split_once('=').ok_or_else(|| {
+                    anyhow!("METHOD_TIMEOUTS_MS entry {:?} must be Method=ms", entry)
+                })?;
+                let method = method.trim();
+                if method.is_empty() || !method.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(anyhow!(
+                        "METHOD_TIMEOUTS_MS entry {:?} must name an RPC method, e.g. GetLikesCount",
+                        entry
+                    ));
+                }
+                Ok((method.to_string(), millis.trim().parse()?))
+            })
+            .collect()
+    }
+
+    fn validate_service_url(url: &str) -> std::result::Result<(), &'static str> {
+        let uri: Uri = url.parse().map_err(|_| "is not a valid URI")?;
+
+        match uri.scheme_str() {
+            Some("http") | Some("https") => {}
+            _ => return Err("must use the http or https scheme"),
+        }
+
+        if uri.host().is_none() {
+            return Err("must include a host");
+        }
+
+        Ok(())
+    }
 }
@@ -1,2 +1,5 @@
 pub mod env;
+pub mod tls;
+
 pub use env::Config;
+pub use tls::{ClientTlsPaths, client_tls_config, server_tls_config};
@@ -0,0 +1,118 @@
+use super::Config;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+use tracing::info;
+
+/// PEM files used when connecting to a downstream service over TLS
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientTlsPaths {
+    pub ca_path: Option<String>,
+    pub cert_path: Option<String>,
+    pub key_path: Option<String>,
+}
+
+impl ClientTlsPaths {
+    /// Read `<PREFIX>_CA_PATH`, `<PREFIX>_CLIENT_CERT_PATH` and `<PREFIX>_CLIENT_KEY_PATH`
+    pub fn from_env(prefix: &str) -> Self {
+        let var = |name: &str| {
+            std::env::var(format!("{}_{}", prefix, name))
+                .ok()
+                .filter(|v| !v.is_empty())
+        };
+
+        Self {
+            ca_path: var("CA_PATH"),
+            cert_path: var("CLIENT_CERT_PATH"),
+            key_path: var("CLIENT_KEY_PATH"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ca_path.is_none() && self.cert_path.is_none() && self.key_path.is_none()
+    }
+}
+
+// Read a PEM file, failing with a message that names the setting it came from
+fn read_pem(path: &str, what: &str) -> Result<Vec<u8>> {
+    let pem =
+        std::fs::read(path).map_err(|e| anyhow!("Failed to read {} at {}: {}", what, path, e))?;
+
+    if !pem
+        .windows(b"-----BEGIN ".len())
+        .any(|w| w == b"-----BEGIN ")
+    {
+        return Err(anyhow!("{} at {} is not PEM encoded", what, path));
+    }
+
+    Ok(pem)
+}
+
+/// Build the server TLS settings, or `None` to serve plaintext
+pub fn server_tls_config(config: &Config) -> Result<Option<ServerTlsConfig>> {
+    let (cert_path, key_path) = match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (None, None) => return Ok(None),
+        _ => {
+            return Err(anyhow!(
+                "TLS_CERT_PATH and TLS_KEY_PATH must be set together"
+            ));
+        }
+    };
+
+    let identity = Identity::from_pem(
+        read_pem(cert_path, "TLS certificate")?,
+        read_pem(key_path, "TLS private key")?,
+    );
+    let mut tls = ServerTlsConfig::new().identity(identity);
+
+    // With a client CA configured, every client must present a certificate it signed
+    if let Some(ca_path) = &config.tls_client_ca_path {
+        let client_ca = Certificate::from_pem(read_pem(ca_path, "TLS client CA certificate")?);
+        tls = tls.client_ca_root(client_ca);
+        info!("Mutual TLS enabled, client certificates are required");
+    } else {
+        info!("TLS enabled");
+    }
+
+    Ok(Some(tls))
+}
+
+/// Build the TLS settings for a downstream client, or `None` for plaintext.
+/// TLS is enabled for `https://` URLs and whenever any TLS file is configured.
+pub fn client_tls_config(
+    service_url: &str,
+    paths: &ClientTlsPaths,
+) -> Result<Option<ClientTlsConfig>> {
+    if paths.is_empty() && !service_url.starts_with("https://") {
+        return Ok(None);
+    }
+
+    let mut tls = ClientTlsConfig::new();
+
+    tls = match &paths.ca_path {
+        Some(ca_path) => {
+            tls.ca_certificate(Certificate::from_pem(read_pem(ca_path, "CA certificate")?))
+        }
+        // No private CA: trust the bundled public roots
+        None => tls.with_enabled_roots(),
+    };
+
+    match (&paths.cert_path, &paths.key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            tls = tls.identity(Identity::from_pem(
+                read_pem(cert_path, "client certificate")?,
+                read_pem(key_path, "client private key")?,
+            ));
+        }
+        (None, None) => {}
+        _ => {
+            return Err(anyhow!(
+                "Client certificate and key for {} must be set together",
+                service_url
+            ));
+        }
+    }
+
+    Ok(Some(tls))
+}
@@ -0,0 +1,268 @@
+use super::Database;
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+/// A versioned schema change, applied at most once per database
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// All migrations in the order they are applied. Append new ones with the
+/// next version; never edit or reorder one that has shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        sql: include_str!("../../migrations/0001_initial_schema.surql"),
+    },
+    Migration {
+        version: 2,
+        name: "unvalidated_likes",
+        sql: include_str!("../../migrations/0002_unvalidated_likes.surql"),
+    },
+    Migration {
+        version: 3,
+        name: "like_versions",
+        sql: include_str!("../../migrations/0003_like_versions.surql"),
+    },
+];
+
+// Bookkeeping table recording which versions have been applied
+const MIGRATIONS_TABLE_SCHEMA: &str = r#"
+    DEFINE TABLE IF NOT EXISTS _migrations SCHEMAFULL;
+    DEFINE FIELD IF NOT EXISTS version ON TABLE _migrations TYPE int;
+    DEFINE FIELD IF NOT EXISTS name ON TABLE _migrations TYPE string;
+    DEFINE FIELD IF NOT EXISTS applied_at ON TABLE _migrations TYPE datetime DEFAULT time::now();
+    DEFINE INDEX IF NOT EXISTS _migrations_version ON TABLE _migrations COLUMNS version UNIQUE;
+"#;
+
+/// Apply every migration newer than the highest recorded version. Each one
+/// runs in a transaction together with its `_migrations` record, so a failed
+/// migration leaves nothing behind and is retried on the next start.
+pub async fn run(database: &Database) -> Result<()> {
+    let mut response = database.query(MIGRATIONS_TABLE_SCHEMA).await?;
+    if let Some(e) = first_error(&mut response) {
+        return Err(anyhow!("Failed to define _migrations table: {}", e));
+    }
+
+    let mut response = database
+        .query("SELECT VALUE version FROM _migrations")
+        .await?;
+    let applied: Vec<i64> = response.take(0)?;
+    let current = applied.into_iter().max().unwrap_or(0);
+
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version > current).collect();
+    if pending.is_empty() {
+        info!("Database schema is up to date (version {})", current);
+        return Ok(());
+    }
+
+    for migration in pending {
+        info!(
+            "Applying migration {} ({})",
+            migration.version, migration.name
+        );
+
+        let sql = format!(
+            "BEGIN TRANSACTION;\n{}\nCREATE _migrations SET version = $version, name = $name;\nCOMMIT TRANSACTION;",
+            migration.sql
+        );
+        let mut response = database
+            .query_builder(&sql)
+            .bind("version", migration.version)
+            .bind("name", migration.name)
+            .execute_with_anyhow()
+            .await?;
+
+        if let Some(e) = first_error(&mut response) {
+            return Err(anyhow!(
+                "Migration {} ({}) failed: {}",
+                migration.version,
+                migration.name,
+                e
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Table whose definitions `verify` checks against the migrations
+const LIKES_TABLE: &str = "likes";
+
+/// Differences between the `likes` table definitions in the database and
+/// those the migrations create
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaReport {
+    pub missing_fields: Vec<String>,
+    pub extra_fields: Vec<String>,
+    pub missing_indexes: Vec<String>,
+    pub extra_indexes: Vec<String>,
+    /// Definitions re-applied by a repair, fields first
+    pub repaired: Vec<String>,
+}
+
+impl SchemaReport {
+    pub fn is_in_sync(&self) -> bool {
+        self.missing_fields.is_empty()
+            && self.extra_fields.is_empty()
+            && self.missing_indexes.is_empty()
+            && self.extra_indexes.is_empty()
+    }
+}
+
+/// Compare the fields and indexes defined on `likes` with the migrations.
+/// With `repair`, missing definitions are re-applied from the migration that
+/// created them and the report describes the table afterwards. Extra
+/// definitions are only reported, never removed.
+pub async fn verify(database: &Database, repair: bool) -> Result<SchemaReport> {
+    let fields = expected_definitions("FIELD");
+    let indexes = expected_definitions("INDEX");
+    let mut report = compare(database, &fields, &indexes).await?;
+
+    if !repair || (report.missing_fields.is_empty() && report.missing_indexes.is_empty()) {
+        return Ok(report);
+    }
+
+    // Fields before indexes, since an index may cover a missing field
+    let statements: Vec<(&str, &str)> = fields
+        .iter()
+        .filter(|(name, _)| report.missing_fields.iter().any(|m| m == name))
+        .chain(
+            indexes
+                .iter()
+                .filter(|(name, _)| report.missing_indexes.iter().any(|m| m == name)),
+        )
+        .map(|(name, sql)| (name.as_str(), sql.as_str()))
+        .collect();
+
+    warn!(
+        "Repairing {} missing definitions on {}",
+        statements.len(),
+        LIKES_TABLE
+    );
+    let sql = format!(
+        "BEGIN TRANSACTION;\n{};\nCOMMIT TRANSACTION;",
+        statements
+            .iter()
+            .map(|(_, sql)| *sql)
+            .collect::<Vec<_>>()
+            .join(";\n")
+    );
+    let mut response = database.query(&sql).await?;
+    if let Some(e) = first_error(&mut response) {
+        return Err(anyhow!("Schema repair failed: {}", e));
+    }
+
+    let repaired = statements
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
+    report = compare(database, &fields, &indexes).await?;
+    report.repaired = repaired;
+    Ok(report)
+}
+
+async fn compare(
+    database: &Database,
+    fields: &[(String, String)],
+    indexes: &[(String, String)],
+) -> Result<SchemaReport> {
+    let mut response = database
+        .query(&format!("INFO FOR TABLE {}", LIKES_TABLE))
+        .await?;
+    let info: Option<Value> = response.take(0)?;
+    let info = info.unwrap_or(Value::Null);
+
+    let (missing_fields, extra_fields) = diff(fields, &info["fields"]);
+    let (missing_indexes, extra_indexes) = diff(indexes, &info["indexes"]);
+    Ok(SchemaReport {
+        missing_fields,
+        extra_fields,
+        missing_indexes,
+        extra_indexes,
+        repaired: Vec::new(),
+    })
+}
+
+// Expected names absent from `defined`, and defined names not expected (sorted)
+fn diff(expected: &[(String, String)], defined: &Value) -> (Vec<String>, Vec<String>) {
+    let defined: HashSet<&str> = defined
+        .as_object()
+        .map(|definitions| definitions.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+    let expected_names: HashSet<&str> = expected.iter().map(|(name, _)| name.as_str()).collect();
+
+    let missing = expected
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| !defined.contains(name.as_str()))
+        .cloned()
+        .collect();
+    let mut extra: Vec<String> = defined
+        .difference(&expected_names)
+        .map(|name| name.to_string())
+        .collect();
+    extra.sort();
+    (missing, extra)
+}
+
+/// Name and statement of every `DEFINE <kind>` on `likes` across all
+/// migrations, in the order they are applied
+pub fn expected_definitions(kind: &str) -> Vec<(String, String)> {
+    let mut definitions: Vec<(String, String)> = Vec::new();
+    for migration in MIGRATIONS {
+        let sql: String = migration
+            .sql
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("--"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for statement in sql.split(';').map(str::trim) {
+            if let Some(name) = defined_name(statement, kind, LIKES_TABLE) {
+                // A later migration redefining the same name wins
+                definitions.retain(|(existing, _)| existing != name);
+                definitions.push((name.to_string(), statement.to_string()));
+            }
+        }
+    }
+    definitions
+}
+
+// `DEFINE <kind> [IF NOT EXISTS] <name> ON [TABLE] <table> ...`
+fn defined_name<'a>(statement: &'a str, kind: &str, table: &str) -> Option<&'a str> {
+    let mut words = statement.split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("DEFINE") || !words.next()?.eq_ignore_ascii_case(kind) {
+        return None;
+    }
+
+    let mut name = words.next()?;
+    if name.eq_ignore_ascii_case("IF") {
+        words.next(); // NOT
+        words.next(); // EXISTS
+        name = words.next()?;
+    }
+    if !words.next()?.eq_ignore_ascii_case("ON") {
+        return None;
+    }
+
+    let mut target = words.next()?;
+    if target.eq_ignore_ascii_case("TABLE") {
+        target = words.next()?;
+    }
+    (target == table).then_some(name)
+}
+
+// Errors are keyed by statement index; report the earliest one
+fn first_error(response: &mut surrealdb::Response) -> Option<surrealdb::Error> {
+    response
+        .take_errors()
+        .into_iter()
+        .min_by_key(|(index, _)| *index)
+        .map(|(_, e)| e)
+}
@@ -1,2 +1,5 @@
+pub mod migrations;
+pub mod rocksdb;
 pub mod surreal;
-pub use surreal::Database;
+pub use rocksdb::RocksDbOptions;
+pub use surreal::{AuthScope, Database, DatabaseOptions};
@@ -0,0 +1,67 @@
+use crate::config::Config;
+use tracing::{info, warn};
+
+const MIB: u64 = 1024 * 1024;
+
+/// RocksDB tuning for a `rocksdb://` database.
+///
+/// SurrealDB's embedded RocksDB engine takes no options through its API;
+/// it reads `SURREAL_ROCKSDB_*` environment variables once, when the first
+/// datastore in the process is opened. The honored options are translated
+/// into those variables just before opening. Compression is not among them:
+/// the engine picks its own per-level compression, so `ROCKSDB_COMPRESSION`
+/// is logged and ignored.
+#[derive(Debug, Clone, Default)]
+pub struct RocksDbOptions {
+    // Size of the shared block cache, in MiB
+    pub block_cache_mb: Option<u64>,
+    // Size of a single memtable, in MiB
+    pub write_buffer_mb: Option<u64>,
+    // Memtables kept in memory before writes stall
+    pub max_write_buffers: Option<u32>,
+    // Requested compression type; not supported by the engine
+    pub compression: Option<String>,
+}
+
+impl RocksDbOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            block_cache_mb: config.rocksdb_block_cache_mb,
+            write_buffer_mb: config.rocksdb_write_buffer_mb,
+            max_write_buffers: config.rocksdb_max_write_buffers,
+            compression: config.rocksdb_compression.clone(),
+        }
+    }
+
+    /// The `SURREAL_ROCKSDB_*` variables the engine reads for these options
+    pub fn engine_variables(&self) -> Vec<(&'static str, String)> {
+        let mut variables = Vec::new();
+        if let Some(mb) = self.block_cache_mb {
+            variables.push(("SURREAL_ROCKSDB_BLOCK_CACHE_SIZE", (mb * MIB).to_string()));
+        }
+        if let Some(mb) = self.write_buffer_mb {
+            variables.push(("SURREAL_ROCKSDB_WRITE_BUFFER_SIZE", (mb * MIB).to_string()));
+        }
+        if let Some(count) = self.max_write_buffers {
+            variables.push(("SURREAL_ROCKSDB_MAX_WRITE_BUFFER_NUMBER", count.to_string()));
+        }
+        variables
+    }
+
+    // Must run before the engine is opened; later changes have no effect
+    pub(crate) fn apply(&self) {
+        if let Some(compression) = &self.compression {
+            warn!(
+                "Ignoring ROCKSDB_COMPRESSION={}: SurrealDB's RocksDB engine does not expose compression",
+                compression
+            );
+        }
+
+        for (name, value) in self.engine_variables() {
+            info!("RocksDB option {}={}", name, value);
+            // SAFETY: called once during startup, before the engine or any
+            // other task reads the environment
+            unsafe { std::env::set_var(name, value) };
+        }
+    }
+}
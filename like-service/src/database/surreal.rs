@@ -1,13 +1,24 @@
+use super::{RocksDbOptions, migrations};
+use crate::{config::Config, error::LikesError, telemetry::record_db_query};
 use anyhow::Result;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
 use surrealdb::{
     Surreal,
     engine::{
         local::{Db, Mem, RocksDb},
         remote::ws::{Client, Ws},
     },
-    opt::auth::Root,
+    error::Api,
+    opt::auth,
 };
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone)]
 pub enum DatabaseClient {
@@ -15,64 +26,234 @@ pub enum DatabaseClient {
     Remote(Surreal<Client>),
 }
 
+/// Level of the SurrealDB user remote connections sign in as. A namespace or
+/// database user only reaches the configured namespace or database, so the
+/// service need not hold root credentials.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuthScope {
+    #[default]
+    Root,
+    Namespace,
+    Database,
+}
+
+impl AuthScope {
+    /// `root`, `ns` or `db`, as in `DB_AUTH_SCOPE`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "root" => Some(Self::Root),
+            "ns" => Some(Self::Namespace),
+            "db" => Some(Self::Database),
+            _ => None,
+        }
+    }
+}
+
+/// Connection settings for [`Database::new`]
+#[derive(Debug, Clone)]
+pub struct DatabaseOptions {
+    pub url: String,
+    // Read replica for read-only queries; same namespace, database and credentials
+    pub read_url: Option<String>,
+    pub namespace: String,
+    pub name: String,
+    // Level of the DB_USER credentials remote connections sign in with
+    pub auth_scope: AuthScope,
+    // Number of websocket connections opened to a remote instance
+    pub pool_size: usize,
+    // Extra attempts at connecting and initializing the schema before giving up
+    pub connect_retries: u32,
+    // Delay before the first retry; doubled after each failed attempt
+    pub connect_retry_delay: Duration,
+    // Attempts at reopening a dropped remote connection before a query fails
+    pub reconnect_attempts: u32,
+    // Longest a single query may run; unbounded when `None`
+    pub query_timeout: Option<Duration>,
+    // Whether a URL without a known scheme may fall back to an in-memory database
+    pub allow_in_memory: bool,
+    // Tuning for a `rocksdb://` URL; ignored by other engines
+    pub rocksdb: RocksDbOptions,
+}
+
+impl Default for DatabaseOptions {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            read_url: None,
+            namespace: "likes_service".to_string(),
+            name: "likes".to_string(),
+            auth_scope: AuthScope::Root,
+            pool_size: 1,
+            connect_retries: 0,
+            connect_retry_delay: Duration::from_secs(1),
+            reconnect_attempts: 0,
+            query_timeout: None,
+            allow_in_memory: true,
+            rocksdb: RocksDbOptions::default(),
+        }
+    }
+}
+
+impl DatabaseOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            url: config.database_url.clone(),
+            read_url: config.database_read_url.clone(),
+            namespace: config.db_namespace.clone(),
+            name: config.db_name.clone(),
+            auth_scope: AuthScope::parse(&config.db_auth_scope).unwrap_or_default(),
+            pool_size: config.db_pool_size,
+            connect_retries: config.db_connect_retries,
+            connect_retry_delay: Duration::from_secs(config.db_connect_retry_delay_seconds),
+            reconnect_attempts: config.db_reconnect_attempts,
+            query_timeout: (config.db_query_timeout_ms > 0)
+                .then(|| Duration::from_millis(config.db_query_timeout_ms)),
+            allow_in_memory: config.allow_inmemory_db || config.is_development(),
+            rocksdb: RocksDbOptions::from_config(config),
+        }
+    }
+}
+
+// One pooled connection; the client is swapped out when it is reopened
+#[derive(Debug)]
+struct Connection {
+    client: RwLock<DatabaseClient>,
+    // Bumped on every reconnect, so queries failing together reconnect once
+    generation: AtomicU64,
+    reconnecting: tokio::sync::Mutex<()>,
+}
+
+impl Connection {
+    fn new(client: DatabaseClient) -> Self {
+        Self {
+            client: RwLock::new(client),
+            generation: AtomicU64::new(0),
+            reconnecting: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn client(&self) -> DatabaseClient {
+        self.client
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Database {
-    pub client: DatabaseClient,
+    connections: Arc<Vec<Arc<Connection>>>,
+    next_client: Arc<AtomicUsize>,
+    query_timeout: Option<Duration>,
+    options: Arc<DatabaseOptions>,
+    // Serves `reader()` when a read replica is configured
+    replica: Option<Arc<Database>>,
 }
 
+// Upper bound on the backoff between connection attempts
+const MAX_CONNECT_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+// Time given to an embedded engine to shut its datastore down after the
+// last handle is dropped
+const LOCAL_ENGINE_SHUTDOWN_GRACE: Duration = Duration::from_millis(500);
+
 impl Database {
-    pub async fn new(database_url: &str) -> Result<Self> {
-        info!("Connecting to database: {}", database_url);
+    /// Connect and initialize the schema, retrying with exponential backoff
+    /// while the database is not reachable yet (e.g. still booting)
+    pub async fn new(options: &DatabaseOptions) -> Result<Self> {
+        // Losing every like on restart must be a deliberate choice outside development
+        if !options.allow_in_memory && Self::is_in_memory(&options.url) {
+            return Err(anyhow::anyhow!(
+                "DATABASE_URL '{}' would use an in-memory database, which loses all data on restart; \
+                 set ALLOW_INMEMORY_DB=true to allow it",
+                options.url
+            ));
+        }
 
-        let client = if database_url.starts_with("ws://") || database_url.starts_with("wss://") {
-            // Remote SurrealDB connection (Docker)
-            info!("Connecting to remote SurrealDB instance: {}", database_url);
-            let url = database_url
-                .strip_prefix("ws://")
-                .or_else(|| database_url.strip_prefix("wss://"))
-                .unwrap_or(database_url);
+        let attempts = options.connect_retries.saturating_add(1);
+        let mut delay = options.connect_retry_delay;
+        let mut attempt = 1;
+
+        loop {
+            match Self::connect(options).await {
+                Ok(database) => return Ok(database),
+                Err(e) if attempt < attempts => {
+                    warn!(
+                        "Database connection attempt {}/{} failed: {}; retrying in {:?}",
+                        attempt, attempts, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_CONNECT_RETRY_DELAY);
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!("Giving up on database after {} attempt(s): {}", attempts, e);
+                    return Err(e);
+                }
+            }
+        }
+    }
 
-            let surreal_client = Surreal::new::<Ws>(url)
-                .await
-                .map_err(|e| anyhow::anyhow!("Failed to connect to SurrealDB: {}", e))?;
-
-            // Get credentials from environment
-            let user = std::env::var("DB_USER").map_err(|_| {
-                anyhow::anyhow!(
-                    "SURREAL_DB_USER environment variable is required for remote connections"
-                )
-            })?;
-            let password = std::env::var("DB_PASSWORD").map_err(|_| {
-                anyhow::anyhow!(
-                    "SURREAL_DB_PASSWORD environment variable is required for remote connections"
-                )
-            })?;
-
-            // Sign in with root credentials
-            surreal_client
-                .signin(Root {
-                    username: &user,
-                    password: &password,
-                })
-                .await
-                .map_err(|e| anyhow::anyhow!("Failed to authenticate with SurrealDB: {}", e))?;
+    // Anything that is neither a remote nor a RocksDB URL falls back to memory
+    fn is_in_memory(url: &str) -> bool {
+        !["ws://", "wss://", "rocksdb://"]
+            .iter()
+            .any(|prefix| url.starts_with(prefix))
+    }
 
-            info!("Successfully authenticated with SurrealDB");
+    async fn connect(options: &DatabaseOptions) -> Result<Self> {
+        let mut database = Self::open(options).await?;
 
-            // Use namespace and database
-            surreal_client
-                .use_ns("likes_service")
-                .use_db("likes")
-                .await
-                .map_err(|e| anyhow::anyhow!("Failed to select namespace/database: {}", e))?;
+        // Migrations only run on the primary; replicas receive them from it
+        database.initialize_schema().await?;
+
+        if let Some(read_url) = &options.read_url {
+            if read_url.starts_with("rocksdb://") {
+                return Err(anyhow::anyhow!(
+                    "DATABASE_READ_URL '{}' must be a remote instance; a RocksDB datastore cannot be replicated",
+                    read_url
+                ));
+            }
+            info!("Routing read-only queries to replica {}", read_url);
+            let replica_options = DatabaseOptions {
+                url: read_url.clone(),
+                read_url: None,
+                ..options.clone()
+            };
+            database.replica = Some(Arc::new(Self::open(&replica_options).await?));
+        }
 
-            DatabaseClient::Remote(surreal_client)
+        Ok(database)
+    }
+
+    // Open the connections to one instance
+    async fn open(options: &DatabaseOptions) -> Result<Self> {
+        let database_url = options.url.as_str();
+        info!(
+            "Connecting to database: {} (ns: {}, db: {})",
+            database_url, options.namespace, options.name
+        );
+
+        let clients = if let Some(url) = Self::remote_endpoint(database_url) {
+            // Remote SurrealDB connection (Docker)
+            info!("Connecting to remote SurrealDB instance: {}", database_url);
+
+            let pool_size = options.pool_size.max(1);
+            info!("Opening {} SurrealDB connection(s)", pool_size);
+
+            let mut clients = Vec::with_capacity(pool_size);
+            for _ in 0..pool_size {
+                let surreal_client = Self::connect_remote(url, options).await?;
+                clients.push(DatabaseClient::Remote(surreal_client));
+            }
+            clients
         } else if database_url.starts_with("rocksdb://") {
             // Local RocksDB
             let path = database_url
                 .strip_prefix("rocksdb://")
                 .unwrap_or("/data/likes.db");
             info!("Using RocksDB at path: {}", path);
+            options.rocksdb.apply();
 
             let surreal_client = Surreal::new::<RocksDb>(path)
                 .await
@@ -80,12 +261,12 @@ impl Database {
 
             // Use namespace and database
             surreal_client
-                .use_ns("likes_service")
-                .use_db("likes")
+                .use_ns(&options.namespace)
+                .use_db(&options.name)
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to select namespace/database: {}", e))?;
 
-            DatabaseClient::Local(surreal_client)
+            vec![DatabaseClient::Local(surreal_client)]
         } else {
             // Fallback to in-memory database
             warn!("Using in-memory database (data will be lost on restart)");
@@ -96,58 +277,210 @@ impl Database {
 
             // Use namespace and database
             surreal_client
-                .use_ns("likes_service")
-                .use_db("likes")
+                .use_ns(&options.namespace)
+                .use_db(&options.name)
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to select namespace/database: {}", e))?;
 
-            DatabaseClient::Local(surreal_client)
+            vec![DatabaseClient::Local(surreal_client)]
         };
 
-        let database = Database { client };
-
-        // Initialize schema
-        database.initialize_schema().await?;
+        let database = Database {
+            connections: Arc::new(
+                clients
+                    .into_iter()
+                    .map(Connection::new)
+                    .map(Arc::new)
+                    .collect(),
+            ),
+            next_client: Arc::new(AtomicUsize::new(0)),
+            query_timeout: options.query_timeout,
+            options: Arc::new(options.clone()),
+            replica: None,
+        };
 
         Ok(database)
     }
 
-    async fn initialize_schema(&self) -> Result<()> {
-        info!("Initializing database schema");
+    // Address of a remote instance, without its scheme
+    fn remote_endpoint(url: &str) -> Option<&str> {
+        url.strip_prefix("ws://")
+            .or_else(|| url.strip_prefix("wss://"))
+    }
 
-        let schema_query = r#"
-            -- Remove table if exists and recreate (for development)
-            -- REMOVE TABLE IF EXISTS likes;
-            
-            -- Define the likes table with schema
-            DEFINE TABLE likes SCHEMAFULL;
-            
-            -- Define fields with proper types and constraints
-            DEFINE FIELD id ON TABLE likes TYPE string;
-            DEFINE FIELD user_id ON TABLE likes TYPE string 
-                ASSERT $value != NONE AND string::len($value) > 0;
-            DEFINE FIELD post_id ON TABLE likes TYPE int 
-                ASSERT $value != NONE AND $value > 0;
-            DEFINE FIELD liked_at ON TABLE likes TYPE datetime DEFAULT time::now();
-            DEFINE FIELD created_at ON TABLE likes TYPE datetime DEFAULT time::now();
-            DEFINE FIELD updated_at ON TABLE likes TYPE datetime DEFAULT time::now() 
-                VALUE $before OR time::now();
-
-            -- Define indexes for performance
-            DEFINE INDEX likes_user_post ON TABLE likes COLUMNS user_id, post_id UNIQUE;
-            DEFINE INDEX likes_user_id ON TABLE likes COLUMNS user_id;
-            DEFINE INDEX likes_post_id ON TABLE likes COLUMNS post_id;
-            DEFINE INDEX likes_created_at ON TABLE likes COLUMNS created_at;
-            DEFINE INDEX likes_liked_at ON TABLE likes COLUMNS liked_at;
-        "#;
-
-        let result = match &self.client {
-            DatabaseClient::Local(client) => client.query(schema_query).await,
-            DatabaseClient::Remote(client) => client.query(schema_query).await,
+    async fn connect_remote(url: &str, options: &DatabaseOptions) -> Result<Surreal<Client>> {
+        let surreal_client = Surreal::new::<Ws>(url)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to SurrealDB: {}", e))?;
+
+        // Get credentials from environment
+        let user = std::env::var("DB_USER").map_err(|_| {
+            anyhow::anyhow!(
+                "SURREAL_DB_USER environment variable is required for remote connections"
+            )
+        })?;
+        let password = std::env::var("DB_PASSWORD").map_err(|_| {
+            anyhow::anyhow!(
+                "SURREAL_DB_PASSWORD environment variable is required for remote connections"
+            )
+        })?;
+
+        // Sign in at the configured level; the user must exist there
+        let signin = match options.auth_scope {
+            AuthScope::Root => surreal_client
+                .signin(auth::Root {
+                    username: &user,
+                    password: &password,
+                })
+                .await
+                .map(|_| ()),
+            AuthScope::Namespace => surreal_client
+                .signin(auth::Namespace {
+                    namespace: &options.namespace,
+                    username: &user,
+                    password: &password,
+                })
+                .await
+                .map(|_| ()),
+            AuthScope::Database => surreal_client
+                .signin(auth::Database {
+                    namespace: &options.namespace,
+                    database: &options.name,
+                    username: &user,
+                    password: &password,
+                })
+                .await
+                .map(|_| ()),
         };
+        signin.map_err(|e| anyhow::anyhow!("Failed to authenticate with SurrealDB: {}", e))?;
 
-        match result {
-            Ok(_) => {
+        info!(
+            "Successfully authenticated with SurrealDB ({:?} user)",
+            options.auth_scope
+        );
+
+        // Use namespace and database
+        surreal_client
+            .use_ns(&options.namespace)
+            .use_db(&options.name)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to select namespace/database: {}", e))?;
+
+        Ok(surreal_client)
+    }
+
+    // Pooled connection `pinned(key)` sends queries over, as an index into
+    // this pool; keys with the same index share a connection
+    pub(crate) fn pinned_connection(&self, key: &str) -> usize {
+        Self::pinned_index(key, self.connections.len())
+    }
+
+    // Index of the pooled connection `pinned(key)` uses in a pool of
+    // `pool_size`; the same for every call with the same key
+    fn pinned_index(key: &str, pool_size: usize) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % pool_size.max(1) as u64) as usize
+    }
+
+    /// A view of this database sending every query over the same pooled
+    /// connection, chosen by hashing `key`. Queries for one key then observe
+    /// each other's writes even if connections reach different nodes. Its
+    /// reads stay on the primary too, since a replica may lag behind.
+    pub fn pinned(&self, key: &str) -> Database {
+        let index = Self::pinned_index(key, self.connections.len());
+
+        Database {
+            connections: Arc::new(vec![self.connections[index].clone()]),
+            next_client: Arc::new(AtomicUsize::new(0)),
+            query_timeout: self.query_timeout,
+            options: self.options.clone(),
+            replica: None,
+        }
+    }
+
+    /// The database read-only queries go to: the read replica when
+    /// `DATABASE_READ_URL` is set, otherwise this one. Replicas may lag, so
+    /// nothing that must see its own writes should read from it.
+    pub fn reader(&self) -> Database {
+        match &self.replica {
+            Some(replica) => Database::clone(replica),
+            None => self.clone(),
+        }
+    }
+
+    // Round-robin over the pooled connections; local engines have exactly one
+    fn connection(&self) -> &Arc<Connection> {
+        let index = self.next_client.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        &self.connections[index]
+    }
+
+    fn client(&self) -> DatabaseClient {
+        self.connection().client()
+    }
+
+    /// Reopen a dropped remote connection (sign in and select ns/db again),
+    /// backing off exponentially between attempts. `generation` is the one the
+    /// failed query ran on; if another query already reconnected since, this
+    /// returns immediately.
+    async fn reconnect(&self, connection: &Connection, generation: u64) -> Result<()> {
+        let _reconnecting = connection.reconnecting.lock().await;
+        if connection.generation.load(Ordering::Acquire) != generation {
+            return Ok(());
+        }
+
+        let url = Self::remote_endpoint(&self.options.url)
+            .ok_or_else(|| anyhow::anyhow!("Only remote connections can be reopened"))?;
+        let attempts = self.options.reconnect_attempts;
+        let mut delay = self.options.connect_retry_delay;
+        let mut attempt = 1;
+
+        loop {
+            match Self::connect_remote(url, &self.options).await {
+                Ok(client) => {
+                    *connection
+                        .client
+                        .write()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner()) =
+                        DatabaseClient::Remote(client);
+                    connection.generation.fetch_add(1, Ordering::AcqRel);
+                    info!("Reconnected to SurrealDB after {} attempt(s)", attempt);
+                    return Ok(());
+                }
+                Err(e) if attempt < attempts => {
+                    warn!(
+                        "Reconnect attempt {}/{} failed: {}; retrying in {:?}",
+                        attempt, attempts, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_CONNECT_RETRY_DELAY);
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!(
+                        "Giving up on reconnecting after {} attempt(s): {}",
+                        attempts, e
+                    );
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    // The websocket to a remote instance is gone; the query never ran or its
+    // outcome is unknown
+    fn is_connection_lost(error: &surrealdb::Error) -> bool {
+        matches!(
+            error,
+            surrealdb::Error::Api(Api::Ws(_) | Api::ConnectionUninitialised)
+        )
+    }
+
+    async fn initialize_schema(&self) -> Result<()> {
+        info!("Initializing database schema");
+
+        match migrations::run(self).await {
+            Ok(()) => {
                 info!("Database schema initialized successfully");
                 Ok(())
             }
@@ -158,15 +491,45 @@ impl Database {
         }
     }
 
+    /// Release this handle during shutdown. SurrealDB has no flush call for
+    /// its embedded engines: a local (RocksDB) datastore flushes pending
+    /// writes and closes when its engine task stops, which happens once the
+    /// last handle is dropped. Call this after everything else holding the
+    /// database has stopped, so that this is the last handle.
+    pub async fn close(self) {
+        let local = self
+            .connections
+            .iter()
+            .any(|connection| matches!(connection.client(), DatabaseClient::Local(_)));
+
+        let others = Arc::strong_count(&self.connections) - 1;
+        if others > 0 {
+            warn!(
+                "Closing the database while {} other handle(s) are open; it stays open until they are dropped",
+                others
+            );
+            return;
+        }
+        drop(self);
+
+        if local {
+            // The engine shuts the datastore down on its own task
+            tokio::time::sleep(LOCAL_ENGINE_SHUTDOWN_GRACE).await;
+            info!("Closed the local database; pending writes were flushed to disk");
+        } else {
+            info!("Closed the database connections");
+        }
+    }
+
     pub async fn health_check(&self) -> Result<bool, surrealdb::Error> {
-        let result = match &self.client {
+        let result = match self.client() {
             DatabaseClient::Local(client) => client.query("INFO FOR DB").await,
             DatabaseClient::Remote(client) => client.query("INFO FOR DB").await,
         };
 
         match result {
             Ok(_) => {
-                info!("Database health check passed");
+                debug!("Database health check passed");
                 Ok(true)
             }
             Err(e) => {
@@ -178,7 +541,7 @@ impl Database {
 
     // Helper method to execute queries
     pub async fn query(&self, sql: &str) -> Result<surrealdb::Response> {
-        let result = match &self.client {
+        let result = match self.client() {
             DatabaseClient::Local(client) => client.query(sql).await,
             DatabaseClient::Remote(client) => client.query(sql).await,
         };
@@ -191,7 +554,7 @@ impl Database {
     where
         T: serde::de::DeserializeOwned,
     {
-        let result = match &self.client {
+        let result = match self.client() {
             DatabaseClient::Local(client) => client.create(resource).await,
             DatabaseClient::Remote(client) => client.create(resource).await,
         };
@@ -206,7 +569,7 @@ impl Database {
     where
         T: serde::de::DeserializeOwned,
     {
-        let result = match &self.client {
+        let result = match self.client() {
             DatabaseClient::Local(client) => client.select(resource).await,
             DatabaseClient::Remote(client) => client.select(resource).await,
         };
@@ -220,7 +583,7 @@ impl Database {
         T: serde::Serialize + 'static,
         U: serde::de::DeserializeOwned,
     {
-        let result = match &self.client {
+        let result = match self.client() {
             DatabaseClient::Local(client) => client.update(resource).content(data).await,
             DatabaseClient::Remote(client) => client.update(resource).content(data).await,
         };
@@ -233,7 +596,7 @@ impl Database {
     where
         T: serde::de::DeserializeOwned,
     {
-        let result = match &self.client {
+        let result = match self.client() {
             DatabaseClient::Local(client) => client.delete(resource).await,
             DatabaseClient::Remote(client) => client.delete(resource).await,
         };
@@ -259,7 +622,7 @@ impl Database {
     where
         P: serde::Serialize + 'static,
     {
-        match &self.client {
+        match self.client() {
             DatabaseClient::Local(client) => client
                 .query(sql)
                 .bind(params)
@@ -289,45 +652,164 @@ impl<'a> QueryBuilder<'a> {
 
     // Alternative method that returns anyhow::Error for compatibility with existing code
     pub async fn execute_with_anyhow(self) -> Result<surrealdb::Response, anyhow::Error> {
-        match &self.database.client {
-            DatabaseClient::Local(client) => {
-                let mut query_builder = client.query(&self.sql);
-                for (key, value) in self.bindings {
-                    query_builder = query_builder.bind((key, value));
-                }
-                query_builder
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Query execution failed: {}", e))
-            }
-            DatabaseClient::Remote(client) => {
-                let mut query_builder = client.query(&self.sql);
-                for (key, value) in self.bindings {
-                    query_builder = query_builder.bind((key, value));
-                }
-                query_builder
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Query execution failed: {}", e))
-            }
+        match self.send_with_timeout().await {
+            Ok(result) => result.map_err(|e| anyhow::anyhow!("Query execution failed: {}", e)),
+            Err(limit) => Err(anyhow::anyhow!("Query timed out after {:?}", limit)),
         }
     }
 
-    // Return the original SurrealDB error
-    pub async fn execute(self) -> Result<surrealdb::Response, surrealdb::Error> {
-        match &self.database.client {
+    // Return the original SurrealDB error, or `Timeout` when the query ran too long
+    pub async fn execute(self) -> Result<surrealdb::Response, LikesError> {
+        let started = Instant::now();
+        let result = match self.send_with_timeout().await {
+            Ok(result) => result.map_err(LikesError::Database),
+            Err(limit) => Err(LikesError::Timeout(format!(
+                "Database query exceeded {}ms",
+                limit.as_millis()
+            ))),
+        };
+        record_db_query(started, result.is_ok());
+        result
+    }
+
+    // A query failing because its remote connection dropped is retried once,
+    // after the connection is reopened. Each attempt is bounded separately.
+    async fn send_with_timeout(
+        self,
+    ) -> Result<Result<surrealdb::Response, surrealdb::Error>, Duration> {
+        let connection = self.database.connection().clone();
+        let generation = connection.generation.load(Ordering::Acquire);
+
+        let result = self.send_once(&connection).await;
+        let lost = match &result {
+            Ok(Err(e)) => Database::is_connection_lost(e),
+            _ => false,
+        };
+        if !lost || self.database.options.reconnect_attempts == 0 {
+            return result;
+        }
+
+        warn!("Lost the SurrealDB connection, reconnecting");
+        match self.database.reconnect(&connection, generation).await {
+            Ok(()) => self.send_once(&connection).await,
+            Err(_) => result,
+        }
+    }
+
+    // Bounded by the database's query timeout, when one is set; a timed out
+    // query is dropped and reports the limit it exceeded
+    async fn send_once(
+        &self,
+        connection: &Connection,
+    ) -> Result<Result<surrealdb::Response, surrealdb::Error>, Duration> {
+        match self.database.query_timeout {
+            Some(limit) => tokio::time::timeout(limit, self.send(connection))
+                .await
+                .map_err(|_| limit),
+            None => Ok(self.send(connection).await),
+        }
+    }
+
+    async fn send(&self, connection: &Connection) -> Result<surrealdb::Response, surrealdb::Error> {
+        match connection.client() {
             DatabaseClient::Local(client) => {
                 let mut query_builder = client.query(&self.sql);
-                for (key, value) in self.bindings {
-                    query_builder = query_builder.bind((key, value));
+                for (key, value) in &self.bindings {
+                    query_builder = query_builder.bind((key.clone(), value.clone()));
                 }
                 query_builder.await
             }
             DatabaseClient::Remote(client) => {
                 let mut query_builder = client.query(&self.sql);
-                for (key, value) in self.bindings {
-                    query_builder = query_builder.bind((key, value));
+                for (key, value) in &self.bindings {
+                    query_builder = query_builder.bind((key.clone(), value.clone()));
                 }
                 query_builder.await
             }
         }
     }
 }
+
+#[cfg(test)]
+impl Database {
+    // A pool of `size` connections to separate in-memory datastores, each
+    // with the schema applied, so a query sent over the wrong connection
+    // cannot see another connection's writes
+    pub(crate) async fn memory_pool(size: usize) -> Result<Self> {
+        let options = DatabaseOptions {
+            pool_size: size,
+            ..DatabaseOptions::default()
+        };
+        let mut connections = Vec::with_capacity(size);
+        for _ in 0..size {
+            let single = Self::open(&options).await?;
+            single.initialize_schema().await?;
+            connections.extend(single.connections.iter().cloned());
+        }
+
+        Ok(Database {
+            connections: Arc::new(connections),
+            next_client: Arc::new(AtomicUsize::new(0)),
+            query_timeout: None,
+            options: Arc::new(options),
+            replica: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Database;
+
+    async fn count_probes(database: &Database, key: &str) -> usize {
+        let mut result = database
+            .query_builder("SELECT * FROM probe WHERE key = $key;")
+            .bind("key", key.to_string())
+            .execute()
+            .await
+            .unwrap();
+        let rows: Vec<serde_json::Value> = result.take(0).unwrap();
+        rows.len()
+    }
+
+    async fn write_probe(database: &Database, key: &str) {
+        database
+            .query_builder("CREATE probe SET key = $key;")
+            .bind("key", key.to_string())
+            .execute()
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn a_key_is_always_pinned_to_the_same_connection() {
+        let indexes: Vec<usize> = (0..64)
+            .map(|i| Database::pinned_index(&format!("user_{}", i), 4))
+            .collect();
+
+        for (i, index) in indexes.iter().enumerate() {
+            assert!(*index < 4);
+            assert_eq!(Database::pinned_index(&format!("user_{}", i), 4), *index);
+        }
+        // Keys are spread over the pool rather than all pinned to one connection
+        assert!(indexes.iter().any(|index| *index != indexes[0]));
+        assert_eq!(Database::pinned_index("alice", 1), 0);
+    }
+
+    #[tokio::test]
+    async fn pinned_reads_see_pinned_writes_across_a_pool() {
+        let database = Database::memory_pool(4).await.unwrap();
+
+        // Round-robin sends the read to another datastore than the write
+        write_probe(&database, "unpinned").await;
+        assert_eq!(count_probes(&database, "unpinned").await, 0);
+
+        for i in 0..16 {
+            let key = format!("user_{}", i);
+            write_probe(&database.pinned(&key), &key).await;
+            for _ in 0..4 {
+                assert_eq!(count_probes(&database.pinned(&key), &key).await, 1);
+            }
+        }
+    }
+}
@@ -20,6 +20,15 @@ pub enum LikesError {
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Downstream service unavailable: {0}")]
+    Downstream(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
 }
 
 impl From<LikesError> for Status {
@@ -36,6 +45,19 @@ impl From<LikesError> for Status {
                 tracing::error!("Serialization error: {}", err);
                 Status::internal("Serialization error occurred")
             }
+            LikesError::Timeout(msg) => {
+                tracing::warn!("Timed out: {}", msg);
+                Status::deadline_exceeded(msg)
+            }
+            LikesError::Downstream(msg) => {
+                tracing::warn!("Downstream service unavailable: {}", msg);
+                Status::unavailable(msg)
+            }
+            // ABORTED tells clients the whole call may be retried
+            LikesError::Conflict(msg) => {
+                tracing::warn!("Conflict: {}", msg);
+                Status::aborted(msg)
+            }
             LikesError::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
                 Status::internal(msg)
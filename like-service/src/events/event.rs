@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LikeAction {
+    Liked,
+    Unliked,
+}
+
+/// Domain event emitted after a like is created or removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LikeEvent {
+    pub user_id: String,
+    pub post_id: u32,
+    pub action: LikeAction,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl LikeEvent {
+    pub fn new(user_id: String, post_id: u32, action: LikeAction) -> Self {
+        Self::new_at(user_id, post_id, action, Utc::now())
+    }
+
+    /// An event that happened at `timestamp`
+    pub fn new_at(
+        user_id: String,
+        post_id: u32,
+        action: LikeAction,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            user_id,
+            post_id,
+            action,
+            timestamp,
+        }
+    }
+}
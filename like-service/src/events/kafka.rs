@@ -0,0 +1,58 @@
+use super::{EventPublisher, LikeEvent};
+use anyhow::{Result, anyhow};
+use rdkafka::{
+    ClientConfig,
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
+};
+use std::time::Duration;
+use tracing::debug;
+
+// How long a send may wait for room in the producer queue
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+pub struct KafkaPublisher {
+    producer: FutureProducer,
+}
+
+impl KafkaPublisher {
+    pub fn new(brokers: &str) -> Result<Self> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "5000")
+            .create()
+            .map_err(|e| anyhow!("Failed to create Kafka producer: {}", e))?;
+
+        Ok(Self { producer })
+    }
+}
+
+impl std::fmt::Debug for KafkaPublisher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaPublisher").finish_non_exhaustive()
+    }
+}
+
+#[tonic::async_trait]
+impl EventPublisher for KafkaPublisher {
+    async fn publish(&self, topic: &str, event: LikeEvent) -> Result<()> {
+        let payload = serde_json::to_string(&event)?;
+        // Key by post so all events for a post land on the same partition
+        let key = event.post_id.to_string();
+
+        self.producer
+            .send(
+                FutureRecord::to(topic).key(&key).payload(&payload),
+                Timeout::After(SEND_TIMEOUT),
+            )
+            .await
+            .map_err(|(e, _)| anyhow!("Failed to publish event to {}: {}", topic, e))?;
+
+        debug!(
+            "Published {:?} event for post {}",
+            event.action, event.post_id
+        );
+        Ok(())
+    }
+}
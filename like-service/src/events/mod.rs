@@ -0,0 +1,35 @@
+pub mod event;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod publisher;
+pub mod watch;
+
+pub use event::{LikeAction, LikeEvent};
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaPublisher;
+pub use publisher::{EventPublisher, NoopPublisher};
+pub use watch::{PostSubscription, PostWatchers, WatchedEvent};
+
+use crate::config::Config;
+use anyhow::Result;
+use std::sync::Arc;
+use tracing::info;
+
+/// Build the event publisher selected by the configuration
+pub fn publisher_from_config(config: &Config) -> Result<Arc<dyn EventPublisher>> {
+    #[cfg(feature = "kafka")]
+    if let Some(brokers) = &config.kafka_brokers {
+        info!("Publishing like events to Kafka at {}", brokers);
+        return Ok(Arc::new(KafkaPublisher::new(brokers)?));
+    }
+
+    #[cfg(not(feature = "kafka"))]
+    if config.kafka_brokers.is_some() {
+        tracing::warn!(
+            "KAFKA_BROKERS is set but the service was built without the `kafka` feature; events are disabled"
+        );
+    }
+
+    info!("Like events are disabled");
+    Ok(Arc::new(NoopPublisher))
+}
@@ -0,0 +1,21 @@
+use super::LikeEvent;
+use anyhow::Result;
+use tracing::debug;
+
+#[tonic::async_trait]
+pub trait EventPublisher: std::fmt::Debug + Send + Sync {
+    /// Publish a like event to the given topic
+    async fn publish(&self, topic: &str, event: LikeEvent) -> Result<()>;
+}
+
+/// Publisher used when no event backend is configured
+#[derive(Debug, Clone, Default)]
+pub struct NoopPublisher;
+
+#[tonic::async_trait]
+impl EventPublisher for NoopPublisher {
+    async fn publish(&self, topic: &str, event: LikeEvent) -> Result<()> {
+        debug!("Dropping {:?} event for topic {}", event.action, topic);
+        Ok(())
+    }
+}
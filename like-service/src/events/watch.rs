@@ -0,0 +1,109 @@
+use super::LikeEvent;
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+// Events a subscriber may fall behind by before it starts missing them
+const DEFAULT_CHANNEL_CAPACITY: usize = 256;
+
+/// Live like events by post, for `watch_post_likes` subscribers. A post's
+/// channel is created by its first subscriber and removed once the last one
+/// leaves, so publishing to an unwatched post costs a map lookup. Publishing
+/// never waits: a subscriber that falls behind misses the oldest events.
+#[derive(Debug, Clone)]
+pub struct PostWatchers {
+    channels: Arc<DashMap<u32, broadcast::Sender<LikeEvent>>>,
+    capacity: usize,
+}
+
+impl Default for PostWatchers {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHANNEL_CAPACITY)
+    }
+}
+
+impl PostWatchers {
+    /// Buffer up to `capacity` events per post for its slowest subscriber
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            channels: Arc::new(DashMap::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Receive the post's events from now until the subscription is dropped
+    pub fn subscribe(&self, post_id: u32) -> PostSubscription {
+        let receiver = self
+            .channels
+            .entry(post_id)
+            .or_insert_with(|| broadcast::channel(self.capacity).0)
+            .subscribe();
+
+        PostSubscription {
+            post_id,
+            receiver,
+            channels: self.channels.clone(),
+        }
+    }
+
+    /// Hand `event` to the post's current subscribers, if any
+    pub fn publish(&self, event: &LikeEvent) {
+        let unwatched = match self.channels.get(&event.post_id) {
+            Some(sender) => sender.send(event.clone()).is_err(),
+            None => return,
+        };
+
+        if unwatched {
+            self.channels
+                .remove_if(&event.post_id, |_, sender| sender.receiver_count() == 0);
+        }
+    }
+
+    /// End every subscription, e.g. so open streams don't hold up shutdown
+    pub fn close(&self) {
+        self.channels.clear();
+    }
+
+    /// Posts with at least one subscriber
+    pub fn watched_posts(&self) -> usize {
+        self.channels.len()
+    }
+}
+
+/// An event delivered to a subscriber
+#[derive(Debug, Clone)]
+pub struct WatchedEvent {
+    pub event: LikeEvent,
+    /// Events dropped just before this one because the subscriber fell behind
+    pub missed: u64,
+}
+
+/// One subscriber's view of a post's events
+#[derive(Debug)]
+pub struct PostSubscription {
+    post_id: u32,
+    receiver: broadcast::Receiver<LikeEvent>,
+    channels: Arc<DashMap<u32, broadcast::Sender<LikeEvent>>>,
+}
+
+impl PostSubscription {
+    /// Wait for the next event; `None` once the channel is gone
+    pub async fn recv(&mut self) -> Option<WatchedEvent> {
+        let mut missed = 0;
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(WatchedEvent { event, missed }),
+                Err(RecvError::Lagged(skipped)) => missed += skipped,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for PostSubscription {
+    fn drop(&mut self) {
+        // Our own receiver is only dropped after this runs, so it still counts
+        self.channels
+            .remove_if(&self.post_id, |_, sender| sender.receiver_count() <= 1);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod rest;
+
+pub use rest::serve;
@@ -0,0 +1,448 @@
+use crate::{
+    auth::{AuthInterceptor, Caller},
+    proto::{likes_service_server::LikesService, *},
+    service::LikesServiceImpl,
+    telemetry::RequestIdLayer,
+};
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::{self, Extension, Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use std::{future::Future, net::SocketAddr, sync::Arc};
+use tokio::net::TcpListener;
+use tonic::{Code, Request, Status, metadata::MetadataMap};
+use tracing::info;
+
+type Service = State<Arc<LikesServiceImpl>>;
+
+/// REST routes, each forwarding to the matching gRPC handler. Bearer tokens
+/// are checked by the same interceptor as on the gRPC server.
+pub fn router(service: Arc<LikesServiceImpl>, auth: AuthInterceptor) -> Router {
+    Router::new()
+        .route("/users/{user_id}/likes", get(get_user_likes))
+        .route(
+            "/users/{user_id}/likes/{post_id}",
+            get(is_post_liked).post(like_post).delete(unlike_post),
+        )
+        .route("/posts/{post_id}/likes", get(get_post_likes))
+        .route("/posts/{post_id}/likes/count", get(get_likes_count))
+        .layer(middleware::from_fn_with_state(auth, authenticate))
+        .layer(RequestIdLayer)
+        .with_state(service)
+}
+
+/// Serve the gateway on `addr` until `shutdown` resolves
+pub async fn serve(
+    addr: SocketAddr,
+    service: Arc<LikesServiceImpl>,
+    auth: AuthInterceptor,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("HTTP gateway listening on {}", addr);
+
+    axum::serve(listener, router(service, auth))
+        .with_graceful_shutdown(shutdown)
+        .await?;
+    Ok(())
+}
+
+// Run the auth interceptor on the HTTP headers, keeping the caller for the handlers
+async fn authenticate(
+    State(auth): State<AuthInterceptor>,
+    mut request: extract::Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let metadata = MetadataMap::from_headers(request.headers().clone());
+    if let Some(caller) = auth.authenticate(&metadata)? {
+        request.extensions_mut().insert(caller);
+    }
+    Ok(next.run(request).await)
+}
+
+// A gRPC request carrying the caller established by `authenticate`
+fn grpc_request<T>(message: T, caller: Option<Extension<Caller>>) -> Request<T> {
+    let mut request = Request::new(message);
+    if let Some(Extension(caller)) = caller {
+        request.extensions_mut().insert(caller);
+    }
+    request
+}
+
+/// A gRPC status rendered as JSON with the equivalent HTTP status code
+pub struct ApiError(Status);
+
+impl From<Status> for ApiError {
+    fn from(status: Status) -> Self {
+        Self(status)
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    code: String,
+    message: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            code: format!("{:?}", self.0.code()),
+            message: self.0.message().to_string(),
+        };
+        (http_status(self.0.code()), Json(body)).into_response()
+    }
+}
+
+// Same mapping as grpc-gateway
+fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        // Client closed request
+        Code::Cancelled => StatusCode::from_u16(499).unwrap_or(StatusCode::BAD_REQUEST),
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LikeQuery {
+    reaction: Option<String>,
+    idempotency_key: Option<String>,
+    #[serde(default)]
+    strict: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PageQuery {
+    page: Option<i32>,
+    limit: Option<i32>,
+    sort_by: Option<String>,
+    sort_order: Option<String>,
+    #[serde(default)]
+    include_post_metadata: bool,
+    viewer_id: Option<String>,
+    #[serde(default)]
+    counts_only: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CountQuery {
+    #[serde(default)]
+    include_reactions: bool,
+}
+
+#[derive(Serialize)]
+struct LikeBody {
+    success: bool,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    liked_at: Option<String>,
+}
+
+#[derive(Serialize)]
+struct IsLikedBody {
+    is_liked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    liked_at: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CountBody {
+    count: i64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reactions: Vec<ReactionCountBody>,
+}
+
+#[derive(Serialize)]
+struct ReactionCountBody {
+    reaction: String,
+    count: i64,
+}
+
+#[derive(Serialize)]
+struct UserLikeBody {
+    post_id: u32,
+    liked_at: Option<String>,
+    created_at: Option<String>,
+    reaction: String,
+}
+
+#[derive(Serialize)]
+struct PostLikeBody {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    user_id: String,
+    liked_at: Option<String>,
+    created_at: Option<String>,
+    reaction: String,
+}
+
+#[derive(Serialize)]
+struct PostSummaryBody {
+    title: String,
+    author_id: String,
+}
+
+#[derive(Serialize)]
+struct PaginationBody {
+    current_page: i32,
+    total_pages: i32,
+    total_count: i64,
+    limit: i32,
+    has_next: bool,
+    has_previous: bool,
+    offset: i32,
+}
+
+#[derive(Serialize)]
+struct PageBody<T> {
+    likes: Vec<T>,
+    pagination: Option<PaginationBody>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post: Option<PostSummaryBody>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    anonymized: bool,
+}
+
+async fn like_post(
+    State(service): Service,
+    caller: Option<Extension<Caller>>,
+    Path((user_id, post_id)): Path<(String, u32)>,
+    Query(query): Query<LikeQuery>,
+) -> Result<Json<LikeBody>, ApiError> {
+    let mut request = LikePostRequest {
+        user_id,
+        post_id,
+        strict: query.strict,
+        idempotency_key: query.idempotency_key.unwrap_or_default(),
+        ..Default::default()
+    };
+    if let Some(reaction) = query.reaction {
+        request.set_reaction(parse_reaction(&reaction)?);
+    }
+
+    let response = service
+        .like_post(grpc_request(request, caller))
+        .await?
+        .into_inner();
+    Ok(Json(LikeBody {
+        success: response.success,
+        message: response.message,
+        liked_at: response.liked_at.and_then(format_timestamp),
+    }))
+}
+
+async fn unlike_post(
+    State(service): Service,
+    caller: Option<Extension<Caller>>,
+    Path((user_id, post_id)): Path<(String, u32)>,
+) -> Result<Json<LikeBody>, ApiError> {
+    let request = UnlikePostRequest { user_id, post_id };
+
+    let response = service
+        .unlike_post(grpc_request(request, caller))
+        .await?
+        .into_inner();
+    Ok(Json(LikeBody {
+        success: response.success,
+        message: response.message,
+        liked_at: None,
+    }))
+}
+
+async fn is_post_liked(
+    State(service): Service,
+    Path((user_id, post_id)): Path<(String, u32)>,
+) -> Result<Json<IsLikedBody>, ApiError> {
+    let request = IsPostLikedRequest { user_id, post_id };
+
+    let response = service
+        .is_post_liked(Request::new(request))
+        .await?
+        .into_inner();
+    Ok(Json(IsLikedBody {
+        is_liked: response.is_liked,
+        liked_at: response.liked_at.and_then(format_timestamp),
+    }))
+}
+
+async fn get_user_likes(
+    State(service): Service,
+    Path(user_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<PageBody<UserLikeBody>>, ApiError> {
+    let (sort_by, sort_order) = parse_sort(&query)?;
+    let mut request = GetUserLikesRequest {
+        user_id,
+        page: query.page.unwrap_or_default(),
+        limit: query.limit.unwrap_or_default(),
+        ..Default::default()
+    };
+    request.set_sort_by(sort_by);
+    request.set_sort_order(sort_order);
+
+    let response = service
+        .get_user_likes(Request::new(request))
+        .await?
+        .into_inner();
+    Ok(Json(PageBody {
+        likes: response
+            .likes
+            .into_iter()
+            .map(|like| UserLikeBody {
+                post_id: like.post_id,
+                liked_at: like.liked_at.and_then(format_timestamp),
+                created_at: like.created_at.and_then(format_timestamp),
+                reaction: format_reaction(like.reaction),
+            })
+            .collect(),
+        pagination: response.pagination.map(pagination_body),
+        post: None,
+        anonymized: false,
+    }))
+}
+
+async fn get_post_likes(
+    State(service): Service,
+    Path(post_id): Path<u32>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<PageBody<PostLikeBody>>, ApiError> {
+    let (sort_by, sort_order) = parse_sort(&query)?;
+    let mut request = GetPostLikesRequest {
+        post_id,
+        page: query.page.unwrap_or_default(),
+        limit: query.limit.unwrap_or_default(),
+        include_post_metadata: query.include_post_metadata,
+        viewer_id: query.viewer_id.unwrap_or_default(),
+        counts_only: query.counts_only,
+        ..Default::default()
+    };
+    request.set_sort_by(sort_by);
+    request.set_sort_order(sort_order);
+
+    let response = service
+        .get_post_likes(Request::new(request))
+        .await?
+        .into_inner();
+    Ok(Json(PageBody {
+        likes: response
+            .likes
+            .into_iter()
+            .map(|like| PostLikeBody {
+                user_id: like.user_id,
+                liked_at: like.liked_at.and_then(format_timestamp),
+                created_at: like.created_at.and_then(format_timestamp),
+                reaction: format_reaction(like.reaction),
+            })
+            .collect(),
+        pagination: response.pagination.map(pagination_body),
+        post: response.post.map(|post| PostSummaryBody {
+            title: post.title,
+            author_id: post.author_id,
+        }),
+        anonymized: response.anonymized,
+    }))
+}
+
+async fn get_likes_count(
+    State(service): Service,
+    Path(post_id): Path<u32>,
+    Query(query): Query<CountQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    // Our tags are strong; a weak comparison is all a GET needs
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().trim_start_matches("W/").trim_matches('"'))
+        .unwrap_or_default();
+    let request = GetLikesCountRequest {
+        post_id,
+        include_reactions: query.include_reactions,
+        if_none_match: if_none_match.to_string(),
+    };
+
+    let response = service
+        .get_likes_count(Request::new(request))
+        .await?
+        .into_inner();
+    let etag = HeaderValue::from_str(&format!("\"{}\"", response.etag))
+        .map_err(|_| Status::internal("Invalid etag"))?;
+    if response.not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    let body = Json(CountBody {
+        count: response.count,
+        reactions: response
+            .reactions
+            .into_iter()
+            .map(|reaction| ReactionCountBody {
+                reaction: format_reaction(reaction.reaction),
+                count: reaction.count,
+            })
+            .collect(),
+    });
+    Ok(([(header::ETAG, etag)], body).into_response())
+}
+
+// Query values use the lowercase enum names without their prefix, e.g. `love`
+fn parse_reaction(value: &str) -> Result<Reaction, ApiError> {
+    Reaction::from_str_name(&format!("REACTION_{}", value.to_uppercase()))
+        .ok_or_else(|| Status::invalid_argument(format!("Unknown reaction '{}'", value)).into())
+}
+
+fn parse_sort(query: &PageQuery) -> Result<(SortField, SortOrder), ApiError> {
+    let sort_by = match &query.sort_by {
+        Some(value) => SortField::from_str_name(&format!("SORT_FIELD_{}", value.to_uppercase()))
+            .ok_or_else(|| Status::invalid_argument(format!("Unknown sort_by '{}'", value)))?,
+        None => SortField::Unspecified,
+    };
+    let sort_order = match &query.sort_order {
+        Some(value) => SortOrder::from_str_name(&format!("SORT_ORDER_{}", value.to_uppercase()))
+            .ok_or_else(|| Status::invalid_argument(format!("Unknown sort_order '{}'", value)))?,
+        None => SortOrder::Unspecified,
+    };
+    Ok((sort_by, sort_order))
+}
+
+fn format_reaction(reaction: i32) -> String {
+    Reaction::try_from(reaction)
+        .unwrap_or(Reaction::Like)
+        .as_str_name()
+        .trim_start_matches("REACTION_")
+        .to_lowercase()
+}
+
+fn format_timestamp(timestamp: prost_types::Timestamp) -> Option<String> {
+    chrono::DateTime::from_timestamp(timestamp.seconds, timestamp.nanos.max(0) as u32)
+        .map(|dt| dt.to_rfc3339())
+}
+
+fn pagination_body(info: PaginationInfo) -> PaginationBody {
+    PaginationBody {
+        current_page: info.current_page,
+        total_pages: info.total_pages,
+        total_count: info.total_count,
+        limit: info.limit,
+        has_next: info.has_next,
+        has_previous: info.has_previous,
+        offset: info.offset,
+    }
+}
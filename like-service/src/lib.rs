@@ -0,0 +1,26 @@
+pub mod auth;
+pub mod clients;
+pub mod clock;
+pub mod config;
+pub mod database;
+pub mod error;
+pub mod events;
+pub mod gateway;
+pub mod models;
+pub mod repository;
+pub mod service;
+pub mod telemetry;
+
+// Include the generated gRPC code
+pub mod proto {
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("like_service_descriptor");
+
+    tonic::include_proto!("like");
+    pub mod user {
+        tonic::include_proto!("user");
+    }
+    pub mod post {
+        tonic::include_proto!("post");
+    }
+}
@@ -1,72 +1,319 @@
-mod clients;
-mod config;
-mod database;
-mod error;
-mod models;
-mod repository;
-mod service;
-
 use anyhow::Result;
-use std::net::SocketAddr;
-use tokio::signal;
-use tonic::transport::Server;
-use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
-use crate::{
-    clients::{PostClient, UserClient},
-    config::Config,
-    database::Database,
-    repository::LikesRepository,
-    service::LikesServiceImpl,
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    signal,
+    sync::oneshot,
+    time::{Instant, timeout_at},
+};
+use tonic::{
+    codec::CompressionEncoding, service::interceptor::InterceptedService, transport::Server,
 };
+use tonic_health::ServingStatus;
+use tracing::{Level, debug, info, warn};
 
-// Include the generated gRPC code
-pub mod proto {
-    pub(crate) const FILE_DESCRIPTOR_SET: &[u8] =
-        tonic::include_file_descriptor_set!("like_service_descriptor");
+use like_service::{
+    auth::{AuthInterceptor, JwtVerifier},
+    clients::{CircuitBreakerSettings, PostClient, UserClient},
+    config::{Config, client_tls_config, server_tls_config},
+    database::{Database, DatabaseOptions},
+    events, gateway,
+    models::PaginationBounds,
+    proto::{self, likes_service_server::LikesServiceServer},
+    repository::{LikeWriteBuffer, LikesRepository},
+    service::{
+        ConcurrencyLimitLayer, InFlightTracker, LikesServiceImpl, MethodTimeoutLayer, RateLimiter,
+        Reconciliation,
+    },
+    telemetry,
+};
 
-    tonic::include_proto!("like");
-    pub mod user {
-        tonic::include_proto!("user");
-    }
-    pub mod post {
-        tonic::include_proto!("post");
-    }
-}
+// How often idle rate limit buckets are dropped
+const RATE_LIMIT_EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+// How often the gRPC health status is refreshed from the database
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// How often soft-deleted likes past the retention window and expired
+// idempotency keys are purged
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// How often likes recorded under lenient validation are re-validated
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+// How long each service gets to answer the startup self-check
+const STARTUP_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "likes_service=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     // Load configuration
     let config = Config::from_env()?;
+    config.validate()?;
+
+    // Initialize tracing, exporting spans over OTLP when configured
+    let tracer_provider = telemetry::init_tracing(&config)?;
     info!("Starting likes service on {}:{}", config.host, config.port);
 
+    // Start the Prometheus metrics endpoint
+    let metrics_addr: SocketAddr = format!("{}:{}", config.host, config.metrics_port).parse()?;
+    let metrics_server = telemetry::install_metrics_exporter(metrics_addr)?;
+
     // Initialize database
-    let database = Database::new(&config.database_url).await?;
+    let database = Database::new(&DatabaseOptions::from_config(&config)).await?;
     info!("Connected to SurrealDB");
 
     // Initialize user client
-    let user_client = UserClient::new(config.user_service_url).await?;
+    let mut user_client = UserClient::new_with_tls(
+        config.user_service_url.clone(),
+        client_tls_config(&config.user_service_url, &config.user_service_tls)?,
+    )
+    .await?;
     info!("Connected to User Service");
 
     // Initialize post client
-    let post_client = PostClient::new(config.post_service_url).await?;
+    let mut post_client = PostClient::new_with_tls(
+        config.post_service_url.clone(),
+        client_tls_config(&config.post_service_url, &config.post_service_tls)?,
+    )
+    .await?;
     info!("Connected to Post Service");
 
-    // Initialize repository
-    let repository = LikesRepository::new(database);
+    // Cache positive existence checks; a TTL of 0 disables the cache
+    if config.existence_cache_ttl_seconds > 0 {
+        let ttl = Duration::from_secs(config.existence_cache_ttl_seconds);
+        user_client = user_client.with_existence_cache(ttl, config.existence_cache_capacity);
+        post_client = post_client.with_existence_cache(ttl, config.existence_cache_capacity);
+    }
+
+    // Fail fast while a dependency is unhealthy; a threshold of 0 disables the breaker
+    if config.circuit_breaker_failure_threshold > 0 {
+        let settings = CircuitBreakerSettings {
+            failure_threshold: config.circuit_breaker_failure_threshold,
+            window: Duration::from_secs(config.circuit_breaker_window_seconds),
+            cooldown: Duration::from_secs(config.circuit_breaker_cooldown_seconds),
+        };
+        user_client = user_client.with_circuit_breaker(settings);
+        post_client = post_client.with_circuit_breaker(settings);
+    }
+
+    // Gzip outbound calls; both services must accept gzip
+    if config.grpc_compression {
+        user_client = user_client.with_compression();
+        post_client = post_client.with_compression();
+    }
+
+    // Initialize repository; `database` is kept to close it on shutdown
+    let mut repository =
+        LikesRepository::new(database.clone()).with_read_your_writes(config.db_read_your_writes);
+    if config.post_likes_cache_ttl_seconds > 0 {
+        repository = repository.with_post_likes_cache(
+            Duration::from_secs(config.post_likes_cache_ttl_seconds),
+            config.post_likes_cache_capacity,
+        );
+    }
+
+    // Validation rejects SEED_DATA outside development; checked again here
+    if config.seed_data && config.is_development() {
+        match repository.seed_demo_data(config.seed_data_count).await {
+            Ok(0) => info!("Likes table is not empty, skipping demo data"),
+            Ok(seeded) => info!("Seeded {} demo likes", seeded),
+            Err(e) => warn!("Failed to seed demo data: {}", e),
+        }
+    }
+
+    // Tasks holding the database, stopped before it is closed
+    let mut database_tasks = Vec::new();
+
+    // Coalesce likes into batched writes; a window of 0 writes each directly
+    if config.write_buffer_window_ms > 0 {
+        let (buffer, task) = LikeWriteBuffer::spawn(
+            repository.clone(),
+            Duration::from_millis(config.write_buffer_window_ms),
+            config.write_buffer_max_batch,
+        );
+        repository = repository.with_write_buffer(buffer);
+        database_tasks.push(task);
+    }
+
+    // Initialize standard gRPC health reporting, driven by the database status
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    let health_repository = repository.clone();
+    database_tasks.push(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HEALTH_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            match health_repository.health_check().await {
+                Ok(_) => {
+                    health_reporter
+                        .set_service_status("", ServingStatus::Serving)
+                        .await;
+                    health_reporter
+                        .set_serving::<LikesServiceServer<LikesServiceImpl>>()
+                        .await;
+                }
+                Err(e) => {
+                    warn!("Database unreachable, reporting NOT_SERVING: {}", e);
+                    health_reporter
+                        .set_service_status("", ServingStatus::NotServing)
+                        .await;
+                    health_reporter
+                        .set_not_serving::<LikesServiceServer<LikesServiceImpl>>()
+                        .await;
+                }
+            }
+        }
+    }));
+
+    // Periodically purge soft-deleted likes past the retention window
+    let purge_repository = repository.clone();
+    let retention = chrono::Duration::days(config.deleted_like_retention_days);
+    database_tasks.push(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match purge_repository.purge_deleted_likes(retention).await {
+                Ok(0) => {}
+                Ok(purged) => info!("Purged {} soft-deleted likes", purged),
+                Err(e) => warn!("Failed to purge soft-deleted likes: {}", e),
+            }
+            match purge_repository.purge_expired_idempotency_keys().await {
+                Ok(0) => {}
+                Ok(purged) => info!("Purged {} expired idempotency keys", purged),
+                Err(e) => warn!("Failed to purge expired idempotency keys: {}", e),
+            }
+        }
+    }));
+
+    // Initialize event publisher
+    let event_publisher = events::publisher_from_config(&config)?;
 
     // Initialize service
-    let likes_service = LikesServiceImpl::new(repository, user_client, post_client);
+    let in_flight = InFlightTracker::new();
+    // Kept to end open `watch_post_likes` streams on shutdown
+    let watchers = events::PostWatchers::new(config.watch_channel_capacity);
+    let mut likes_service =
+        LikesServiceImpl::new(repository, Arc::new(user_client), Arc::new(post_client))
+            .with_event_publisher(event_publisher, config.kafka_topic.clone())
+            .with_in_flight_tracker(in_flight.clone())
+            .with_post_watchers(watchers.clone())
+            .with_idempotency_ttl(chrono::Duration::seconds(config.idempotency_ttl_seconds))
+            .with_validation_concurrency(config.validation_concurrency)
+            .with_lenient_post_validation(config.lenient_post_validation)
+            .with_lowercase_user_ids(config.lowercase_user_ids)
+            .with_pagination_bounds(
+                PaginationBounds {
+                    default_limit: config.user_likes_default_limit,
+                    max_limit: config.user_likes_max_limit,
+                    max_offset: config.max_pagination_offset,
+                    ..PaginationBounds::default()
+                },
+                PaginationBounds {
+                    default_limit: config.post_likes_default_limit,
+                    max_limit: config.post_likes_max_limit,
+                    max_offset: config.max_pagination_offset,
+                    ..PaginationBounds::default()
+                },
+            );
+
+    // Cache Clerk ID → DB ID resolutions; a TTL of 0 disables the cache
+    if config.user_id_cache_ttl_seconds > 0 {
+        likes_service = likes_service.with_user_id_cache(
+            Duration::from_secs(config.user_id_cache_ttl_seconds),
+            config.user_id_cache_capacity,
+        );
+    }
+
+    // Per-user rate limiting of writes; a rate of 0 disables it
+    if config.rate_limit_per_second > 0.0 {
+        let rate_limiter = RateLimiter::new(config.rate_limit_per_second, config.rate_limit_burst);
+        likes_service = likes_service.with_rate_limiter(rate_limiter.clone());
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RATE_LIMIT_EVICTION_INTERVAL);
+            loop {
+                interval.tick().await;
+                let evicted = rate_limiter.evict_idle();
+                if evicted > 0 {
+                    debug!("Evicted {} idle rate limit buckets", evicted);
+                }
+            }
+        });
+    }
+
+    // Clients connect lazily, so call each dependency once to surface
+    // misconfiguration at boot rather than on the first request
+    let dependencies = likes_service
+        .check_dependencies(STARTUP_CHECK_TIMEOUT)
+        .await;
+    if dependencies.all_healthy() {
+        info!(
+            database = true,
+            user_service = true,
+            post_service = true,
+            "Startup self-check passed, all dependencies reachable"
+        );
+    } else if config.strict_startup {
+        return Err(anyhow::anyhow!(
+            "Startup self-check failed, unreachable: {}",
+            dependencies.unhealthy().join(", ")
+        ));
+    } else {
+        warn!(
+            database = dependencies.database,
+            user_service = dependencies.user_service,
+            post_service = dependencies.post_service,
+            "Startup self-check found unreachable dependencies, starting anyway: {}",
+            dependencies.unhealthy().join(", ")
+        );
+    }
+
+    // Shared by the gRPC server and the HTTP gateway
+    let likes_service = Arc::new(likes_service);
+
+    // Confirm or discard likes recorded while the Post Service was down
+    if config.lenient_post_validation {
+        let reconcile_service = likes_service.clone();
+        database_tasks.push(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+            loop {
+                interval.tick().await;
+                match reconcile_service.reconcile_unvalidated_likes().await {
+                    Ok(reconciliation) if reconciliation == Reconciliation::default() => {}
+                    Ok(reconciliation) => info!(
+                        "Reconciled unvalidated likes: {} confirmed, {} discarded, {} posts pending",
+                        reconciliation.confirmed, reconciliation.discarded, reconciliation.pending
+                    ),
+                    Err(e) => warn!("Failed to reconcile unvalidated likes: {}", e),
+                }
+            }
+        }));
+    }
+
+    // Verify bearer JWTs when a signing key or JWKS URL is configured
+    let auth = AuthInterceptor::new(JwtVerifier::from_config(&config).await?)
+        .require_token(config.auth_require_for_reads);
+    if auth.is_enabled() {
+        info!(
+            "JWT authentication enabled (required for reads: {})",
+            config.auth_require_for_reads
+        );
+    }
+
+    // Optional HTTP/JSON gateway for browser clients
+    let (gateway_shutdown_tx, gateway_shutdown_rx) = oneshot::channel::<()>();
+    let gateway = match config.gateway_port {
+        Some(port) => {
+            let gateway_addr: SocketAddr = format!("{}:{}", config.host, port).parse()?;
+            Some(tokio::spawn(gateway::serve(
+                gateway_addr,
+                likes_service.clone(),
+                auth.clone(),
+                async {
+                    let _ = gateway_shutdown_rx.await;
+                },
+            )))
+        }
+        None => None,
+    };
 
     // Build server address
     let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
@@ -79,18 +326,165 @@ async fn main() -> Result<()> {
         .build_v1()
         .unwrap();
 
-    Server::builder()
-        .add_service(proto::likes_service_server::LikesServiceServer::new(
-            likes_service,
-        ))
-        .add_service(reflection_service) // enable reflection
-        .serve_with_shutdown(addr, async {
-            signal::ctrl_c()
-                .await
-                .expect("Failed to install Ctrl+C handler");
-            println!("Shutting down...");
-        })
-        .await?;
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    // Keepalives stop load balancers from silently reaping idle connections
+    let mut server_builder = Server::builder()
+        .trace_fn(telemetry::server_span)
+        .tcp_keepalive(Some(Duration::from_secs(config.tcp_keepalive_seconds)))
+        .tcp_nodelay(config.tcp_nodelay)
+        .http2_keepalive_interval(Some(Duration::from_secs(
+            config.http2_keepalive_interval_seconds,
+        )))
+        .http2_keepalive_timeout(Some(Duration::from_secs(
+            config.http2_keepalive_timeout_seconds,
+        )));
+    if let Some(tls) = server_tls_config(&config)? {
+        server_builder = server_builder
+            .tls_config(tls)
+            .map_err(|e| anyhow::anyhow!("Invalid TLS certificate or key: {}", e))?;
+    }
+
+    let mut likes_server = LikesServiceServer::from_arc(likes_service)
+        .max_decoding_message_size(config.max_decoding_message_size)
+        .max_encoding_message_size(config.max_encoding_message_size);
+    if config.grpc_compression {
+        // Responses are only gzipped for clients advertising gzip support
+        likes_server = likes_server
+            .accept_compressed(CompressionEncoding::Gzip)
+            .send_compressed(CompressionEncoding::Gzip);
+    }
+
+    let access_log_level = config.log_level.parse().unwrap_or(Level::INFO);
+    // Backpressure protecting the database; 0 leaves concurrency unbounded
+    if config.max_concurrent_requests > 0 {
+        info!(
+            "Limiting to {} concurrent requests (queue timeout {}ms)",
+            config.max_concurrent_requests, config.concurrency_queue_timeout_ms
+        );
+    }
+    let concurrency_limit = ConcurrencyLimitLayer::new(
+        config.max_concurrent_requests,
+        Duration::from_millis(config.concurrency_queue_timeout_ms),
+    );
+    // Latency budgets by method; 0 means no timeout. Inside the concurrency
+    // limit, so time spent queueing for a slot doesn't count.
+    let mut method_timeouts = MethodTimeoutLayer::new(
+        (config.default_method_timeout_ms > 0)
+            .then(|| Duration::from_millis(config.default_method_timeout_ms)),
+    );
+    for (method, millis) in &config.method_timeouts_ms {
+        method_timeouts = method_timeouts.with_method(
+            method,
+            (*millis > 0).then(|| Duration::from_millis(*millis)),
+        );
+    }
+    let mut server = tokio::spawn(
+        server_builder
+            .layer(telemetry::RequestIdLayer)
+            .layer(telemetry::access_log_layer(access_log_level))
+            .layer(concurrency_limit)
+            .layer(method_timeouts)
+            .add_service(InterceptedService::new(likes_server, auth))
+            .add_service(reflection_service) // enable reflection
+            .add_service(health_service) // grpc.health.v1.Health
+            .serve_with_shutdown(addr, async {
+                let _ = shutdown_rx.await;
+            }),
+    );
+
+    tokio::select! {
+        // The server only finishes on its own if it failed
+        result = &mut server => {
+            result??;
+            return Ok(());
+        }
+        received = shutdown_signal() => {
+            info!(signal = received, "Received shutdown signal");
+        }
+    }
+
+    // Stop accepting new requests, then give in-flight ones the grace period to finish
+    let shutdown_started = Instant::now();
+    let grace = Duration::from_secs(config.shutdown_grace_seconds);
+    info!(
+        in_flight = in_flight.active(),
+        grace_seconds = grace.as_secs(),
+        "Shutting down, draining in-flight requests"
+    );
+    let _ = shutdown_tx.send(());
+    let _ = gateway_shutdown_tx.send(());
+    watchers.close();
+
+    let deadline = Instant::now() + grace;
+    if timeout_at(deadline, in_flight.wait_idle()).await.is_err() {
+        warn!(
+            in_flight = in_flight.active(),
+            "Shutdown grace period elapsed with requests still in flight"
+        );
+    } else {
+        info!(
+            elapsed_ms = shutdown_started.elapsed().as_millis() as u64,
+            "In-flight requests drained"
+        );
+    }
+
+    match timeout_at(deadline, &mut server).await {
+        Ok(result) => result??,
+        Err(_) => {
+            warn!("Server did not stop within the grace period, aborting");
+            server.abort();
+        }
+    }
+
+    if let Some(mut gateway) = gateway {
+        match timeout_at(deadline, &mut gateway).await {
+            Ok(result) => result??,
+            Err(_) => {
+                warn!("HTTP gateway did not stop within the grace period, aborting");
+                gateway.abort();
+            }
+        }
+    }
+
+    // Drop every other database handle first, so closing flushes the database
+    for task in database_tasks {
+        task.abort();
+        let _ = task.await;
+    }
+    database.close().await;
 
+    metrics_server.abort();
+    if let Some(Err(e)) = tracer_provider.map(|provider| provider.shutdown()) {
+        warn!("Failed to flush traces: {}", e);
+    }
+    info!(
+        elapsed_ms = shutdown_started.elapsed().as_millis() as u64,
+        "Shutdown complete"
+    );
     Ok(())
 }
+
+/// Resolves with the name of the first shutdown signal received: SIGTERM
+/// (sent by Kubernetes and most process managers) or Ctrl+C
+#[cfg(unix)]
+async fn shutdown_signal() -> &'static str {
+    let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+        .expect("Failed to install SIGTERM handler");
+
+    tokio::select! {
+        result = signal::ctrl_c() => {
+            result.expect("Failed to install Ctrl+C handler");
+            "SIGINT"
+        }
+        _ = terminate.recv() => "SIGTERM",
+    }
+}
+
+/// Resolves once Ctrl+C is pressed; other platforms have no SIGTERM
+#[cfg(not(unix))]
+async fn shutdown_signal() -> &'static str {
+    signal::ctrl_c()
+        .await
+        .expect("Failed to install Ctrl+C handler");
+    "Ctrl+C"
+}
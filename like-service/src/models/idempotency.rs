@@ -0,0 +1,10 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A `like_post` outcome remembered under a caller-supplied idempotency key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotentResponse {
+    pub success: bool,
+    pub message: String,
+    pub liked_at: Option<DateTime<Utc>>,
+}
@@ -1,28 +1,85 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use surrealdb::sql::Thing;
 use uuid::Uuid;
 
+/// Reaction a user left on a post; stored as a lowercase string in SurrealDB.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReactionType {
+    #[default]
+    Like,
+    Love,
+    Laugh,
+    Wow,
+    Sad,
+    Angry,
+}
+
+impl ReactionType {
+    pub const ALL: [ReactionType; 6] = [
+        ReactionType::Like,
+        ReactionType::Love,
+        ReactionType::Laugh,
+        ReactionType::Wow,
+        ReactionType::Sad,
+        ReactionType::Angry,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReactionType::Like => "like",
+            ReactionType::Love => "love",
+            ReactionType::Laugh => "laugh",
+            ReactionType::Wow => "wow",
+            ReactionType::Sad => "sad",
+            ReactionType::Angry => "angry",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Like {
     pub id: Option<Thing>,
     pub user_id: String,
     pub post_id: u32,
+    // Rows written before reactions existed have no value and read as Like
+    #[serde(default)]
+    pub reaction: ReactionType,
     pub liked_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // Set when the like is removed; soft-deleted rows are hidden from reads
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    // Bumped by every write, so a writer can tell its read went stale
+    #[serde(default)]
+    pub version: i64,
 }
 
 impl Like {
-    pub fn new(user_id: String, post_id: u32) -> Self {
-        let now = Utc::now();
+    pub fn new(user_id: String, post_id: u32, reaction: ReactionType) -> Self {
+        Self::new_at(user_id, post_id, reaction, Utc::now())
+    }
+
+    /// A like made at `now`
+    pub fn new_at(
+        user_id: String,
+        post_id: u32,
+        reaction: ReactionType,
+        now: DateTime<Utc>,
+    ) -> Self {
         Self {
             id: None,
             user_id,
             post_id,
+            reaction,
             liked_at: now,
             created_at: now,
             updated_at: now,
+            deleted_at: None,
+            version: 0,
         }
     }
 }
@@ -33,6 +90,199 @@ pub struct LikeCount {
     pub count: i64,
 }
 
+/// A post's active like count with its most recent `liked_at`; adding or
+/// removing a like changes at least one of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LikeCountStamp {
+    pub count: i64,
+    pub latest_liked_at: Option<DateTime<Utc>>,
+}
+
+impl LikeCountStamp {
+    /// Opaque validator for caches of the post's count, and of its
+    /// per-reaction counts when given. Stable within a build of the service.
+    pub fn etag(&self, post_id: u32, reactions: &[ReactionCount]) -> String {
+        let mut hasher = DefaultHasher::new();
+        post_id.hash(&mut hasher);
+        self.count.hash(&mut hasher);
+        self.latest_liked_at.hash(&mut hasher);
+        for reaction in reactions {
+            reaction.reaction.hash(&mut hasher);
+            reaction.count.hash(&mut hasher);
+        }
+        format!("{:016x}", hasher.finish())
+    }
+}
+
+/// Outcome of moving one user's likes to another
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LikeMerge {
+    /// Rows reassigned to the target user, removed likes included
+    pub moved: usize,
+    /// Rows left behind because the target has a row for the same post
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReactionCount {
+    pub reaction: ReactionType,
+    pub count: i64,
+}
+
+/// Width of one bucket of a likes histogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketWidth {
+    Hour,
+    Day,
+}
+
+impl BucketWidth {
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            BucketWidth::Hour => chrono::Duration::hours(1),
+            BucketWidth::Day => chrono::Duration::days(1),
+        }
+    }
+
+    /// A fixed SurrealDB duration literal, safe to splice into a query
+    pub fn as_surql(self) -> &'static str {
+        match self {
+            BucketWidth::Hour => "1h",
+            BucketWidth::Day => "1d",
+        }
+    }
+
+    /// Start of the bucket containing `at`. Buckets are aligned to the Unix
+    /// epoch in UTC, as SurrealDB's `time::floor` aligns them.
+    pub fn floor(self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let width = self.duration().num_seconds();
+        let seconds = at.timestamp();
+        DateTime::from_timestamp(seconds - seconds.rem_euclid(width), 0).unwrap_or(at)
+    }
+}
+
+/// Active likes made within the bucket starting at `bucket_start`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketCount {
+    pub bucket_start: DateTime<Utc>,
+    pub count: i64,
+}
+
+/// Position after the last entry of an activity feed page. The feed is
+/// ordered by `liked_at` and then record id, both descending.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedCursor {
+    pub liked_at: DateTime<Utc>,
+    pub id: String,
+}
+
+impl FeedCursor {
+    pub fn from_like(like: &Like) -> Option<Self> {
+        like.id.as_ref().map(|thing| Self {
+            liked_at: like.liked_at,
+            id: thing.id.to_raw(),
+        })
+    }
+
+    /// Page token handed to clients, who treat it as opaque
+    pub fn encode(&self) -> String {
+        format!(
+            "{}|{}",
+            self.liked_at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            self.id
+        )
+    }
+
+    pub fn decode(token: &str) -> Option<Self> {
+        let (liked_at, id) = token.split_once('|')?;
+        if id.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            liked_at: DateTime::parse_from_rfc3339(liked_at)
+                .ok()?
+                .with_timezone(&Utc),
+            id: id.to_string(),
+        })
+    }
+}
+
+/// Page size limits for one endpoint. A requested limit below `min_limit`
+/// falls back to `default_limit`; one above `max_limit` is clamped to it.
+/// Pages starting past `max_offset` rows are refused, since the database
+/// still walks every skipped row.
+#[derive(Debug, Clone, Copy)]
+pub struct PaginationBounds {
+    pub min_limit: i32,
+    pub default_limit: i32,
+    pub max_limit: i32,
+    pub max_offset: i32,
+}
+
+impl Default for PaginationBounds {
+    fn default() -> Self {
+        Self {
+            min_limit: 1,
+            default_limit: 10,
+            max_limit: 100,
+            max_offset: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LikeSortField {
+    #[default]
+    CreatedAt,
+    LikedAt,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Ordering for like listings; defaults to newest `created_at` first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LikeSort {
+    pub field: LikeSortField,
+    pub direction: SortDirection,
+}
+
+impl LikeSort {
+    /// A fixed ORDER BY clause, so no caller input is ever spliced into a query
+    pub fn order_by_clause(&self) -> &'static str {
+        match (self.field, self.direction) {
+            (LikeSortField::CreatedAt, SortDirection::Asc) => "ORDER BY created_at ASC",
+            (LikeSortField::CreatedAt, SortDirection::Desc) => "ORDER BY created_at DESC",
+            (LikeSortField::LikedAt, SortDirection::Asc) => "ORDER BY liked_at ASC",
+            (LikeSortField::LikedAt, SortDirection::Desc) => "ORDER BY liked_at DESC",
+        }
+    }
+}
+
+/// Inclusive bounds on `liked_at`; an unset bound leaves that side open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LikedAtRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl LikedAtRange {
+    /// A fixed filter on `$from`/`$to`, to splice after the other WHERE conditions
+    pub fn where_clause(&self) -> &'static str {
+        match (self.from.is_some(), self.to.is_some()) {
+            (false, false) => "",
+            (true, false) => "AND liked_at >= <datetime>$from",
+            (false, true) => "AND liked_at <= <datetime>$to",
+            (true, true) => "AND liked_at >= <datetime>$from AND liked_at <= <datetime>$to",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PaginationParams {
     pub page: i32,
@@ -41,11 +291,15 @@ pub struct PaginationParams {
 
 impl PaginationParams {
     pub fn new(page: i32, limit: i32) -> Self {
+        Self::with_bounds(page, limit, &PaginationBounds::default())
+    }
+
+    pub fn with_bounds(page: i32, limit: i32, bounds: &PaginationBounds) -> Self {
         let page = if page < 1 { 1 } else { page };
-        let limit = if limit < 1 {
-            10
-        } else if limit > 100 {
-            100
+        let limit = if limit < bounds.min_limit {
+            bounds.default_limit
+        } else if limit > bounds.max_limit {
+            bounds.max_limit
         } else {
             limit
         };
@@ -54,7 +308,12 @@ impl PaginationParams {
     }
 
     pub fn offset(&self) -> i32 {
-        (self.page - 1) * self.limit
+        (self.page - 1).saturating_mul(self.limit)
+    }
+
+    /// Whether the page starts within `bounds.max_offset`
+    pub fn within_depth(&self, bounds: &PaginationBounds) -> bool {
+        self.offset() <= bounds.max_offset
     }
 }
 
@@ -65,11 +324,20 @@ pub struct PaginatedResult<T> {
     pub current_page: i32,
     pub total_pages: i32,
     pub limit: i32,
+    pub has_next: bool,
+    pub has_previous: bool,
+    pub offset: i32,
 }
 
 impl<T> PaginatedResult<T> {
+    /// `total_pages` is never 0: an empty result is a single empty page, so
+    /// `current_page: 1` is always within range. A zero limit counts as 1.
+    /// `has_next`/`has_previous` follow from the page position, so a page
+    /// past the end has a previous page but no next one.
     pub fn new(data: Vec<T>, total_count: i64, params: &PaginationParams) -> Self {
-        let total_pages = ((total_count as f64) / (params.limit as f64)).ceil() as i32;
+        let limit = i64::from(params.limit.max(1));
+        let total_pages = (total_count.max(0) + limit - 1) / limit;
+        let total_pages = total_pages.clamp(1, i64::from(i32::MAX)) as i32;
 
         Self {
             data,
@@ -77,6 +345,9 @@ impl<T> PaginatedResult<T> {
             current_page: params.page,
             total_pages,
             limit: params.limit,
+            has_next: params.page < total_pages,
+            has_previous: params.page > 1,
+            offset: params.offset(),
         }
     }
 }
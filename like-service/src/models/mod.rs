@@ -1,2 +1,9 @@
+pub mod idempotency;
 pub mod like;
-pub use like::{Like, PaginatedResult, PaginationParams};
+
+pub use idempotency::IdempotentResponse;
+pub use like::{
+    BucketCount, BucketWidth, FeedCursor, Like, LikeCount, LikeCountStamp, LikeMerge, LikeSort,
+    LikeSortField, LikedAtRange, PaginatedResult, PaginationBounds, PaginationParams,
+    ReactionCount, ReactionType, SortDirection,
+};
@@ -1,303 +1,1642 @@
 use crate::{
-    database::Database,
+    clock::{Clock, SystemClock},
+    database::{
+        Database,
+        migrations::{self, SchemaReport},
+    },
     error::{LikesError, Result},
-    models::{Like, PaginatedResult, PaginationParams},
+    models::{
+        BucketCount, BucketWidth, FeedCursor, IdempotentResponse, Like, LikeCount, LikeCountStamp,
+        LikeMerge, LikeSort, LikedAtRange, PaginatedResult, PaginationParams, ReactionCount,
+        ReactionType,
+    },
+    repository::write_buffer::{BufferedLike, LikeWriteBuffer},
+    telemetry::record_cache_lookup,
 };
 use chrono::{DateTime, Utc};
-use tracing::{debug, error};
+use dashmap::DashMap;
+use moka::future::Cache;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use surrealdb::sql::Thing;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, warn};
+
+/// Read the `count` of a `count() ... GROUP ALL` query. Depending on the
+/// SurrealDB version the row is `{ "count": n }` or wrapped as
+/// `[{ "count": n }]`; no row at all means nothing matched.
+fn parse_count(value: Option<serde_json::Value>) -> i64 {
+    let row = match value {
+        None | Some(serde_json::Value::Null) => return 0,
+        Some(serde_json::Value::Array(rows)) if rows.len() <= 1 => match rows.into_iter().next() {
+            Some(row) => row,
+            None => return 0,
+        },
+        Some(row) => row,
+    };
+
+    let count = row.get("count").and_then(|count| {
+        count
+            .as_i64()
+            .or_else(|| count.as_f64().map(|count| count as i64))
+    });
+    match count {
+        Some(count) => count,
+        None => {
+            // Reading 0 here would hide real likes, so make the mismatch visible
+            warn!("Unexpected shape of count result: {}", row);
+            0
+        }
+    }
+}
+
+// Version a versioned write expects when the user has no row for the post
+const NO_ROW_VERSION: i64 = -1;
+
+// Attempts at a versioned write before reporting the conflict
+const MAX_VERSION_ATTEMPTS: usize = 3;
+
+// Sample users and posts demo likes are spread over
+const DEMO_USERS: usize = 50;
+const DEMO_POSTS: usize = 100;
+
+/// Most demo likes `seed_demo_data` inserts: one per sample user and post
+pub const MAX_DEMO_LIKES: usize = DEMO_USERS * DEMO_POSTS;
+
+#[derive(Debug, Serialize)]
+struct DemoLike {
+    user_id: String,
+    post_id: u32,
+    reaction: ReactionType,
+    liked_at: DateTime<Utc>,
+}
+
+// A like's state before a buffered batch was written
+#[derive(Debug, Deserialize)]
+struct PriorLike {
+    user_id: String,
+    post_id: u32,
+    reaction: ReactionType,
+    #[serde(default)]
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostId {
+    post_id: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostLikedAt {
+    post_id: u32,
+    liked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserLikedAt {
+    user_id: String,
+    liked_at: DateTime<Utc>,
+}
+
+// Rows walked by a likes stream
+#[derive(Debug)]
+enum StreamScope {
+    // A post's active likes
+    Post(u32),
+    // Every like of a user, removed ones included
+    User(String),
+}
+
+impl StreamScope {
+    fn condition(&self) -> &'static str {
+        match self {
+            Self::Post(_) => "post_id = $post_id AND deleted_at = NONE",
+            Self::User(_) => "user_id = $user_id",
+        }
+    }
+}
+
+// Identifies one cached `get_post_likes` page of a post
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PageKey {
+    page: i32,
+    limit: i32,
+    sort: LikeSort,
+}
+
+// Cached pages of one post, dropped together when the post's likes change
+type PostPages = DashMap<PageKey, PaginatedResult<Like>>;
 
 #[derive(Debug, Clone)]
 pub struct LikesRepository {
     db: Database,
+    // Send each user's queries over one pooled connection, so a user's reads
+    // see their own writes even when connections reach different nodes
+    read_your_writes: bool,
+    // Recent `get_post_likes` pages by post, when enabled
+    post_likes_cache: Option<Cache<u32, Arc<PostPages>>>,
+    // Coalesces `create_like` calls into batched writes, when enabled
+    write_buffer: Option<LikeWriteBuffer>,
+    // Stamps every write and cutoff instead of the database's own clock
+    clock: Arc<dyn Clock>,
 }
 
 impl LikesRepository {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            read_your_writes: false,
+            post_likes_cache: None,
+            write_buffer: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Take timestamps, cutoffs and expiries from `clock`
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_read_your_writes(mut self, enabled: bool) -> Self {
+        self.read_your_writes = enabled;
+        self
+    }
+
+    /// Cache `get_post_likes` pages of up to `capacity` posts for `ttl`.
+    /// Writes through this repository drop the pages of the posts they
+    /// touch; other instances' writes show up once the pages expire.
+    pub fn with_post_likes_cache(mut self, ttl: Duration, capacity: u64) -> Self {
+        self.post_likes_cache = Some(
+            Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(ttl)
+                .build(),
+        );
+        self
+    }
+
+    /// Send `create_like` through `buffer`. Attach it last: the buffer
+    /// writes through the repository it was spawned with.
+    pub fn with_write_buffer(mut self, buffer: LikeWriteBuffer) -> Self {
+        self.write_buffer = Some(buffer);
+        self
+    }
+
+    // The same repository writing likes directly, for the buffer's own writes
+    pub(super) fn without_write_buffer(mut self) -> Self {
+        self.write_buffer = None;
+        self
+    }
+
+    // Connection the user's writes go over when read-your-writes pins them;
+    // likes of users sharing one may be written in one buffered batch
+    pub(super) fn write_connection(&self, user_id: &str) -> usize {
+        if self.read_your_writes {
+            self.db.pinned_connection(user_id)
+        } else {
+            0
+        }
+    }
+
+    // Drop the cached pages of the posts of `post_ids`
+    async fn invalidate_post_pages(&self, post_ids: impl IntoIterator<Item = u32>) {
+        if let Some(cache) = &self.post_likes_cache {
+            for post_id in post_ids {
+                cache.invalidate(&post_id).await;
+            }
+        }
+    }
+
+    // Connection for queries scoped to one user
+    fn db_for_user(&self, user_id: &str) -> Database {
+        if self.read_your_writes {
+            self.db.pinned(user_id)
+        } else {
+            self.db.clone()
+        }
+    }
+
+    // Connection for read-only queries: the read replica, if any
+    fn db_for_reads(&self) -> Database {
+        self.db.reader()
+    }
+
+    // Connection for read-only queries scoped to one user. With
+    // read-your-writes they stay on the user's primary connection, since the
+    // replica may not have their latest like yet.
+    fn db_for_user_reads(&self, user_id: &str) -> Database {
+        if self.read_your_writes {
+            self.db.pinned(user_id)
+        } else {
+            self.db.reader()
+        }
+    }
+
+    pub async fn create_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        reaction: ReactionType,
+    ) -> Result<Like> {
+        match &self.write_buffer {
+            Some(buffer) => {
+                Self::check_like_ids(user_id, post_id)?;
+                buffer.create_like(user_id, post_id, reaction).await
+            }
+            None => self.insert_like(user_id, post_id, reaction, false).await,
+        }
+    }
+
+    /// Like a post that could not be checked against the Post Service. The
+    /// row is flagged `unvalidated` until reconciliation confirms or
+    /// discards it.
+    pub async fn create_unvalidated_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        reaction: ReactionType,
+    ) -> Result<Like> {
+        self.insert_like(user_id, post_id, reaction, true).await
     }
 
-    pub async fn create_like(&self, user_id: &str, post_id: &u32) -> Result<Like> {
+    async fn insert_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        reaction: ReactionType,
+        unvalidated: bool,
+    ) -> Result<Like> {
         debug!("Creating like for user {} on post {}", user_id, post_id);
+        Self::check_like_ids(user_id, post_id)?;
+
+        let like = Like::new_at(user_id.to_string(), *post_id, reaction, self.clock.now());
+        debug!("Creating like record: {:?}", like);
+
+        let like = self
+            .retry_on_conflict(user_id, post_id, |expected| {
+                self.try_insert_like(&like, unvalidated, expected)
+            })
+            .await?;
+        self.invalidate_post_pages([*post_id]).await;
+        Ok(like)
+    }
 
-        // Validate input
+    // Both IDs must be set before a like is written or buffered
+    fn check_like_ids(user_id: &str, post_id: &u32) -> Result<()> {
         if user_id.is_empty() {
             return Err(LikesError::InvalidInput(
                 "User ID cannot be empty".to_string(),
             ));
         }
 
-        if *post_id <= 0 {
+        if *post_id == 0 {
             return Err(LikesError::InvalidInput(
                 "Post ID must be a positive integer".to_string(),
             ));
         }
 
-        let like = Like::new(user_id.to_string(), post_id.clone());
-        debug!("Creating like record: {:?}", like);
+        Ok(())
+    }
 
+    // One attempt at `insert_like`, applied only while the row is still at
+    // the `expected` version
+    async fn try_insert_like(&self, like: &Like, unvalidated: bool, expected: i64) -> Result<Like> {
+        // A user has at most one row per post (the likes_user_post index):
+        // liking with a different reaction updates the reaction and refreshes
+        // liked_at, and a previously unliked (soft-deleted) row is revived.
+        // Liking again with the same reaction hits the index and is reported
+        // as AlreadyExists.
         let query = r#"
-            CREATE likes SET 
-                id = $id,
-                user_id = $user_id,
-                post_id = $post_id,
-                liked_at = time::now(),
-                created_at = time::now(),
-                updated_at = time::now();
+            BEGIN TRANSACTION;
+            LET $current = (
+                SELECT VALUE version ?? 0 FROM likes
+                WHERE user_id = $user_id AND post_id = $post_id
+            )[0] ?? $no_row;
+            IF $current != $expected { THROW "version conflict"; };
+            LET $changed = (
+                UPDATE likes SET
+                    reaction = $reaction,
+                    unvalidated = $unvalidated,
+                    liked_at = <datetime>$now,
+                    updated_at = <datetime>$now,
+                    version = $expected + 1
+                WHERE user_id = $user_id AND post_id = $post_id AND deleted_at = NONE
+                    AND (reaction ?? 'like') != $reaction AND (version ?? 0) = $expected
+            );
+            LET $revived = (
+                UPDATE likes SET
+                    deleted_at = NONE,
+                    reaction = $reaction,
+                    unvalidated = $unvalidated,
+                    liked_at = <datetime>$now,
+                    updated_at = <datetime>$now,
+                    version = $expected + 1
+                WHERE user_id = $user_id AND post_id = $post_id AND deleted_at != NONE
+                    AND (version ?? 0) = $expected
+            );
+            IF array::len($changed) = 0 AND array::len($revived) = 0 {
+                CREATE likes SET
+                    id = $id,
+                    user_id = $user_id,
+                    post_id = $post_id,
+                    reaction = $reaction,
+                    unvalidated = $unvalidated,
+                    liked_at = <datetime>$now,
+                    created_at = <datetime>$now,
+                    updated_at = <datetime>$now,
+                    version = 0;
+            };
+            SELECT * FROM likes
+            WHERE user_id = $user_id AND post_id = $post_id AND deleted_at = NONE
+            LIMIT 1;
+            COMMIT TRANSACTION;
         "#;
 
+        // The row is read back in the same transaction, on the same connection,
+        // so success is only reported once the like is visible
         let mut result = self
-            .db
+            .db_for_user(&like.user_id)
             .query_builder(query)
+            .bind("now", self.clock.now())
             .bind("id", like.id.clone())
             .bind("user_id", like.user_id.clone())
-            .bind("post_id", like.post_id.clone())
-            .bind("liked_at", like.liked_at)
-            .bind("created_at", like.created_at)
-            .bind("updated_at", like.updated_at)
+            .bind("post_id", like.post_id)
+            .bind("reaction", like.reaction)
+            .bind("unvalidated", unvalidated)
+            .bind("expected", expected)
+            .bind("no_row", NO_ROW_VERSION)
             .execute()
             .await
             .map_err(|e| {
                 error!("Failed to create like: {}", e);
-                if e.to_string().contains("duplicate") {
-                    LikesError::AlreadyExists("User has already liked this post".to_string())
-                } else {
-                    println!("SurrealDB error creating like: {:?}", e);
-                    LikesError::Database(e)
-                }
+                e
             })?;
 
-        let created_like: Option<Like> = result.take(0)?;
+        // Statement errors (including unique index violations) are reported
+        // per statement rather than by `execute`
+        let errors = result.take_errors();
+        if errors.values().any(Self::is_conflict_error) {
+            return Err(Self::version_conflict(&like.user_id, &like.post_id));
+        }
+        if errors.values().any(Self::is_duplicate_error) {
+            return Err(LikesError::AlreadyExists(
+                "User has already liked this post".to_string(),
+            ));
+        }
+        if let Some(e) = Self::first_error(errors) {
+            error!("Failed to create like: {}", e);
+            return Err(LikesError::Database(e));
+        }
+
+        let last = result.num_statements().saturating_sub(1);
+        let created_like: Option<Like> = result.take(last)?;
         created_like.ok_or_else(|| LikesError::Internal("Failed to create like".to_string()))
     }
 
+    // Write a buffered batch of likes, each for a different user/post pair, in
+    // one transaction. Each like gets the outcome `create_like` would have
+    // given it; an error means nothing was written. Every like must have the
+    // same `write_connection`, which the batch is sent over.
+    pub(super) async fn insert_likes_batch(
+        &self,
+        likes: &[BufferedLike],
+    ) -> Result<Vec<Result<Like>>> {
+        debug!("Writing a batch of {} buffered likes", likes.len());
+        let Some(first) = likes.first() else {
+            return Ok(Vec::new());
+        };
+
+        // Per like, the same writes as `try_insert_like`: change the reaction,
+        // revive a removed like or create the row. Liking again with the same
+        // reaction leaves the row alone and is reported from the prior state.
+        let query = r#"
+            BEGIN TRANSACTION;
+            SELECT user_id, post_id, reaction ?? 'like' AS reaction, deleted_at FROM likes
+            WHERE user_id IN $user_ids AND post_id IN $post_ids;
+            FOR $like IN $likes {
+                IF array::len((
+                    SELECT id FROM likes
+                    WHERE user_id = $like.user_id AND post_id = $like.post_id
+                )) = 0 {
+                    CREATE likes SET
+                        user_id = $like.user_id,
+                        post_id = $like.post_id,
+                        reaction = $like.reaction,
+                        unvalidated = false,
+                        liked_at = <datetime>$now,
+                        created_at = <datetime>$now,
+                        updated_at = <datetime>$now,
+                        version = 0;
+                } ELSE {
+                    UPDATE likes SET
+                        deleted_at = NONE,
+                        reaction = $like.reaction,
+                        unvalidated = false,
+                        liked_at = <datetime>$now,
+                        updated_at = <datetime>$now,
+                        version = (version ?? 0) + 1
+                    WHERE user_id = $like.user_id AND post_id = $like.post_id
+                        AND (deleted_at != NONE OR (reaction ?? 'like') != $like.reaction);
+                };
+            };
+            SELECT * FROM likes
+            WHERE user_id IN $user_ids AND post_id IN $post_ids AND deleted_at = NONE;
+            COMMIT TRANSACTION;
+        "#;
+
+        let user_ids: HashSet<&str> = likes.iter().map(|like| like.user_id.as_str()).collect();
+        let post_ids: HashSet<u32> = likes.iter().map(|like| like.post_id).collect();
+
+        let mut result = self
+            .db_for_user(&first.user_id)
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .bind("likes", likes.to_vec())
+            .bind(
+                "user_ids",
+                user_ids.into_iter().map(str::to_string).collect::<Vec<_>>(),
+            )
+            .bind("post_ids", post_ids.into_iter().collect::<Vec<_>>())
+            .execute()
+            .await?;
+
+        let errors = result.take_errors();
+        if errors.values().any(Self::is_duplicate_error) {
+            return Err(LikesError::AlreadyExists(
+                "A concurrent request liked one of these posts".to_string(),
+            ));
+        }
+        if let Some(e) = Self::first_error(errors) {
+            error!("Failed to write buffered likes: {}", e);
+            return Err(LikesError::Database(e));
+        }
+
+        // The selects span every user and post of the batch, so they may
+        // hold pairs nobody asked for; only the batch's own pairs are read
+        let prior: Vec<PriorLike> = result.take(0)?;
+        let prior: HashMap<(String, u32), PriorLike> = prior
+            .into_iter()
+            .map(|like| ((like.user_id.clone(), like.post_id), like))
+            .collect();
+        let last = result.num_statements().saturating_sub(1);
+        let written: Vec<Like> = result.take(last)?;
+        let mut written: HashMap<(String, u32), Like> = written
+            .into_iter()
+            .map(|like| ((like.user_id.clone(), like.post_id), like))
+            .collect();
+
+        self.invalidate_post_pages(likes.iter().map(|like| like.post_id))
+            .await;
+
+        Ok(likes
+            .iter()
+            .map(|like| {
+                let key = (like.user_id.clone(), like.post_id);
+                match prior.get(&key) {
+                    Some(prior)
+                        if prior.deleted_at.is_none() && prior.reaction == like.reaction =>
+                    {
+                        Err(LikesError::AlreadyExists(
+                            "User has already liked this post".to_string(),
+                        ))
+                    }
+                    _ => written
+                        .remove(&key)
+                        .ok_or_else(|| LikesError::Internal("Failed to create like".to_string())),
+                }
+            })
+            .collect())
+    }
+
+    /// Like many posts for one user in a single transaction. Soft-deleted likes
+    /// are revived and posts the user already likes are left untouched.
+    /// Maps each post ID to `true` if it was liked now, `false` if it already was.
+    pub async fn create_likes(
+        &self,
+        user_id: &str,
+        post_ids: &[u32],
+        reaction: ReactionType,
+    ) -> Result<HashMap<u32, bool>> {
+        debug!("Creating {} likes for user {}", post_ids.len(), user_id);
+
+        let query = r#"
+            BEGIN TRANSACTION;
+            SELECT VALUE post_id FROM likes
+            WHERE user_id = $user_id AND post_id IN $post_ids AND deleted_at = NONE;
+            LET $existing = (
+                SELECT VALUE post_id FROM likes
+                WHERE user_id = $user_id AND post_id IN $post_ids
+            );
+            UPDATE likes SET
+                deleted_at = NONE,
+                reaction = $reaction,
+                liked_at = <datetime>$now,
+                updated_at = <datetime>$now,
+                version = (version ?? 0) + 1
+            WHERE user_id = $user_id AND post_id IN $post_ids AND deleted_at != NONE;
+            FOR $post_id IN array::complement($post_ids, $existing) {
+                CREATE likes SET
+                    user_id = $user_id,
+                    post_id = $post_id,
+                    reaction = $reaction,
+                    liked_at = <datetime>$now,
+                    created_at = <datetime>$now,
+                    updated_at = <datetime>$now,
+                    version = 0;
+            };
+            COMMIT TRANSACTION;
+        "#;
+
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .bind("user_id", user_id.to_string())
+            .bind("post_ids", post_ids.to_vec())
+            .bind("reaction", reaction)
+            .execute()
+            .await?;
+
+        let errors = result.take_errors();
+        if errors.values().any(Self::is_duplicate_error) {
+            return Err(LikesError::AlreadyExists(
+                "A concurrent request liked one of these posts, retry the batch".to_string(),
+            ));
+        }
+        if let Some(e) = Self::first_error(errors) {
+            error!("Failed to create likes: {}", e);
+            return Err(LikesError::Database(e));
+        }
+
+        let already_liked: Vec<u32> = result.take(0)?;
+        let liked: HashMap<u32, bool> = post_ids
+            .iter()
+            .map(|post_id| (*post_id, !already_liked.contains(post_id)))
+            .collect();
+        self.invalidate_post_pages(
+            liked
+                .iter()
+                .filter(|(_, newly_liked)| **newly_liked)
+                .map(|(post_id, _)| *post_id),
+        )
+        .await;
+        Ok(liked)
+    }
+
     pub async fn delete_like(&self, user_id: &str, post_id: &u32) -> Result<bool> {
         debug!("Deleting like for user {} on post {}", user_id, post_id);
 
+        let deleted = self
+            .retry_on_conflict(user_id, post_id, |expected| {
+                self.try_delete_like(user_id, post_id, expected)
+            })
+            .await?;
+        if deleted {
+            self.invalidate_post_pages([*post_id]).await;
+        }
+        Ok(deleted)
+    }
+
+    // One attempt at `delete_like`, applied only while the row is still at
+    // the `expected` version
+    async fn try_delete_like(&self, user_id: &str, post_id: &u32, expected: i64) -> Result<bool> {
+        // Soft delete: keep the row for auditing and mark when it was removed
         let query = r#"
-            DELETE FROM likes WHERE user_id = $user_id AND post_id = $post_id;
+            BEGIN TRANSACTION;
+            LET $current = (
+                SELECT VALUE version ?? 0 FROM likes
+                WHERE user_id = $user_id AND post_id = $post_id
+            )[0] ?? $no_row;
+            IF $current != $expected { THROW "version conflict"; };
+            UPDATE likes SET
+                deleted_at = <datetime>$now,
+                updated_at = <datetime>$now,
+                version = $expected + 1
+            WHERE user_id = $user_id AND post_id = $post_id AND deleted_at = NONE
+                AND (version ?? 0) = $expected;
+            COMMIT TRANSACTION;
         "#;
 
         let mut result = self
-            .db
+            .db_for_user(user_id)
             .query_builder(query)
+            .bind("now", self.clock.now())
             .bind("user_id", user_id.to_string())
             .bind("post_id", *post_id)
+            .bind("expected", expected)
+            .bind("no_row", NO_ROW_VERSION)
             .execute()
-            .await
-            .map_err(LikesError::Database)?;
+            .await?;
 
-        let deleted: Vec<Like> = result.take(0)?;
+        let errors = result.take_errors();
+        if errors.values().any(Self::is_conflict_error) {
+            return Err(Self::version_conflict(user_id, post_id));
+        }
+        if let Some(e) = Self::first_error(errors) {
+            error!("Failed to delete like: {}", e);
+            return Err(LikesError::Database(e));
+        }
+
+        let last = result.num_statements().saturating_sub(1);
+        let deleted: Vec<Like> = result.take(last)?;
         Ok(!deleted.is_empty())
     }
 
+    /// Flips the like state for the user/post pair and returns the resulting
+    /// `liked_at` when the post ends up liked, or `None` when it was unliked.
+    pub async fn toggle_like(&self, user_id: &str, post_id: &u32) -> Result<Option<DateTime<Utc>>> {
+        debug!("Toggling like for user {} on post {}", user_id, post_id);
+
+        if user_id.is_empty() {
+            return Err(LikesError::InvalidInput(
+                "User ID cannot be empty".to_string(),
+            ));
+        }
+
+        if *post_id == 0 {
+            return Err(LikesError::InvalidInput(
+                "Post ID must be a positive integer".to_string(),
+            ));
+        }
+
+        let liked_at = self
+            .retry_on_conflict(user_id, post_id, |expected| {
+                self.try_toggle_like(user_id, post_id, expected)
+            })
+            .await?;
+        self.invalidate_post_pages([*post_id]).await;
+        Ok(liked_at)
+    }
+
+    // One attempt at `toggle_like`, applied only while the row is still at
+    // the `expected` version
+    async fn try_toggle_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        expected: i64,
+    ) -> Result<Option<DateTime<Utc>>> {
+        // The existence check and the create/delete run in one transaction so
+        // two simultaneous toggles can't both insert a row.
+        let query = r#"
+            BEGIN TRANSACTION;
+            LET $current = (
+                SELECT VALUE version ?? 0 FROM likes
+                WHERE user_id = $user_id AND post_id = $post_id
+            )[0] ?? $no_row;
+            IF $current != $expected { THROW "version conflict"; };
+            LET $existing = (
+                SELECT id FROM likes
+                WHERE user_id = $user_id AND post_id = $post_id AND deleted_at = NONE
+            );
+            IF array::len($existing) > 0 {
+                UPDATE likes SET
+                    deleted_at = <datetime>$now,
+                    updated_at = <datetime>$now,
+                    version = $expected + 1
+                WHERE user_id = $user_id AND post_id = $post_id AND deleted_at = NONE
+                    AND (version ?? 0) = $expected;
+            } ELSE {
+                LET $revived = (
+                    UPDATE likes SET
+                        deleted_at = NONE,
+                        liked_at = <datetime>$now,
+                        updated_at = <datetime>$now,
+                        version = $expected + 1
+                    WHERE user_id = $user_id AND post_id = $post_id AND deleted_at != NONE
+                        AND (version ?? 0) = $expected
+                );
+                IF array::len($revived) = 0 {
+                    CREATE likes SET
+                        user_id = $user_id,
+                        post_id = $post_id,
+                        liked_at = <datetime>$now,
+                        created_at = <datetime>$now,
+                        updated_at = <datetime>$now,
+                        version = 0;
+                };
+            };
+            SELECT * FROM likes
+            WHERE user_id = $user_id AND post_id = $post_id AND deleted_at = NONE
+            LIMIT 1;
+            COMMIT TRANSACTION;
+        "#;
+
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .bind("user_id", user_id.to_string())
+            .bind("post_id", *post_id)
+            .bind("expected", expected)
+            .bind("no_row", NO_ROW_VERSION)
+            .execute()
+            .await?;
+
+        let errors = result.take_errors();
+        if errors.values().any(Self::is_conflict_error) {
+            return Err(Self::version_conflict(user_id, post_id));
+        }
+        if errors.values().any(Self::is_duplicate_error) {
+            // A concurrent toggle created the row first, so the unique
+            // index rejected ours; the post is liked either way.
+            debug!(
+                "Concurrent like detected for user {} on post {}",
+                user_id, post_id
+            );
+            // Read from the primary: a replica may not have that row yet
+            let like = self.get_like_including_deleted(user_id, post_id).await?;
+            return Ok(like
+                .filter(|like| like.deleted_at.is_none())
+                .map(|like| like.liked_at));
+        }
+        if let Some(e) = Self::first_error(errors) {
+            error!("Failed to toggle like: {}", e);
+            return Err(LikesError::Database(e));
+        }
+
+        let last = result.num_statements().saturating_sub(1);
+        let like: Option<Like> = result.take(last)?;
+        Ok(like.map(|l| l.liked_at))
+    }
+
+    fn is_duplicate_error(error: &surrealdb::Error) -> bool {
+        let message = error.to_string();
+        message.contains("already contains") || message.contains("duplicate")
+    }
+
+    // Our version check failing, or the engine rejecting a transaction that
+    // raced another one over the same row
+    fn is_conflict_error(error: &surrealdb::Error) -> bool {
+        error.to_string().contains("conflict")
+    }
+
+    fn version_conflict(user_id: &str, post_id: &u32) -> LikesError {
+        LikesError::Conflict(format!(
+            "Like of post {} by user {} was changed concurrently, retry the request",
+            post_id, user_id
+        ))
+    }
+
+    // Current version of the user's row for the post, or `NO_ROW_VERSION`
+    async fn like_version(&self, user_id: &str, post_id: &u32) -> Result<i64> {
+        let query = r#"
+            SELECT VALUE version ?? 0 FROM likes
+            WHERE user_id = $user_id AND post_id = $post_id
+            LIMIT 1;
+        "#;
+
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("user_id", user_id.to_string())
+            .bind("post_id", *post_id)
+            .execute()
+            .await?;
+
+        let versions: Vec<i64> = result.take(0)?;
+        Ok(versions.first().copied().unwrap_or(NO_ROW_VERSION))
+    }
+
+    // Run a versioned write against the version read just before it, reading
+    // again and retrying while concurrent writers keep winning
+    async fn retry_on_conflict<T, F, Fut>(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        mut write: F,
+    ) -> Result<T>
+    where
+        F: FnMut(i64) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let expected = self.like_version(user_id, post_id).await?;
+            match write(expected).await {
+                Err(LikesError::Conflict(_)) if attempt < MAX_VERSION_ATTEMPTS => {
+                    debug!(
+                        "Version conflict on post {} for user {} (attempt {}), retrying",
+                        post_id, user_id, attempt
+                    );
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // Errors are keyed by statement index; report the earliest one
+    fn first_error(errors: HashMap<usize, surrealdb::Error>) -> Option<surrealdb::Error> {
+        errors
+            .into_iter()
+            .min_by_key(|(index, _)| *index)
+            .map(|(_, e)| e)
+    }
+
     pub async fn get_user_likes(
         &self,
         user_id: &str,
         params: &PaginationParams,
+        sort: LikeSort,
+        range: LikedAtRange,
     ) -> Result<PaginatedResult<Like>> {
         debug!(
-            "Getting likes for user {} (page: {}, limit: {})",
-            user_id, params.page, params.limit
+            "Getting likes for user {} (page: {}, limit: {}, range: {:?})",
+            user_id, params.page, params.limit, range
         );
 
-        // Get total count
-        let count_query = "SELECT count() FROM likes WHERE user_id = $user_id GROUP ALL;";
+        // Get total count, over the same range as the page
+        let count_query = format!(
+            "SELECT count() FROM likes WHERE user_id = $user_id AND deleted_at = NONE {} GROUP ALL;",
+            range.where_clause()
+        );
         let mut count_result = self
-            .db
-            .query_builder(count_query)
+            .db_for_user_reads(user_id)
+            .query_builder(&count_query)
             .bind("user_id", user_id.to_string())
+            .bind("from", range.from)
+            .bind("to", range.to)
             .execute()
-            .await
-            .map_err(LikesError::Database)?;
+            .await?;
 
         let count_data: Option<serde_json::Value> = count_result.take(0)?;
-        let total_count = count_data.and_then(|v| v["count"].as_i64()).unwrap_or(0);
+        let total_count = parse_count(count_data);
 
-        // Get paginated data
-        let data_query = r#"
-            SELECT * FROM likes 
-            WHERE user_id = $user_id 
-            ORDER BY created_at DESC 
-            LIMIT $limit 
-            START $offset;
-        "#;
+        // Get paginated data; the range and ORDER BY clauses are from fixed sets
+        let data_query = format!(
+            "SELECT * FROM likes WHERE user_id = $user_id AND deleted_at = NONE {} {} LIMIT $limit START $offset;",
+            range.where_clause(),
+            sort.order_by_clause()
+        );
 
         let mut data_result = self
-            .db
-            .query_builder(data_query)
+            .db_for_user_reads(user_id)
+            .query_builder(&data_query)
             .bind("user_id", user_id.to_string())
+            .bind("from", range.from)
+            .bind("to", range.to)
             .bind("limit", params.limit)
             .bind("offset", params.offset())
             .execute()
-            .await
-            .map_err(LikesError::Database)?;
+            .await?;
 
         let likes: Vec<Like> = data_result.take(0)?;
 
         Ok(PaginatedResult::new(likes, total_count, params))
     }
 
+    /// Active likes of any of `user_ids`, most recently liked first, e.g.
+    /// to review the likes of flagged accounts
+    pub async fn get_likes_by_users(
+        &self,
+        user_ids: &[String],
+        params: &PaginationParams,
+    ) -> Result<PaginatedResult<Like>> {
+        debug!(
+            "Getting likes of {} users (page: {}, limit: {})",
+            user_ids.len(),
+            params.page,
+            params.limit
+        );
+
+        let query = r#"
+            SELECT count() FROM likes
+            WHERE user_id IN $user_ids AND deleted_at = NONE
+            GROUP ALL;
+            SELECT * FROM likes
+            WHERE user_id IN $user_ids AND deleted_at = NONE
+            ORDER BY liked_at DESC
+            LIMIT $limit START $offset;
+        "#;
+
+        let mut result = self
+            .db_for_reads()
+            .query_builder(query)
+            .bind("user_ids", user_ids.to_vec())
+            .bind("limit", params.limit)
+            .bind("offset", params.offset())
+            .execute()
+            .await?;
+
+        let count_data: Option<serde_json::Value> = result.take(0)?;
+        let likes: Vec<Like> = result.take(1)?;
+
+        Ok(PaginatedResult::new(likes, parse_count(count_data), params))
+    }
+
     pub async fn get_post_likes(
         &self,
         post_id: &u32,
         params: &PaginationParams,
+        sort: LikeSort,
     ) -> Result<PaginatedResult<Like>> {
         debug!(
             "Getting likes for post {} (page: {}, limit: {})",
             post_id, params.page, params.limit
         );
 
+        // The pages are looked up before querying, so a write invalidating
+        // them meanwhile discards the page stored below along with them
+        let key = PageKey {
+            page: params.page,
+            limit: params.limit,
+            sort,
+        };
+        let pages = match &self.post_likes_cache {
+            Some(cache) => Some(
+                cache
+                    .get_with(*post_id, async { Arc::new(PostPages::new()) })
+                    .await,
+            ),
+            None => None,
+        };
+        if let Some(pages) = &pages {
+            let cached = pages.get(&key).map(|page| page.clone());
+            record_cache_lookup("post_likes", cached.is_some());
+            if let Some(page) = cached {
+                return Ok(page);
+            }
+        }
+
         // Get total count
-        let count_query = "SELECT count() FROM likes WHERE post_id = $post_id GROUP ALL;";
+        let count_query =
+            "SELECT count() FROM likes WHERE post_id = $post_id AND deleted_at = NONE GROUP ALL;";
         let mut count_result = self
-            .db
+            .db_for_reads()
             .query_builder(count_query)
             .bind("post_id", *post_id)
             .execute()
-            .await
-            .map_err(LikesError::Database)?;
+            .await?;
 
         let count_data: Option<serde_json::Value> = count_result.take(0)?;
-        let total_count = count_data.and_then(|v| v["count"].as_i64()).unwrap_or(0);
+        let total_count = parse_count(count_data);
 
-        // Get paginated data
-        let data_query = r#"
-            SELECT * FROM likes 
-            WHERE post_id = $post_id 
-            ORDER BY created_at DESC 
-            LIMIT $limit 
-            START $offset;
-        "#;
+        // Get paginated data; the ORDER BY clause is one of a fixed set
+        let data_query = format!(
+            "SELECT * FROM likes WHERE post_id = $post_id AND deleted_at = NONE {} LIMIT $limit START $offset;",
+            sort.order_by_clause()
+        );
 
         let mut data_result = self
-            .db
-            .query_builder(data_query)
+            .db_for_reads()
+            .query_builder(&data_query)
             .bind("post_id", *post_id)
             .bind("limit", params.limit)
             .bind("offset", params.offset())
             .execute()
-            .await
-            .map_err(LikesError::Database)?;
+            .await?;
 
         let likes: Vec<Like> = data_result.take(0)?;
 
-        Ok(PaginatedResult::new(likes, total_count, params))
+        let page = PaginatedResult::new(likes, total_count, params);
+        if let Some(pages) = pages {
+            pages.insert(key, page.clone());
+        }
+        Ok(page)
     }
 
-    pub async fn is_post_liked(
+    /// Stream every active like on a post, `batch_size` rows per query.
+    /// The next batch is only fetched as the consumer drains the previous one,
+    /// and fetching stops once the returned stream is dropped.
+    pub fn stream_post_likes(
+        &self,
+        post_id: u32,
+        batch_size: usize,
+    ) -> ReceiverStream<Result<Like>> {
+        self.stream_likes(StreamScope::Post(post_id), batch_size)
+    }
+
+    /// Stream every like a user ever gave, removed ones included, for a
+    /// data export. Batched like [`Self::stream_post_likes`].
+    pub fn stream_user_likes(
         &self,
         user_id: &str,
-        post_id: &u32,
-    ) -> Result<Option<DateTime<Utc>>> {
-        debug!("Checking if user {} likes post {}", user_id, post_id);
+        batch_size: usize,
+    ) -> ReceiverStream<Result<Like>> {
+        self.stream_likes(StreamScope::User(user_id.to_string()), batch_size)
+    }
 
-        let query = r#"
-            SELECT liked_at FROM likes 
-            WHERE user_id = $user_id AND post_id = $post_id 
-            LIMIT 1;
-        "#;
+    fn stream_likes(&self, scope: StreamScope, batch_size: usize) -> ReceiverStream<Result<Like>> {
+        let batch_size = batch_size.max(1);
+        let (tx, rx) = mpsc::channel(batch_size);
+        let repository = self.clone();
 
-        let mut result = self
-            .db
-            .query_builder(query)
-            .bind("user_id", user_id.to_string())
-            .bind("post_id", *post_id)
+        tokio::spawn(async move {
+            let mut after: Option<Thing> = None;
+
+            loop {
+                let batch = match repository
+                    .get_likes_after(&scope, after.as_ref(), batch_size)
+                    .await
+                {
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+                        return;
+                    }
+                };
+
+                let exhausted = batch.len() < batch_size;
+                after = batch.last().and_then(|like| like.id.clone());
+
+                for like in batch {
+                    if tx.send(Ok(like)).await.is_err() {
+                        debug!("Likes stream for {:?} closed by consumer", scope);
+                        return;
+                    }
+                }
+
+                if exhausted || after.is_none() {
+                    return;
+                }
+            }
+        });
+
+        ReceiverStream::new(rx)
+    }
+
+    // One keyset page of the scope's likes in record id order
+    async fn get_likes_after(
+        &self,
+        scope: &StreamScope,
+        after: Option<&Thing>,
+        limit: usize,
+    ) -> Result<Vec<Like>> {
+        let cursor = if after.is_some() {
+            "AND id > $after"
+        } else {
+            ""
+        };
+        let query = format!(
+            "SELECT * FROM likes WHERE {} {} ORDER BY id LIMIT $limit;",
+            scope.condition(),
+            cursor
+        );
+
+        let query = self.db.query_builder(&query);
+        let query = match scope {
+            StreamScope::Post(post_id) => query.bind("post_id", *post_id),
+            StreamScope::User(user_id) => query.bind("user_id", user_id.clone()),
+        };
+        let mut result = query
+            .bind("after", after.cloned())
+            .bind("limit", limit)
             .execute()
-            .await
-            .map_err(LikesError::Database)?;
+            .await?;
+
+        let likes: Vec<Like> = result.take(0)?;
+        Ok(likes)
+    }
+
+    pub async fn is_post_liked(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+    ) -> Result<Option<DateTime<Utc>>> {
+        debug!("Checking if user {} likes post {}", user_id, post_id);
+
+        let query = r#"
+            SELECT * FROM likes 
+            WHERE user_id = $user_id AND post_id = $post_id AND deleted_at = NONE
+            LIMIT 1;
+        "#;
+
+        let mut result = self
+            .db_for_user_reads(user_id)
+            .query_builder(query)
+            .bind("user_id", user_id.to_string())
+            .bind("post_id", *post_id)
+            .execute()
+            .await?;
 
         let like: Option<Like> = result.take(0)?;
         Ok(like.map(|l| l.liked_at))
     }
 
+    /// The user's like of the post, including one that was removed since;
+    /// `deleted_at` tells the two apart. Purged likes are gone for good.
+    pub async fn get_like_including_deleted(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+    ) -> Result<Option<Like>> {
+        debug!(
+            "Getting like of post {} by user {}, including removed",
+            post_id, user_id
+        );
+
+        let query = r#"
+            SELECT * FROM likes
+            WHERE user_id = $user_id AND post_id = $post_id
+            LIMIT 1;
+        "#;
+
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("user_id", user_id.to_string())
+            .bind("post_id", *post_id)
+            .execute()
+            .await?;
+
+        let like: Option<Like> = result.take(0)?;
+        Ok(like)
+    }
+
+    /// Looks up the like state of many posts for one user in a single query.
+    /// Every requested post is present in the map; unliked posts map to `None`.
+    pub async fn are_posts_liked(
+        &self,
+        user_id: &str,
+        post_ids: &[u32],
+    ) -> Result<HashMap<u32, Option<DateTime<Utc>>>> {
+        debug!(
+            "Checking if user {} likes {} posts",
+            user_id,
+            post_ids.len()
+        );
+
+        let query = r#"
+            SELECT post_id, liked_at FROM likes
+            WHERE user_id = $user_id AND post_id IN $post_ids AND deleted_at = NONE;
+        "#;
+
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("user_id", user_id.to_string())
+            .bind("post_ids", post_ids.to_vec())
+            .execute()
+            .await?;
+
+        let rows: Vec<PostLikedAt> = result.take(0)?;
+
+        let mut liked: HashMap<u32, Option<DateTime<Utc>>> =
+            post_ids.iter().map(|post_id| (*post_id, None)).collect();
+        for row in rows {
+            liked.insert(row.post_id, Some(row.liked_at));
+        }
+
+        Ok(liked)
+    }
+
+    /// The subset of `user_ids` that currently like the post
+    pub async fn which_users_liked(
+        &self,
+        post_id: &u32,
+        user_ids: &[String],
+    ) -> Result<HashSet<String>> {
+        debug!(
+            "Checking which of {} users like post {}",
+            user_ids.len(),
+            post_id
+        );
+
+        let query = "SELECT VALUE user_id FROM likes WHERE post_id = $post_id AND user_id IN $user_ids AND deleted_at = NONE;";
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("post_id", *post_id)
+            .bind("user_ids", user_ids.to_vec())
+            .execute()
+            .await?;
+
+        let likers: Vec<String> = result.take(0)?;
+        Ok(likers.into_iter().collect())
+    }
+
     pub async fn get_likes_count(&self, post_id: &u32) -> Result<i64> {
         debug!("Getting likes count for post {}", post_id);
 
-        let query = "SELECT count() FROM likes WHERE post_id = $post_id GROUP ALL;";
+        let query =
+            "SELECT count() FROM likes WHERE post_id = $post_id AND deleted_at = NONE GROUP ALL;";
+        let mut result = self
+            .db_for_reads()
+            .query_builder(query)
+            .bind("post_id", *post_id)
+            .execute()
+            .await?;
+
+        let count_data: Option<serde_json::Value> = result.take(0)?;
+        Ok(parse_count(count_data))
+    }
+
+    /// The post's active like count and latest `liked_at`, read together
+    pub async fn get_likes_count_stamp(&self, post_id: &u32) -> Result<LikeCountStamp> {
+        debug!("Getting likes count stamp for post {}", post_id);
+
+        let query = r#"
+            SELECT count() FROM likes WHERE post_id = $post_id AND deleted_at = NONE GROUP ALL;
+            SELECT post_id, liked_at FROM likes
+                WHERE post_id = $post_id AND deleted_at = NONE
+                ORDER BY liked_at DESC LIMIT 1;
+        "#;
+        let mut result = self
+            .db_for_reads()
+            .query_builder(query)
+            .bind("post_id", *post_id)
+            .execute()
+            .await?;
+
+        let count_data: Option<serde_json::Value> = result.take(0)?;
+        let latest: Option<PostLikedAt> = result.take(1)?;
+        Ok(LikeCountStamp {
+            count: parse_count(count_data),
+            latest_liked_at: latest.map(|row| row.liked_at),
+        })
+    }
+
+    /// Number of active likes on a post made after `since`
+    pub async fn get_likes_count_since(&self, post_id: &u32, since: DateTime<Utc>) -> Result<i64> {
+        debug!("Getting likes count for post {} since {}", post_id, since);
+
+        let query = r#"
+            SELECT count() FROM likes
+            WHERE post_id = $post_id AND deleted_at = NONE AND liked_at > <datetime>$since
+            GROUP ALL;
+        "#;
         let mut result = self
             .db
             .query_builder(query)
             .bind("post_id", *post_id)
+            .bind("since", since)
             .execute()
-            .await
-            .map_err(LikesError::Database)?;
+            .await?;
 
         let count_data: Option<serde_json::Value> = result.take(0)?;
-        Ok(count_data.and_then(|v| v["count"].as_i64()).unwrap_or(0))
+        Ok(parse_count(count_data))
     }
 
-    pub async fn unlike_posts(&self, user_ids: &[String], post_ids: &[u32]) -> Result<bool> {
+    /// Active likes on a post made in `[from, to)`, counted per bucket. Only
+    /// buckets with likes are returned, oldest first.
+    pub async fn get_likes_histogram(
+        &self,
+        post_id: &u32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        width: BucketWidth,
+    ) -> Result<Vec<BucketCount>> {
         debug!(
-            "Unliking posts for {} users on {} posts",
-            user_ids.len(),
+            "Getting {:?} likes histogram for post {} from {} to {}",
+            width, post_id, from, to
+        );
+
+        // The bucket width is one of a fixed set of duration literals
+        let query = format!(
+            r#"
+            SELECT time::floor(liked_at, {}) AS bucket_start, count() AS count FROM likes
+            WHERE post_id = $post_id AND deleted_at = NONE
+                AND liked_at >= <datetime>$from AND liked_at < <datetime>$to
+            GROUP BY bucket_start
+            ORDER BY bucket_start ASC;
+            "#,
+            width.as_surql()
+        );
+        let mut result = self
+            .db
+            .query_builder(&query)
+            .bind("post_id", *post_id)
+            .bind("from", from)
+            .bind("to", to)
+            .execute()
+            .await?;
+
+        let buckets: Vec<BucketCount> = result.take(0)?;
+        Ok(buckets)
+    }
+
+    /// Whether the user currently likes at least one of the given posts
+    pub async fn has_user_liked_any(&self, user_id: &str, post_ids: &[u32]) -> Result<bool> {
+        debug!(
+            "Checking if user {} liked any of {} posts",
+            user_id,
             post_ids.len()
         );
 
-        // Reject if both lists are empty
-        if user_ids.is_empty() && post_ids.is_empty() {
-            return Err(LikesError::InvalidInput(
-                "At least one of user_ids or post_ids must be provided".to_string(),
-            ));
+        let query = r#"
+            SELECT count() FROM likes
+            WHERE user_id = $user_id AND post_id IN $post_ids AND deleted_at = NONE
+            GROUP ALL;
+        "#;
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("user_id", user_id.to_string())
+            .bind("post_ids", post_ids.to_vec())
+            .execute()
+            .await?;
+
+        let count_data: Option<serde_json::Value> = result.take(0)?;
+        Ok(parse_count(count_data) > 0)
+    }
+
+    /// Number of posts a user currently likes; the unique user/post index keeps it distinct
+    pub async fn get_user_likes_count(&self, user_id: &str) -> Result<i64> {
+        debug!("Getting likes count for user {}", user_id);
+
+        let query =
+            "SELECT count() FROM likes WHERE user_id = $user_id AND deleted_at = NONE GROUP ALL;";
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("user_id", user_id.to_string())
+            .execute()
+            .await?;
+
+        let count_data: Option<serde_json::Value> = result.take(0)?;
+        Ok(parse_count(count_data))
+    }
+
+    /// Counts likes for many posts in a single query. Posts without likes map to zero.
+    pub async fn get_likes_counts(&self, post_ids: &[u32]) -> Result<HashMap<u32, i64>> {
+        debug!("Getting likes counts for {} posts", post_ids.len());
+
+        let query = r#"
+            SELECT post_id, count() AS count FROM likes
+            WHERE post_id IN $post_ids AND deleted_at = NONE
+            GROUP BY post_id;
+        "#;
+
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("post_ids", post_ids.to_vec())
+            .execute()
+            .await?;
+
+        let rows: Vec<LikeCount> = result.take(0)?;
+
+        let mut counts: HashMap<u32, i64> = post_ids.iter().map(|post_id| (*post_id, 0)).collect();
+        for row in rows {
+            counts.insert(row.post_id, row.count);
         }
 
-        // Handle different scenarios with separate queries for clarity
-        let deleted_result = match (!user_ids.is_empty(), !post_ids.is_empty()) {
-            (true, true) => {
-                // Both provided - delete likes where user_id AND post_id match
-                // Use RETURN BEFORE to get the records that will be deleted
-                let query = "DELETE FROM likes WHERE user_id IN $user_ids AND post_id IN $post_ids RETURN BEFORE";
-                let mut result = self
-                    .db
-                    .query_builder(query)
-                    .bind("user_ids", user_ids.to_vec())
-                    .bind("post_ids", post_ids.to_vec())
-                    .execute()
-                    .await
-                    .map_err(LikesError::Database)?;
+        Ok(counts)
+    }
 
-                let deleted: Vec<Like> = result.take(0)?;
-                println!("Deleted likes (both criteria): {:?}", deleted);
-                deleted
-            }
-            (true, false) => {
-                // Only user_ids provided - delete all likes by these users
-                let query = "DELETE FROM likes WHERE user_id IN $user_ids RETURN BEFORE";
-                let mut result = self
-                    .db
-                    .query_builder(query)
-                    .bind("user_ids", user_ids.to_vec())
-                    .execute()
-                    .await
-                    .map_err(LikesError::Database)?;
+    /// Returns posts ordered by like count, optionally only counting likes after `since`
+    pub async fn get_most_liked_posts(
+        &self,
+        params: &PaginationParams,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<PaginatedResult<LikeCount>> {
+        debug!(
+            "Getting most liked posts since {:?} (page: {}, limit: {})",
+            since, params.page, params.limit
+        );
 
-                let deleted: Vec<Like> = result.take(0)?;
-                println!("Deleted likes (user criteria): {:?}", deleted);
-                deleted
-            }
-            (false, true) => {
-                // Only post_ids provided - delete all likes on these posts
-                let query = "DELETE FROM likes WHERE post_id IN $post_ids RETURN BEFORE";
-                let mut result = self
-                    .db
-                    .query_builder(query)
-                    .bind("post_ids", post_ids.to_vec())
-                    .execute()
-                    .await
-                    .map_err(LikesError::Database)?;
+        // Only fixed fragments are spliced in; values stay bound parameters
+        let window = if since.is_some() {
+            "AND liked_at > <datetime>$since"
+        } else {
+            ""
+        };
 
-                let deleted: Vec<Like> = result.take(0)?;
-                println!("Deleted likes (post criteria): {:?}", deleted);
-                deleted
-            }
-            (false, false) => {
-                return Err(LikesError::InvalidInput(
-                    "At least one of user_ids or post_ids must be provided".to_string(),
-                ));
-            }
+        // Number of ranked posts, i.e. posts with at least one like in the window
+        let count_query = format!(
+            "SELECT count() FROM (SELECT post_id FROM likes WHERE deleted_at = NONE {} GROUP BY post_id) GROUP ALL;",
+            window
+        );
+        let mut count_result = self
+            .db
+            .query_builder(&count_query)
+            .bind("since", since)
+            .execute()
+            .await?;
+
+        let count_data: Option<serde_json::Value> = count_result.take(0)?;
+        let total_count = parse_count(count_data);
+
+        // post_id breaks ties so equal counts keep their order across pages
+        let query = format!(
+            "SELECT post_id, count() AS count FROM likes WHERE deleted_at = NONE {} GROUP BY post_id ORDER BY count DESC, post_id ASC LIMIT $limit START $offset;",
+            window
+        );
+
+        let mut result = self
+            .db
+            .query_builder(&query)
+            .bind("since", since)
+            .bind("limit", params.limit)
+            .bind("offset", params.offset())
+            .execute()
+            .await?;
+
+        let rows: Vec<LikeCount> = result.take(0)?;
+        Ok(PaginatedResult::new(rows, total_count, params))
+    }
+
+    /// Other users who liked the post, most recent first, excluding `user_id`
+    pub async fn get_mutual_likers(
+        &self,
+        post_id: &u32,
+        user_id: &str,
+        limit: i32,
+    ) -> Result<Vec<String>> {
+        debug!(
+            "Getting up to {} mutual likers of post {} for user {}",
+            limit, post_id, user_id
+        );
+
+        // liked_at is selected because SurrealDB requires ORDER BY fields in the projection
+        let query = "SELECT user_id, liked_at FROM likes WHERE post_id = $post_id AND user_id != $user_id AND deleted_at = NONE ORDER BY liked_at DESC LIMIT $limit;";
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("post_id", *post_id)
+            .bind("user_id", user_id.to_string())
+            .bind("limit", limit)
+            .execute()
+            .await?;
+
+        let rows: Vec<UserLikedAt> = result.take(0)?;
+        Ok(rows.into_iter().map(|row| row.user_id).collect())
+    }
+
+    /// The user who has liked the post the longest, with when they liked it
+    pub async fn get_first_liker(&self, post_id: &u32) -> Result<Option<(String, DateTime<Utc>)>> {
+        debug!("Getting the first liker of post {}", post_id);
+
+        let query = "SELECT user_id, liked_at FROM likes WHERE post_id = $post_id AND deleted_at = NONE ORDER BY liked_at ASC LIMIT 1;";
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("post_id", *post_id)
+            .execute()
+            .await?;
+
+        let row: Option<UserLikedAt> = result.take(0)?;
+        Ok(row.map(|row| (row.user_id, row.liked_at)))
+    }
+
+    /// Likes on a post made after `since`, newest first
+    pub async fn get_recent_likers(
+        &self,
+        post_id: &u32,
+        since: DateTime<Utc>,
+        limit: i32,
+    ) -> Result<Vec<Like>> {
+        debug!(
+            "Getting up to {} likers of post {} since {}",
+            limit, post_id, since
+        );
+
+        let query = "SELECT * FROM likes WHERE post_id = $post_id AND deleted_at = NONE AND liked_at > <datetime>$since ORDER BY liked_at DESC LIMIT $limit;";
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("post_id", *post_id)
+            .bind("since", since)
+            .bind("limit", limit)
+            .execute()
+            .await?;
+
+        let likes: Vec<Like> = result.take(0)?;
+        Ok(likes)
+    }
+
+    /// Active likes across `post_ids`, newest first, starting after `after`.
+    /// Ties on `liked_at` are broken by record id so pages never overlap.
+    pub async fn get_activity_feed(
+        &self,
+        post_ids: &[u32],
+        after: Option<&FeedCursor>,
+        limit: i32,
+    ) -> Result<Vec<Like>> {
+        debug!(
+            "Getting up to {} feed entries across {} posts",
+            limit,
+            post_ids.len()
+        );
+
+        let cursor = if after.is_some() {
+            "AND (liked_at < <datetime>$after_liked_at OR (liked_at = <datetime>$after_liked_at AND id < type::thing('likes', $after_id)))"
+        } else {
+            ""
         };
+        let query = format!(
+            "SELECT * FROM likes WHERE post_id IN $post_ids AND deleted_at = NONE {} ORDER BY liked_at DESC, id DESC LIMIT $limit;",
+            cursor
+        );
+
+        let mut result = self
+            .db
+            .query_builder(&query)
+            .bind("post_ids", post_ids.to_vec())
+            .bind("after_liked_at", after.map(|cursor| cursor.liked_at))
+            .bind("after_id", after.map(|cursor| cursor.id.clone()))
+            .bind("limit", limit)
+            .execute()
+            .await?;
+
+        let likes: Vec<Like> = result.take(0)?;
+        Ok(likes)
+    }
+
+    /// IDs of the posts a user currently likes, most recently liked first
+    pub async fn get_user_liked_post_ids(&self, user_id: &str, limit: i32) -> Result<Vec<u32>> {
+        debug!(
+            "Getting up to {} liked post IDs for user {}",
+            limit, user_id
+        );
+
+        // liked_at is selected because SurrealDB requires ORDER BY fields in the projection
+        let query = "SELECT post_id, liked_at FROM likes WHERE user_id = $user_id AND deleted_at = NONE ORDER BY liked_at DESC LIMIT $limit;";
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("user_id", user_id.to_string())
+            .bind("limit", limit)
+            .execute()
+            .await?;
+
+        let rows: Vec<PostLikedAt> = result.take(0)?;
+        Ok(rows.into_iter().map(|row| row.post_id).collect())
+    }
+
+    pub async fn get_likes_count_by_reaction(&self, post_id: &u32) -> Result<Vec<ReactionCount>> {
+        debug!("Getting per-reaction likes count for post {}", post_id);
+
+        let query = "SELECT reaction, count() AS count FROM likes WHERE post_id = $post_id AND deleted_at = NONE GROUP BY reaction;";
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("post_id", *post_id)
+            .execute()
+            .await?;
+
+        let rows: Vec<serde_json::Value> = result.take(0)?;
+
+        // Rows from before reactions existed group under NONE; fold them into Like
+        let mut counts: HashMap<ReactionType, i64> = HashMap::new();
+        for row in rows {
+            let reaction: ReactionType =
+                serde_json::from_value(row["reaction"].clone()).unwrap_or_default();
+            *counts.entry(reaction).or_insert(0) += row["count"].as_i64().unwrap_or(0);
+        }
+
+        Ok(ReactionType::ALL
+            .iter()
+            .map(|reaction| ReactionCount {
+                reaction: *reaction,
+                count: counts.get(reaction).copied().unwrap_or(0),
+            })
+            .collect())
+    }
+
+    // WHERE clause shared by `unlike_posts` and `count_matching_likes`; an
+    // empty list means "any", but at least one list must be given
+    fn unlike_posts_condition(user_ids: &[String], post_ids: &[u32]) -> Result<&'static str> {
+        match (!user_ids.is_empty(), !post_ids.is_empty()) {
+            // Both provided - likes where user_id AND post_id match
+            (true, true) => {
+                Ok("user_id IN $user_ids AND post_id IN $post_ids AND deleted_at = NONE")
+            }
+            // Only user_ids provided - all likes by these users
+            (true, false) => Ok("user_id IN $user_ids AND deleted_at = NONE"),
+            // Only post_ids provided - all likes on these posts
+            (false, true) => Ok("post_id IN $post_ids AND deleted_at = NONE"),
+            (false, false) => Err(LikesError::InvalidInput(
+                "At least one of user_ids or post_ids must be provided".to_string(),
+            )),
+        }
+    }
+
+    pub async fn unlike_posts(&self, user_ids: &[String], post_ids: &[u32]) -> Result<bool> {
+        debug!(
+            "Unliking posts for {} users on {} posts",
+            user_ids.len(),
+            post_ids.len()
+        );
+
+        let condition = Self::unlike_posts_condition(user_ids, post_ids)?;
+        let query = format!(
+            "UPDATE likes SET deleted_at = <datetime>$now, updated_at = <datetime>$now, version = (version ?? 0) + 1 WHERE {}",
+            condition
+        );
+        let mut result = self
+            .db
+            .query_builder(&query)
+            .bind("now", self.clock.now())
+            .bind("user_ids", user_ids.to_vec())
+            .bind("post_ids", post_ids.to_vec())
+            .execute()
+            .await?;
+
+        let deleted_result: Vec<Like> = result.take(0)?;
+        debug!("Soft-deleted likes: {:?}", deleted_result);
+        self.invalidate_post_pages(deleted_result.iter().map(|like| like.post_id))
+            .await;
 
         let success = !deleted_result.is_empty();
-        println!(
+        debug!(
             "Delete operation success: {}, deleted {} records",
             success,
             deleted_result.len()
@@ -306,7 +1645,435 @@ impl LikesRepository {
         Ok(success)
     }
 
+    /// Number of likes `unlike_posts` would remove for the same arguments
+    pub async fn count_matching_likes(&self, user_ids: &[String], post_ids: &[u32]) -> Result<i64> {
+        debug!(
+            "Counting likes matching {} users and {} posts",
+            user_ids.len(),
+            post_ids.len()
+        );
+
+        let condition = Self::unlike_posts_condition(user_ids, post_ids)?;
+        let query = format!("SELECT count() FROM likes WHERE {} GROUP ALL;", condition);
+        let mut result = self
+            .db
+            .query_builder(&query)
+            .bind("user_ids", user_ids.to_vec())
+            .bind("post_ids", post_ids.to_vec())
+            .execute()
+            .await?;
+
+        let count_data: Option<serde_json::Value> = result.take(0)?;
+        Ok(parse_count(count_data))
+    }
+
+    /// Soft-deletes every active like on a post, e.g. after the post was deleted.
+    /// The rows are removed for good by the retention purge.
+    pub async fn delete_post_likes(&self, post_id: &u32) -> Result<usize> {
+        debug!("Deleting all likes on post {}", post_id);
+
+        let query = r#"
+            UPDATE likes SET
+                deleted_at = <datetime>$now,
+                updated_at = <datetime>$now,
+                version = (version ?? 0) + 1
+            WHERE post_id = $post_id AND deleted_at = NONE
+            RETURN BEFORE;
+        "#;
+
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .bind("post_id", *post_id)
+            .execute()
+            .await?;
+
+        let deleted: Vec<Like> = result.take(0)?;
+        self.invalidate_post_pages([*post_id]).await;
+        Ok(deleted.len())
+    }
+
+    /// Permanently removes every like a user made, including soft-deleted ones,
+    /// for account deletion. Returns 0 when the user had none.
+    pub async fn delete_user_likes(&self, user_id: &str) -> Result<usize> {
+        debug!("Deleting all likes by user {}", user_id);
+
+        let query = "DELETE likes WHERE user_id = $user_id RETURN BEFORE;";
+        let mut result = self
+            .db_for_user(user_id)
+            .query_builder(query)
+            .bind("user_id", user_id.to_string())
+            .execute()
+            .await?;
+
+        let deleted: Vec<Like> = result.take(0)?;
+        self.invalidate_post_pages(deleted.iter().map(|like| like.post_id))
+            .await;
+        Ok(deleted.len())
+    }
+
+    /// Reassigns every like of `from_user_id`, removed ones included, to
+    /// `to_user_id` in one transaction, e.g. when two accounts merge. A user
+    /// has one row per post, so posts the target has a row for (even a
+    /// removed like) are skipped; with `remove_source` the skipped likes
+    /// that are still active are removed.
+    pub async fn merge_user_likes(
+        &self,
+        from_user_id: &str,
+        to_user_id: &str,
+        remove_source: bool,
+    ) -> Result<LikeMerge> {
+        debug!(
+            "Merging likes of user {} into user {} (remove source: {})",
+            from_user_id, to_user_id, remove_source
+        );
+
+        let query = r#"
+            BEGIN TRANSACTION;
+            LET $taken = (SELECT VALUE post_id FROM likes WHERE user_id = $to_user_id);
+            UPDATE likes SET
+                user_id = $to_user_id,
+                updated_at = <datetime>$now,
+                version = (version ?? 0) + 1
+            WHERE user_id = $from_user_id AND post_id NOTINSIDE $taken;
+            SELECT * FROM likes WHERE user_id = $from_user_id;
+            IF $remove_source {
+                UPDATE likes SET
+                    deleted_at = <datetime>$now,
+                    updated_at = <datetime>$now,
+                    version = (version ?? 0) + 1
+                WHERE user_id = $from_user_id AND deleted_at = NONE;
+            };
+            COMMIT TRANSACTION;
+        "#;
+
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .bind("from_user_id", from_user_id.to_string())
+            .bind("to_user_id", to_user_id.to_string())
+            .bind("remove_source", remove_source)
+            .execute()
+            .await?;
+
+        let errors = result.take_errors();
+        if errors.values().any(Self::is_duplicate_error) {
+            return Err(LikesError::AlreadyExists(
+                "The target user liked one of these posts meanwhile, retry the merge".to_string(),
+            ));
+        }
+        if let Some(e) = Self::first_error(errors) {
+            error!("Failed to merge user likes: {}", e);
+            return Err(LikesError::Database(e));
+        }
+
+        // Counted from the end: the moved rows, then those left behind
+        let last = result.num_statements().saturating_sub(1);
+        let moved: Vec<Like> = result.take(last - 2)?;
+        let skipped: Vec<Like> = result.take(last - 1)?;
+
+        self.invalidate_post_pages(
+            moved
+                .iter()
+                .chain(skipped.iter().filter(|_| remove_source))
+                .map(|like| like.post_id),
+        )
+        .await;
+        Ok(LikeMerge {
+            moved: moved.len(),
+            skipped: skipped.len(),
+        })
+    }
+
+    /// Fill an empty likes table with `count` demo likes (at most
+    /// [`MAX_DEMO_LIKES`]) for local development. Users `demo_user_1` to
+    /// `demo_user_50` like posts 1 to 100, low post ids most, over the last
+    /// 30 days with a mix of reactions. Does nothing when the table has any
+    /// row, soft-deleted ones included; returns how many likes were inserted.
+    pub async fn seed_demo_data(&self, count: usize) -> Result<usize> {
+        let mut result = self
+            .db
+            .query_builder("SELECT count() FROM likes GROUP ALL;")
+            .execute()
+            .await?;
+        let existing: Option<serde_json::Value> = result.take(0)?;
+        if parse_count(existing) > 0 {
+            debug!("Likes table is not empty, not seeding demo data");
+            return Ok(0);
+        }
+
+        let now = self.clock.now();
+        let likes: Vec<DemoLike> = (0..count.min(MAX_DEMO_LIKES))
+            .map(|i| DemoLike {
+                user_id: format!("demo_user_{}", i % DEMO_USERS + 1),
+                // Every user likes post 1 before anyone likes post 2, and so on
+                post_id: (i / DEMO_USERS + 1) as u32,
+                // Mostly plain likes
+                reaction: if i % 3 == 0 {
+                    ReactionType::ALL[i % ReactionType::ALL.len()]
+                } else {
+                    ReactionType::Like
+                },
+                liked_at: now - chrono::Duration::minutes((i as i64 * 37) % (30 * 24 * 60)),
+            })
+            .collect();
+        debug!("Seeding {} demo likes", likes.len());
+
+        let query = r#"
+            BEGIN TRANSACTION;
+            FOR $like IN $likes {
+                CREATE likes SET
+                    user_id = $like.user_id,
+                    post_id = $like.post_id,
+                    reaction = $like.reaction,
+                    liked_at = <datetime>$like.liked_at,
+                    created_at = <datetime>$like.liked_at,
+                    updated_at = <datetime>$like.liked_at,
+                    version = 0;
+            };
+            COMMIT TRANSACTION;
+        "#;
+
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("likes", &likes)
+            .execute()
+            .await?;
+
+        if let Some(e) = Self::first_error(result.take_errors()) {
+            error!("Failed to seed demo data: {}", e);
+            return Err(LikesError::Database(e));
+        }
+
+        Ok(likes.len())
+    }
+
+    /// Permanently removes likes that were soft-deleted before `older_than` ago
+    pub async fn purge_deleted_likes(&self, older_than: chrono::Duration) -> Result<usize> {
+        let cutoff = self.clock.now() - older_than;
+        debug!("Purging likes soft-deleted before {}", cutoff);
+
+        let query = r#"
+            DELETE FROM likes
+            WHERE deleted_at != NONE AND deleted_at < <datetime>$cutoff
+            RETURN BEFORE;
+        "#;
+
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("cutoff", cutoff)
+            .execute()
+            .await?;
+
+        let purged: Vec<Like> = result.take(0)?;
+        Ok(purged.len())
+    }
+
+    /// Up to `limit` distinct posts with active likes awaiting validation
+    pub async fn get_unvalidated_post_ids(&self, limit: i32) -> Result<Vec<u32>> {
+        debug!("Getting up to {} posts with unvalidated likes", limit);
+
+        let query = r#"
+            SELECT post_id FROM likes
+            WHERE unvalidated = true AND deleted_at = NONE
+            GROUP BY post_id
+            LIMIT $limit;
+        "#;
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("limit", limit)
+            .execute()
+            .await?;
+
+        let rows: Vec<PostId> = result.take(0)?;
+        Ok(rows.into_iter().map(|row| row.post_id).collect())
+    }
+
+    /// Clear the flag on unvalidated likes of posts that turned out to
+    /// exist; returns how many likes were confirmed
+    pub async fn confirm_unvalidated_likes(&self, post_ids: &[u32]) -> Result<usize> {
+        debug!("Confirming unvalidated likes on {} posts", post_ids.len());
+
+        let query = r#"
+            UPDATE likes SET unvalidated = false, version = (version ?? 0) + 1
+            WHERE post_id IN $post_ids AND unvalidated = true
+            RETURN BEFORE;
+        "#;
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("post_ids", post_ids.to_vec())
+            .execute()
+            .await?;
+
+        let confirmed: Vec<Like> = result.take(0)?;
+        Ok(confirmed.len())
+    }
+
+    /// Soft-delete unvalidated likes of posts that do not exist; returns how
+    /// many likes were discarded
+    pub async fn discard_unvalidated_likes(&self, post_ids: &[u32]) -> Result<usize> {
+        debug!("Discarding unvalidated likes on {} posts", post_ids.len());
+
+        let query = r#"
+            UPDATE likes SET
+                unvalidated = false,
+                deleted_at = <datetime>$now,
+                version = (version ?? 0) + 1
+            WHERE post_id IN $post_ids AND unvalidated = true AND deleted_at = NONE
+            RETURN BEFORE;
+        "#;
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .bind("post_ids", post_ids.to_vec())
+            .execute()
+            .await?;
+
+        let discarded: Vec<Like> = result.take(0)?;
+        self.invalidate_post_pages(discarded.iter().map(|like| like.post_id))
+            .await;
+        Ok(discarded.len())
+    }
+
+    /// The response stored for an unexpired idempotency key, if any
+    pub async fn get_idempotent_response(
+        &self,
+        user_id: &str,
+        key: &str,
+    ) -> Result<Option<IdempotentResponse>> {
+        let query = r#"
+            SELECT VALUE response FROM idempotency
+            WHERE user_id = $user_id AND key = $key AND expires_at > <datetime>$now
+            LIMIT 1;
+        "#;
+
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .bind("user_id", user_id.to_string())
+            .bind("key", key.to_string())
+            .execute()
+            .await?;
+
+        let response: Option<IdempotentResponse> = result.take(0)?;
+        Ok(response)
+    }
+
+    /// Remember a response under an idempotency key for `ttl`.
+    /// If a concurrent request already stored one for the same key, that one is kept.
+    pub async fn save_idempotent_response(
+        &self,
+        user_id: &str,
+        key: &str,
+        response: &IdempotentResponse,
+        ttl: chrono::Duration,
+    ) -> Result<()> {
+        let query = r#"
+            BEGIN TRANSACTION;
+            DELETE idempotency
+            WHERE user_id = $user_id AND key = $key AND expires_at <= <datetime>$now;
+            CREATE idempotency SET
+                user_id = $user_id,
+                key = $key,
+                response = $response,
+                created_at = <datetime>$now,
+                expires_at = <datetime>$expires_at;
+            COMMIT TRANSACTION;
+        "#;
+
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .bind("user_id", user_id.to_string())
+            .bind("key", key.to_string())
+            .bind("response", response.clone())
+            .bind("expires_at", self.clock.now() + ttl)
+            .execute()
+            .await?;
+
+        let errors = result.take_errors();
+        if errors.values().any(Self::is_duplicate_error) {
+            debug!(
+                "Idempotency key {} already stored for user {}",
+                key, user_id
+            );
+            return Ok(());
+        }
+        match Self::first_error(errors) {
+            Some(e) => Err(LikesError::Database(e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Permanently removes expired idempotency keys
+    pub async fn purge_expired_idempotency_keys(&self) -> Result<usize> {
+        let query = "DELETE FROM idempotency WHERE expires_at <= <datetime>$now RETURN BEFORE;";
+
+        let mut result = self
+            .db
+            .query_builder(query)
+            .bind("now", self.clock.now())
+            .execute()
+            .await?;
+
+        let purged: Vec<serde_json::Value> = result.take(0)?;
+        Ok(purged.len())
+    }
+
+    /// Compare the `likes` definitions with the migrations, re-applying
+    /// missing ones when `repair` is set
+    pub async fn verify_schema(&self, repair: bool) -> Result<SchemaReport> {
+        migrations::verify(&self.db, repair)
+            .await
+            .map_err(|e| LikesError::Internal(e.to_string()))
+    }
+
     pub async fn health_check(&self) -> Result<bool> {
         self.db.health_check().await.map_err(LikesError::Database)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_count;
+    use serde_json::json;
+
+    #[test]
+    fn parse_count_reads_object_rows() {
+        // SurrealDB 2.x over the SDK; counts may arrive as floats through JSON
+        assert_eq!(parse_count(Some(json!({ "count": 7 }))), 7);
+        assert_eq!(parse_count(Some(json!({ "count": 7.0 }))), 7);
+    }
+
+    #[test]
+    fn parse_count_reads_single_row_arrays() {
+        // SurrealDB 1.x, and 2.x when the statement result is taken whole
+        assert_eq!(parse_count(Some(json!([{ "count": 3 }]))), 3);
+        assert_eq!(parse_count(Some(json!([]))), 0);
+    }
+
+    #[test]
+    fn parse_count_treats_no_row_as_zero() {
+        // GROUP ALL over no matching rows returns no row at all
+        assert_eq!(parse_count(None), 0);
+        assert_eq!(parse_count(Some(json!(null))), 0);
+    }
+
+    #[test]
+    fn parse_count_rejects_unexpected_shapes() {
+        assert_eq!(parse_count(Some(json!({ "total": 3 }))), 0);
+        assert_eq!(
+            parse_count(Some(json!([{ "count": 1 }, { "count": 2 }]))),
+            0
+        );
+    }
+}
@@ -1,2 +1,7 @@
 pub mod like_repository;
-pub use like_repository::LikesRepository;
+pub mod store;
+pub mod write_buffer;
+
+pub use like_repository::{LikesRepository, MAX_DEMO_LIKES};
+pub use store::LikesStore;
+pub use write_buffer::LikeWriteBuffer;
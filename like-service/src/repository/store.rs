@@ -0,0 +1,502 @@
+use super::LikesRepository;
+use crate::{
+    database::migrations::SchemaReport,
+    error::Result,
+    models::{
+        BucketCount, BucketWidth, FeedCursor, IdempotentResponse, Like, LikeCount, LikeCountStamp,
+        LikeMerge, LikeSort, LikedAtRange, PaginatedResult, PaginationParams, ReactionCount,
+        ReactionType,
+    },
+};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Storage of likes and idempotency keys, as used by the service.
+/// `LikesRepository` is the SurrealDB implementation.
+#[tonic::async_trait]
+pub trait LikesStore: std::fmt::Debug + Send + Sync {
+    /// Record a like, reviving a soft-deleted one or changing the reaction of
+    /// an active one. A repeat with the same reaction is `AlreadyExists`.
+    async fn create_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        reaction: ReactionType,
+    ) -> Result<Like>;
+
+    /// Like a post that could not be checked against the Post Service. The
+    /// row is flagged `unvalidated` until reconciliation confirms or
+    /// discards it.
+    async fn create_unvalidated_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        reaction: ReactionType,
+    ) -> Result<Like>;
+
+    /// Like many posts for one user in a single transaction. Soft-deleted likes
+    /// are revived and posts the user already likes are left untouched.
+    /// Maps each post ID to `true` if it was liked now, `false` if it already was.
+    async fn create_likes(
+        &self,
+        user_id: &str,
+        post_ids: &[u32],
+        reaction: ReactionType,
+    ) -> Result<HashMap<u32, bool>>;
+
+    /// Soft-delete the user's like on a post; `false` when there was none
+    async fn delete_like(&self, user_id: &str, post_id: &u32) -> Result<bool>;
+
+    /// Flips the like state for the user/post pair and returns the resulting
+    /// `liked_at` when the post ends up liked, or `None` when it was unliked.
+    async fn toggle_like(&self, user_id: &str, post_id: &u32) -> Result<Option<DateTime<Utc>>>;
+
+    /// One page of the posts a user likes, liked within `range`
+    async fn get_user_likes(
+        &self,
+        user_id: &str,
+        params: &PaginationParams,
+        sort: LikeSort,
+        range: LikedAtRange,
+    ) -> Result<PaginatedResult<Like>>;
+
+    /// One page of the likes of any of `user_ids`, most recently liked first
+    async fn get_likes_by_users(
+        &self,
+        user_ids: &[String],
+        params: &PaginationParams,
+    ) -> Result<PaginatedResult<Like>>;
+
+    /// One page of the likes on a post
+    async fn get_post_likes(
+        &self,
+        post_id: &u32,
+        params: &PaginationParams,
+        sort: LikeSort,
+    ) -> Result<PaginatedResult<Like>>;
+
+    /// Stream every active like on a post, `batch_size` rows per query.
+    /// The next batch is only fetched as the consumer drains the previous one,
+    /// and fetching stops once the returned stream is dropped.
+    fn stream_post_likes(&self, post_id: u32, batch_size: usize) -> ReceiverStream<Result<Like>>;
+
+    /// Stream every like a user ever gave, removed ones included, for a
+    /// data export
+    fn stream_user_likes(&self, user_id: &str, batch_size: usize) -> ReceiverStream<Result<Like>>;
+
+    /// When the user liked the post, if they currently like it
+    async fn is_post_liked(&self, user_id: &str, post_id: &u32) -> Result<Option<DateTime<Utc>>>;
+
+    /// The user's like of the post, including one that was removed since
+    async fn get_like_including_deleted(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+    ) -> Result<Option<Like>>;
+
+    /// Looks up the like state of many posts for one user in a single query.
+    /// Every requested post is present in the map; unliked posts map to `None`.
+    async fn are_posts_liked(
+        &self,
+        user_id: &str,
+        post_ids: &[u32],
+    ) -> Result<HashMap<u32, Option<DateTime<Utc>>>>;
+
+    /// The subset of `user_ids` that currently like the post
+    async fn which_users_liked(
+        &self,
+        post_id: &u32,
+        user_ids: &[String],
+    ) -> Result<HashSet<String>>;
+
+    /// Number of active likes on a post
+    async fn get_likes_count(&self, post_id: &u32) -> Result<i64>;
+
+    /// Number of active likes on a post with the latest `liked_at` among
+    /// them, from one query
+    async fn get_likes_count_stamp(&self, post_id: &u32) -> Result<LikeCountStamp>;
+
+    /// Number of active likes on a post made after `since`
+    async fn get_likes_count_since(&self, post_id: &u32, since: DateTime<Utc>) -> Result<i64>;
+
+    /// Active likes on a post made in `[from, to)`, counted per bucket. Only
+    /// buckets with likes are returned, oldest first.
+    async fn get_likes_histogram(
+        &self,
+        post_id: &u32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        width: BucketWidth,
+    ) -> Result<Vec<BucketCount>>;
+
+    /// Whether the user currently likes at least one of the given posts
+    async fn has_user_liked_any(&self, user_id: &str, post_ids: &[u32]) -> Result<bool>;
+
+    /// Number of posts a user currently likes; the unique user/post index keeps it distinct
+    async fn get_user_likes_count(&self, user_id: &str) -> Result<i64>;
+
+    /// Counts likes for many posts in a single query. Posts without likes map to zero.
+    async fn get_likes_counts(&self, post_ids: &[u32]) -> Result<HashMap<u32, i64>>;
+
+    /// Returns posts ordered by like count, optionally only counting likes after `since`
+    async fn get_most_liked_posts(
+        &self,
+        params: &PaginationParams,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<PaginatedResult<LikeCount>>;
+
+    /// Other users who liked the post, most recent first, excluding `user_id`
+    async fn get_mutual_likers(
+        &self,
+        post_id: &u32,
+        user_id: &str,
+        limit: i32,
+    ) -> Result<Vec<String>>;
+
+    /// The user who has liked the post the longest, with when they liked it
+    async fn get_first_liker(&self, post_id: &u32) -> Result<Option<(String, DateTime<Utc>)>>;
+
+    /// Likes on a post made after `since`, newest first
+    async fn get_recent_likers(
+        &self,
+        post_id: &u32,
+        since: DateTime<Utc>,
+        limit: i32,
+    ) -> Result<Vec<Like>>;
+
+    /// Active likes across `post_ids`, newest first, starting after `after`.
+    /// Ties on `liked_at` are broken by record id so pages never overlap.
+    async fn get_activity_feed(
+        &self,
+        post_ids: &[u32],
+        after: Option<&FeedCursor>,
+        limit: i32,
+    ) -> Result<Vec<Like>>;
+
+    /// IDs of the posts a user currently likes, most recently liked first
+    async fn get_user_liked_post_ids(&self, user_id: &str, limit: i32) -> Result<Vec<u32>>;
+
+    /// Active likes on a post, per reaction
+    async fn get_likes_count_by_reaction(&self, post_id: &u32) -> Result<Vec<ReactionCount>>;
+
+    /// Soft-delete the likes matching `user_ids` and `post_ids`; an empty
+    /// list matches everything. `false` when nothing matched.
+    async fn unlike_posts(&self, user_ids: &[String], post_ids: &[u32]) -> Result<bool>;
+
+    /// Number of likes `unlike_posts` would remove for the same arguments
+    async fn count_matching_likes(&self, user_ids: &[String], post_ids: &[u32]) -> Result<i64>;
+
+    /// Soft-deletes every active like on a post, e.g. after the post was deleted.
+    /// The rows are removed for good by the retention purge.
+    async fn delete_post_likes(&self, post_id: &u32) -> Result<usize>;
+
+    /// Permanently removes every like a user made, including soft-deleted ones,
+    /// for account deletion. Returns 0 when the user had none.
+    async fn delete_user_likes(&self, user_id: &str) -> Result<usize>;
+
+    /// Reassigns `from_user_id`'s likes to `to_user_id`, skipping posts the
+    /// target already has a row for; with `remove_source` the skipped likes
+    /// are removed. Both are database IDs.
+    async fn merge_user_likes(
+        &self,
+        from_user_id: &str,
+        to_user_id: &str,
+        remove_source: bool,
+    ) -> Result<LikeMerge>;
+
+    /// Permanently removes likes that were soft-deleted before `older_than` ago
+    async fn purge_deleted_likes(&self, older_than: chrono::Duration) -> Result<usize>;
+
+    /// Up to `limit` distinct posts with active likes awaiting validation
+    async fn get_unvalidated_post_ids(&self, limit: i32) -> Result<Vec<u32>>;
+
+    /// Clear the flag on unvalidated likes of posts that turned out to
+    /// exist; returns how many likes were confirmed
+    async fn confirm_unvalidated_likes(&self, post_ids: &[u32]) -> Result<usize>;
+
+    /// Soft-delete unvalidated likes of posts that do not exist; returns how
+    /// many likes were discarded
+    async fn discard_unvalidated_likes(&self, post_ids: &[u32]) -> Result<usize>;
+
+    /// The response stored for an unexpired idempotency key, if any
+    async fn get_idempotent_response(
+        &self,
+        user_id: &str,
+        key: &str,
+    ) -> Result<Option<IdempotentResponse>>;
+
+    /// Remember a response under an idempotency key for `ttl`.
+    /// If a concurrent request already stored one for the same key, that one is kept.
+    async fn save_idempotent_response(
+        &self,
+        user_id: &str,
+        key: &str,
+        response: &IdempotentResponse,
+        ttl: chrono::Duration,
+    ) -> Result<()>;
+
+    /// Permanently removes expired idempotency keys
+    async fn purge_expired_idempotency_keys(&self) -> Result<usize>;
+
+    /// Compare the `likes` definitions with the migrations, re-applying
+    /// missing ones when `repair` is set
+    async fn verify_schema(&self, repair: bool) -> Result<SchemaReport>;
+
+    /// Whether the store is reachable
+    async fn health_check(&self) -> Result<bool>;
+}
+
+// The inherent methods keep working without the trait in scope
+#[tonic::async_trait]
+impl LikesStore for LikesRepository {
+    async fn create_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        reaction: ReactionType,
+    ) -> Result<Like> {
+        LikesRepository::create_like(self, user_id, post_id, reaction).await
+    }
+
+    async fn create_unvalidated_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        reaction: ReactionType,
+    ) -> Result<Like> {
+        LikesRepository::create_unvalidated_like(self, user_id, post_id, reaction).await
+    }
+
+    async fn create_likes(
+        &self,
+        user_id: &str,
+        post_ids: &[u32],
+        reaction: ReactionType,
+    ) -> Result<HashMap<u32, bool>> {
+        LikesRepository::create_likes(self, user_id, post_ids, reaction).await
+    }
+
+    async fn delete_like(&self, user_id: &str, post_id: &u32) -> Result<bool> {
+        LikesRepository::delete_like(self, user_id, post_id).await
+    }
+
+    async fn toggle_like(&self, user_id: &str, post_id: &u32) -> Result<Option<DateTime<Utc>>> {
+        LikesRepository::toggle_like(self, user_id, post_id).await
+    }
+
+    async fn get_user_likes(
+        &self,
+        user_id: &str,
+        params: &PaginationParams,
+        sort: LikeSort,
+        range: LikedAtRange,
+    ) -> Result<PaginatedResult<Like>> {
+        LikesRepository::get_user_likes(self, user_id, params, sort, range).await
+    }
+
+    async fn get_likes_by_users(
+        &self,
+        user_ids: &[String],
+        params: &PaginationParams,
+    ) -> Result<PaginatedResult<Like>> {
+        LikesRepository::get_likes_by_users(self, user_ids, params).await
+    }
+
+    async fn get_post_likes(
+        &self,
+        post_id: &u32,
+        params: &PaginationParams,
+        sort: LikeSort,
+    ) -> Result<PaginatedResult<Like>> {
+        LikesRepository::get_post_likes(self, post_id, params, sort).await
+    }
+
+    fn stream_post_likes(&self, post_id: u32, batch_size: usize) -> ReceiverStream<Result<Like>> {
+        LikesRepository::stream_post_likes(self, post_id, batch_size)
+    }
+
+    fn stream_user_likes(&self, user_id: &str, batch_size: usize) -> ReceiverStream<Result<Like>> {
+        LikesRepository::stream_user_likes(self, user_id, batch_size)
+    }
+
+    async fn is_post_liked(&self, user_id: &str, post_id: &u32) -> Result<Option<DateTime<Utc>>> {
+        LikesRepository::is_post_liked(self, user_id, post_id).await
+    }
+
+    async fn get_like_including_deleted(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+    ) -> Result<Option<Like>> {
+        LikesRepository::get_like_including_deleted(self, user_id, post_id).await
+    }
+
+    async fn are_posts_liked(
+        &self,
+        user_id: &str,
+        post_ids: &[u32],
+    ) -> Result<HashMap<u32, Option<DateTime<Utc>>>> {
+        LikesRepository::are_posts_liked(self, user_id, post_ids).await
+    }
+
+    async fn which_users_liked(
+        &self,
+        post_id: &u32,
+        user_ids: &[String],
+    ) -> Result<HashSet<String>> {
+        LikesRepository::which_users_liked(self, post_id, user_ids).await
+    }
+
+    async fn get_likes_count(&self, post_id: &u32) -> Result<i64> {
+        LikesRepository::get_likes_count(self, post_id).await
+    }
+
+    async fn get_likes_count_stamp(&self, post_id: &u32) -> Result<LikeCountStamp> {
+        LikesRepository::get_likes_count_stamp(self, post_id).await
+    }
+
+    async fn get_likes_count_since(&self, post_id: &u32, since: DateTime<Utc>) -> Result<i64> {
+        LikesRepository::get_likes_count_since(self, post_id, since).await
+    }
+
+    async fn get_likes_histogram(
+        &self,
+        post_id: &u32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        width: BucketWidth,
+    ) -> Result<Vec<BucketCount>> {
+        LikesRepository::get_likes_histogram(self, post_id, from, to, width).await
+    }
+
+    async fn has_user_liked_any(&self, user_id: &str, post_ids: &[u32]) -> Result<bool> {
+        LikesRepository::has_user_liked_any(self, user_id, post_ids).await
+    }
+
+    async fn get_user_likes_count(&self, user_id: &str) -> Result<i64> {
+        LikesRepository::get_user_likes_count(self, user_id).await
+    }
+
+    async fn get_likes_counts(&self, post_ids: &[u32]) -> Result<HashMap<u32, i64>> {
+        LikesRepository::get_likes_counts(self, post_ids).await
+    }
+
+    async fn get_most_liked_posts(
+        &self,
+        params: &PaginationParams,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<PaginatedResult<LikeCount>> {
+        LikesRepository::get_most_liked_posts(self, params, since).await
+    }
+
+    async fn get_mutual_likers(
+        &self,
+        post_id: &u32,
+        user_id: &str,
+        limit: i32,
+    ) -> Result<Vec<String>> {
+        LikesRepository::get_mutual_likers(self, post_id, user_id, limit).await
+    }
+
+    async fn get_first_liker(&self, post_id: &u32) -> Result<Option<(String, DateTime<Utc>)>> {
+        LikesRepository::get_first_liker(self, post_id).await
+    }
+
+    async fn get_recent_likers(
+        &self,
+        post_id: &u32,
+        since: DateTime<Utc>,
+        limit: i32,
+    ) -> Result<Vec<Like>> {
+        LikesRepository::get_recent_likers(self, post_id, since, limit).await
+    }
+
+    async fn get_activity_feed(
+        &self,
+        post_ids: &[u32],
+        after: Option<&FeedCursor>,
+        limit: i32,
+    ) -> Result<Vec<Like>> {
+        LikesRepository::get_activity_feed(self, post_ids, after, limit).await
+    }
+
+    async fn get_user_liked_post_ids(&self, user_id: &str, limit: i32) -> Result<Vec<u32>> {
+        LikesRepository::get_user_liked_post_ids(self, user_id, limit).await
+    }
+
+    async fn get_likes_count_by_reaction(&self, post_id: &u32) -> Result<Vec<ReactionCount>> {
+        LikesRepository::get_likes_count_by_reaction(self, post_id).await
+    }
+
+    async fn unlike_posts(&self, user_ids: &[String], post_ids: &[u32]) -> Result<bool> {
+        LikesRepository::unlike_posts(self, user_ids, post_ids).await
+    }
+
+    async fn count_matching_likes(&self, user_ids: &[String], post_ids: &[u32]) -> Result<i64> {
+        LikesRepository::count_matching_likes(self, user_ids, post_ids).await
+    }
+
+    async fn delete_post_likes(&self, post_id: &u32) -> Result<usize> {
+        LikesRepository::delete_post_likes(self, post_id).await
+    }
+
+    async fn delete_user_likes(&self, user_id: &str) -> Result<usize> {
+        LikesRepository::delete_user_likes(self, user_id).await
+    }
+
+    async fn merge_user_likes(
+        &self,
+        from_user_id: &str,
+        to_user_id: &str,
+        remove_source: bool,
+    ) -> Result<LikeMerge> {
+        LikesRepository::merge_user_likes(self, from_user_id, to_user_id, remove_source).await
+    }
+
+    async fn purge_deleted_likes(&self, older_than: chrono::Duration) -> Result<usize> {
+        LikesRepository::purge_deleted_likes(self, older_than).await
+    }
+
+    async fn get_unvalidated_post_ids(&self, limit: i32) -> Result<Vec<u32>> {
+        LikesRepository::get_unvalidated_post_ids(self, limit).await
+    }
+
+    async fn confirm_unvalidated_likes(&self, post_ids: &[u32]) -> Result<usize> {
+        LikesRepository::confirm_unvalidated_likes(self, post_ids).await
+    }
+
+    async fn discard_unvalidated_likes(&self, post_ids: &[u32]) -> Result<usize> {
+        LikesRepository::discard_unvalidated_likes(self, post_ids).await
+    }
+
+    async fn get_idempotent_response(
+        &self,
+        user_id: &str,
+        key: &str,
+    ) -> Result<Option<IdempotentResponse>> {
+        LikesRepository::get_idempotent_response(self, user_id, key).await
+    }
+
+    async fn save_idempotent_response(
+        &self,
+        user_id: &str,
+        key: &str,
+        response: &IdempotentResponse,
+        ttl: chrono::Duration,
+    ) -> Result<()> {
+        LikesRepository::save_idempotent_response(self, user_id, key, response, ttl).await
+    }
+
+    async fn purge_expired_idempotency_keys(&self) -> Result<usize> {
+        LikesRepository::purge_expired_idempotency_keys(self).await
+    }
+
+    async fn verify_schema(&self, repair: bool) -> Result<SchemaReport> {
+        LikesRepository::verify_schema(self, repair).await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        LikesRepository::health_check(self).await
+    }
+}
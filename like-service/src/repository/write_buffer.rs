@@ -0,0 +1,206 @@
+use crate::{
+    error::{LikesError, Result},
+    models::{Like, ReactionType},
+    repository::LikesRepository,
+    telemetry::record_write_batch,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::Instant,
+};
+use tracing::{debug, warn};
+
+// One like waiting in the buffer, as written by the batch
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct BufferedLike {
+    pub user_id: String,
+    pub post_id: u32,
+    pub reaction: ReactionType,
+}
+
+#[derive(Debug)]
+struct PendingLike {
+    like: BufferedLike,
+    reply: oneshot::Sender<Result<Like>>,
+}
+
+/// Coalesces `create_like` calls: likes arriving within `window` of each
+/// other, up to `max_batch`, are written in one transaction and each caller
+/// gets the outcome of its own like. Batches are flushed one at a time.
+#[derive(Debug, Clone)]
+pub struct LikeWriteBuffer {
+    sender: mpsc::Sender<PendingLike>,
+}
+
+impl LikeWriteBuffer {
+    /// Start the flushing task, writing through `repository` (minus any
+    /// buffer of its own). The task ends once every handle to the buffer is
+    /// dropped.
+    pub fn spawn(
+        repository: LikesRepository,
+        window: Duration,
+        max_batch: usize,
+    ) -> (Self, JoinHandle<()>) {
+        let max_batch = max_batch.max(1);
+        // Callers wait for room once two batches are queued
+        let (sender, receiver) = mpsc::channel(max_batch * 2);
+        let task = tokio::spawn(run(
+            repository.without_write_buffer(),
+            receiver,
+            window,
+            max_batch,
+        ));
+        (Self { sender }, task)
+    }
+
+    /// Queue a like and wait for the batch it lands in to be written
+    pub async fn create_like(
+        &self,
+        user_id: &str,
+        post_id: &u32,
+        reaction: ReactionType,
+    ) -> Result<Like> {
+        let (reply, outcome) = oneshot::channel();
+        let pending = PendingLike {
+            like: BufferedLike {
+                user_id: user_id.to_string(),
+                post_id: *post_id,
+                reaction,
+            },
+            reply,
+        };
+
+        self.sender
+            .send(pending)
+            .await
+            .map_err(|_| LikesError::Internal("Like write buffer has stopped".to_string()))?;
+        outcome.await.map_err(|_| {
+            LikesError::Internal("Like write buffer dropped the request".to_string())
+        })?
+    }
+}
+
+async fn run(
+    repository: LikesRepository,
+    mut receiver: mpsc::Receiver<PendingLike>,
+    window: Duration,
+    max_batch: usize,
+) {
+    while let Some(first) = receiver.recv().await {
+        // The window opens with the first like of the batch
+        let deadline = Instant::now() + window;
+        let mut batch = vec![first];
+        while batch.len() < max_batch {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(pending)) => batch.push(pending),
+                // Window elapsed, or every sender is gone
+                _ => break,
+            }
+        }
+
+        flush(&repository, batch).await;
+    }
+    debug!("Like write buffer stopped");
+}
+
+async fn flush(repository: &LikesRepository, batch: Vec<PendingLike>) {
+    record_write_batch(batch.len());
+
+    // A user/post pair appearing twice is written once in the batch; the
+    // repeats follow one by one so each sees the earlier like, as it would
+    // unbuffered
+    let mut seen = HashSet::new();
+    let (batched, repeats): (Vec<_>, Vec<_>) = batch
+        .into_iter()
+        .partition(|pending| seen.insert((pending.like.user_id.clone(), pending.like.post_id)));
+
+    // Each user's like goes over the connection their reads are pinned to,
+    // so the batch is split by connection
+    let mut groups: BTreeMap<usize, Vec<PendingLike>> = BTreeMap::new();
+    for pending in batched {
+        groups
+            .entry(repository.write_connection(&pending.like.user_id))
+            .or_default()
+            .push(pending);
+    }
+    for group in groups.into_values() {
+        write_group(repository, group).await;
+    }
+
+    for pending in repeats {
+        write_one(repository, pending).await;
+    }
+}
+
+async fn write_group(repository: &LikesRepository, group: Vec<PendingLike>) {
+    let likes: Vec<BufferedLike> = group.iter().map(|pending| pending.like.clone()).collect();
+    match repository.insert_likes_batch(&likes).await {
+        Ok(outcomes) => {
+            for (pending, outcome) in group.into_iter().zip(outcomes) {
+                // The caller may have given up waiting; nothing to report to
+                let _ = pending.reply.send(outcome);
+            }
+        }
+        Err(e) => {
+            // Most likely a concurrent unbuffered write to one of the rows;
+            // writing singly gives each caller its own outcome
+            warn!(
+                "Batched write of {} likes failed, writing them one by one: {}",
+                group.len(),
+                e
+            );
+            for pending in group {
+                write_one(repository, pending).await;
+            }
+        }
+    }
+}
+
+async fn write_one(repository: &LikesRepository, pending: PendingLike) {
+    let like = &pending.like;
+    let outcome = repository
+        .create_like(&like.user_id, &like.post_id, like.reaction)
+        .await;
+    let _ = pending.reply.send(outcome);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LikeWriteBuffer;
+    use crate::{database::Database, models::ReactionType, repository::LikesRepository};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn buffered_likes_are_read_back_on_each_users_connection() {
+        let repository = LikesRepository::new(Database::memory_pool(4).await.unwrap())
+            .with_read_your_writes(true);
+        let (buffer, _task) =
+            LikeWriteBuffer::spawn(repository.clone(), Duration::from_millis(50), 100);
+        let repository = repository.with_write_buffer(buffer);
+
+        // One window's worth of users, spread over every pooled connection
+        let users: Vec<String> = (0..16).map(|i| format!("user_{}", i)).collect();
+        let likes = users
+            .iter()
+            .map(|user_id| repository.create_like(user_id, &1, ReactionType::Like));
+        for like in futures::future::join_all(likes).await {
+            like.unwrap();
+        }
+
+        for user_id in &users {
+            assert!(
+                repository
+                    .is_post_liked(user_id, &1)
+                    .await
+                    .unwrap()
+                    .is_some()
+            );
+        }
+    }
+}
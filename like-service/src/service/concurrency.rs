@@ -0,0 +1,110 @@
+use crate::service::{BoxResponseFuture, is_health_check};
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use tonic::{
+    Status,
+    codegen::http::{Request, Response},
+};
+use tower::{Layer, Service};
+use tracing::warn;
+
+/// Tower layer capping how many RPCs are handled at once across the server.
+/// Requests over the limit wait up to `queue_timeout` for a slot, then are
+/// answered `RESOURCE_EXHAUSTED` without reaching the service. A slot is
+/// held until the handler returns its response, not while a stream drains.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimitLayer {
+    // None when unlimited
+    permits: Option<Arc<Semaphore>>,
+    queue_timeout: Duration,
+}
+
+impl ConcurrencyLimitLayer {
+    /// Allow `max_concurrent_requests` at once; 0 disables the limit
+    pub fn new(max_concurrent_requests: usize, queue_timeout: Duration) -> Self {
+        Self {
+            permits: (max_concurrent_requests > 0)
+                .then(|| Arc::new(Semaphore::new(max_concurrent_requests))),
+            queue_timeout,
+        }
+    }
+
+    /// Slots currently free, or None when unlimited
+    pub fn available(&self) -> Option<usize> {
+        self.permits
+            .as_ref()
+            .map(|permits| permits.available_permits())
+    }
+}
+
+impl<S> Layer<S> for ConcurrencyLimitLayer {
+    type Service = ConcurrencyLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyLimit {
+            inner,
+            permits: self.permits.clone(),
+            queue_timeout: self.queue_timeout,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit<S> {
+    inner: S,
+    permits: Option<Arc<Semaphore>>,
+    queue_timeout: Duration,
+}
+
+impl<S, B, ResBody> Service<Request<B>> for ConcurrencyLimit<S>
+where
+    S: Service<Request<B>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+    ResBody: Default,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxResponseFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let permits = match &self.permits {
+            Some(permits) if !is_health_check(request.uri().path()) => permits.clone(),
+            _ => return Box::pin(self.inner.call(request)),
+        };
+
+        // The clone isn't ready yet; keep the one `poll_ready` was called on
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let queue_timeout = self.queue_timeout;
+
+        Box::pin(async move {
+            let permit = match tokio::time::timeout(queue_timeout, permits.acquire_owned()).await {
+                Ok(Ok(permit)) => permit,
+                // Timed out queueing, or the semaphore was closed
+                _ => {
+                    warn!(
+                        "Rejecting {}: server at its concurrency limit",
+                        request.uri().path()
+                    );
+                    return Ok(Status::resource_exhausted(
+                        "Server is at its concurrency limit, retry later",
+                    )
+                    .into_http());
+                }
+            };
+
+            let response = inner.call(request).await;
+            drop(permit);
+            response
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+use tonic::metadata::MetadataMap;
+
+/// The caller's deadline, taken from the `grpc-timeout` header when the request arrives
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Deadline(Option<Instant>);
+
+impl Deadline {
+    pub fn from_metadata(metadata: &MetadataMap) -> Self {
+        let timeout = metadata
+            .get("grpc-timeout")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_grpc_timeout);
+
+        Self(timeout.map(|timeout| Instant::now() + timeout))
+    }
+
+    /// Time left before the caller gives up, or `None` if it set no deadline
+    pub fn remaining(&self) -> Option<Duration> {
+        self.0
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+// A grpc-timeout value is up to 8 digits followed by a unit: H, M, S, m, u or n
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let unit = value.chars().last()?;
+    let digits = &value[..value.len() - unit.len_utf8()];
+    if digits.is_empty() || digits.len() > 8 {
+        return None;
+    }
+
+    let amount: u64 = digits.parse().ok()?;
+    match unit {
+        'H' => Some(Duration::from_secs(amount * 60 * 60)),
+        'M' => Some(Duration::from_secs(amount * 60)),
+        'S' => Some(Duration::from_secs(amount)),
+        'm' => Some(Duration::from_millis(amount)),
+        'u' => Some(Duration::from_micros(amount)),
+        'n' => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
@@ -0,0 +1,46 @@
+use tracing::debug;
+
+/// Optional fields of a listed like that a `field_mask` can select. The
+/// like's identity (`post_id` on user likes, `user_id` on post likes) is
+/// always returned; an empty mask returns every field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LikeFieldMask {
+    pub liked_at: bool,
+    pub created_at: bool,
+    pub reaction: bool,
+}
+
+impl Default for LikeFieldMask {
+    fn default() -> Self {
+        Self {
+            liked_at: true,
+            created_at: true,
+            reaction: true,
+        }
+    }
+}
+
+impl LikeFieldMask {
+    /// Select the named fields; unknown names are ignored
+    pub fn from_paths(paths: &[String]) -> Self {
+        if paths.is_empty() {
+            return Self::default();
+        }
+
+        let mut mask = Self {
+            liked_at: false,
+            created_at: false,
+            reaction: false,
+        };
+        for path in paths {
+            match path.trim() {
+                "liked_at" => mask.liked_at = true,
+                "created_at" => mask.created_at = true,
+                "reaction" => mask.reaction = true,
+                "post_id" | "user_id" => {}
+                unknown => debug!("Ignoring unknown field mask entry: {}", unknown),
+            }
+        }
+        mask
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use tokio::sync::Notify;
+
+/// Counts requests currently being handled so shutdown can wait for them
+#[derive(Debug, Clone, Default)]
+pub struct InFlightTracker {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    active: AtomicUsize,
+    idle: Notify,
+}
+
+impl InFlightTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a request as started; it is finished when the guard is dropped
+    pub fn track(&self) -> InFlightGuard {
+        self.inner.active.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard {
+            inner: self.inner.clone(),
+        }
+    }
+
+    pub fn active(&self) -> usize {
+        self.inner.active.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once no tracked request is running
+    pub async fn wait_idle(&self) {
+        loop {
+            // Register before checking so a concurrent drop can't be missed
+            let notified = self.inner.idle.notified();
+            if self.active() == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InFlightGuard {
+    inner: Arc<Inner>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.inner.active.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
@@ -1,63 +1,700 @@
 use crate::{
-    clients::{PostClient, UserClient},
-    models::PaginationParams,
+    auth::{Caller, authorize},
+    clients::{PostMetadata, PostValidator, UserValidator, is_dependency_failure},
+    clock::{Clock, SystemClock},
+    error::LikesError,
+    events::{EventPublisher, LikeAction, LikeEvent, NoopPublisher, PostWatchers, WatchedEvent},
+    models::{
+        BucketWidth, FeedCursor, IdempotentResponse, LikeSort, LikeSortField, LikedAtRange,
+        PaginatedResult, PaginationBounds, PaginationParams, ReactionType, SortDirection,
+    },
     proto::{likes_service_server::LikesService, *},
-    repository::LikesRepository,
+    repository::LikesStore,
+    service::{Deadline, InFlightTracker, LikeFieldMask, RateLimiter, UserIdNormalizer},
+    telemetry::observe_rpc,
 };
-use tonic::{Request, Response, Status};
-use tracing::{debug, error, info};
+use moka::future::Cache;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Code, Request, Response, Status};
+use tonic_types::{ErrorDetails, StatusExt};
+use tracing::{debug, error, info, warn};
+
+// Upper bound on the number of post IDs accepted by batch lookups
+const MAX_BATCH_POST_IDS: usize = 200;
+
+// Upper bound on the number of user IDs accepted by batch writes; each
+// Clerk ID costs a User Service lookup
+const MAX_BATCH_USER_IDS: usize = 200;
+
+// Default and maximum number of entries returned by ranking queries
+const DEFAULT_RANKING_LIMIT: i32 = 10;
+const MAX_RANKING_LIMIT: i32 = 100;
+
+// Widest window accepted by GetRecentLikers; older likes belong to GetPostLikes
+const MAX_RECENT_LIKERS_WINDOW_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+// Buckets in a GetLikesHistogram range by default and at most: a month of
+// hours, or about two years of days
+const DEFAULT_HISTOGRAM_BUCKETS: i32 = 30;
+const MAX_HISTOGRAM_BUCKETS: i64 = 744;
+
+// Window counted by `GetLikesCountSince` when the request sets no `since`
+const DEFAULT_LIKES_COUNT_SINCE: chrono::Duration = chrono::Duration::hours(1);
+
+// How long HealthCheck waits on each of the User and Post services
+const DEPENDENCY_HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+// User or Post Service lookups in flight at once when validating a batch,
+// by default and at most
+const DEFAULT_VALIDATION_CONCURRENCY: usize = 16;
+const MAX_VALIDATION_CONCURRENCY: usize = 64;
+
+// Most recent likes examined by GetLikesBetweenUsers. Authorship is not stored
+// with likes, so each examined post costs one Post Service lookup.
+const MAX_RECIPROCITY_POSTS: i32 = 200;
+
+// Default and maximum number of posts returned by GetRecentLikedPosts; each
+// costs a Post Service lookup
+const DEFAULT_RECENT_LIKED_POSTS: i32 = 10;
+const MAX_RECENT_LIKED_POSTS: i32 = 50;
+
+// Rows fetched from the database per round-trip when streaming likes
+const STREAM_BATCH_SIZE: usize = 500;
+
+// Posts re-validated per reconciliation run
+const RECONCILE_BATCH_SIZE: i32 = 200;
+
+/// Reachability of the service's dependencies, from [`LikesServiceImpl::check_dependencies`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DependencyStatus {
+    pub database: bool,
+    pub user_service: bool,
+    pub post_service: bool,
+}
+
+impl DependencyStatus {
+    pub fn all_healthy(&self) -> bool {
+        self.database && self.user_service && self.post_service
+    }
+
+    /// Each dependency by name, in reporting order
+    pub fn entries(&self) -> [(&'static str, bool); 3] {
+        [
+            ("database", self.database),
+            ("user_service", self.user_service),
+            ("post_service", self.post_service),
+        ]
+    }
+
+    /// Names of the dependencies that did not answer
+    pub fn unhealthy(&self) -> Vec<&'static str> {
+        self.entries()
+            .into_iter()
+            .filter(|(_, healthy)| !healthy)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+/// Outcome of one [`LikesServiceImpl::reconcile_unvalidated_likes`] run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reconciliation {
+    /// Likes on posts that exist, now validated
+    pub confirmed: usize,
+    /// Likes on posts that do not exist, now soft-deleted
+    pub discarded: usize,
+    /// Posts the Post Service still could not answer for
+    pub pending: usize,
+}
 
 #[derive(Debug)]
 pub struct LikesServiceImpl {
-    repository: LikesRepository,
-    user_client: UserClient,
-    post_client: PostClient,
+    repository: Arc<dyn LikesStore>,
+    user_client: Arc<dyn UserValidator>,
+    post_client: Arc<dyn PostValidator>,
+    event_publisher: Arc<dyn EventPublisher>,
+    event_topic: String,
+    // Subscribers of `watch_post_likes`, by post
+    watchers: PostWatchers,
+    in_flight: InFlightTracker,
+    user_likes_bounds: PaginationBounds,
+    post_likes_bounds: PaginationBounds,
+    idempotency_ttl: chrono::Duration,
+    rate_limiter: Option<RateLimiter>,
+    // Clerk ID → DB ID, so repeated calls for a user skip the User Service
+    user_id_cache: Option<Cache<String, String>>,
+    // Downstream lookups in flight at once for a batch request
+    validation_concurrency: usize,
+    // Record likes unvalidated instead of failing while the Post Service is down
+    lenient_post_validation: bool,
+    // Applied to every user ID in a request before it is resolved or stored
+    user_ids: UserIdNormalizer,
+    // "Now" for default windows, event times and health reports
+    clock: Arc<dyn Clock>,
 }
 
 impl LikesServiceImpl {
     pub fn new(
-        repository: LikesRepository,
-        user_client: UserClient,
-        post_client: PostClient,
+        repository: impl LikesStore + 'static,
+        user_client: Arc<dyn UserValidator>,
+        post_client: Arc<dyn PostValidator>,
     ) -> Self {
         Self {
-            repository,
+            repository: Arc::new(repository),
             user_client,
             post_client,
+            event_publisher: Arc::new(NoopPublisher),
+            event_topic: String::new(),
+            watchers: PostWatchers::default(),
+            in_flight: InFlightTracker::new(),
+            user_likes_bounds: PaginationBounds::default(),
+            post_likes_bounds: PaginationBounds::default(),
+            idempotency_ttl: chrono::Duration::hours(24),
+            rate_limiter: None,
+            user_id_cache: None,
+            validation_concurrency: DEFAULT_VALIDATION_CONCURRENCY,
+            lenient_post_validation: false,
+            user_ids: UserIdNormalizer::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Take the current time from `clock`; give the repository the same one
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Limit how often each user may like, unlike or toggle
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    fn check_rate_limit(&self, user_id: &str) -> Result<(), Status> {
+        match &self.rate_limiter {
+            Some(limiter) if !limiter.try_acquire(user_id) => {
+                warn!("Rate limit exceeded for user {}", user_id);
+                Err(Status::resource_exhausted(
+                    "Too many like requests, please slow down",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Remember resolved Clerk IDs for `ttl`; unknown users are never cached
+    pub fn with_user_id_cache(mut self, ttl: Duration, capacity: u64) -> Self {
+        self.user_id_cache = Some(
+            Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(ttl)
+                .build(),
+        );
+        self
+    }
+
+    /// Validate up to `concurrency` users or posts of a batch at once,
+    /// capped at 64 to spare the User and Post services
+    pub fn with_validation_concurrency(mut self, concurrency: usize) -> Self {
+        self.validation_concurrency = concurrency.clamp(1, MAX_VALIDATION_CONCURRENCY);
+        self
+    }
+
+    /// While the Post Service is unavailable, let `like_post` record likes
+    /// flagged `unvalidated` instead of failing. A post reported missing is
+    /// still rejected.
+    pub fn with_lenient_post_validation(mut self, lenient: bool) -> Self {
+        self.lenient_post_validation = lenient;
+        self
+    }
+
+    /// Lowercase user IDs before using them; they are always trimmed
+    pub fn with_lowercase_user_ids(mut self, lowercase: bool) -> Self {
+        self.user_ids = UserIdNormalizer::new(lowercase);
+        self
+    }
+
+    /// Forget the cached DB ID of a Clerk user, e.g. after the account is erased
+    pub async fn invalidate_user_id(&self, user_id: &str) {
+        if let Some(cache) = &self.user_id_cache {
+            cache.invalidate(user_id).await;
+        }
+    }
+
+    /// How long a `like_post` idempotency key replays its first response
+    pub fn with_idempotency_ttl(mut self, ttl: chrono::Duration) -> Self {
+        self.idempotency_ttl = ttl;
+        self
+    }
+
+    /// Page size limits for `get_user_likes` and `get_post_likes`
+    pub fn with_pagination_bounds(
+        mut self,
+        user_likes: PaginationBounds,
+        post_likes: PaginationBounds,
+    ) -> Self {
+        self.user_likes_bounds = user_likes;
+        self.post_likes_bounds = post_likes;
+        self
+    }
+
+    /// Share a tracker with the caller so shutdown can wait for in-flight writes
+    pub fn with_in_flight_tracker(mut self, in_flight: InFlightTracker) -> Self {
+        self.in_flight = in_flight;
+        self
+    }
+
+    pub fn with_event_publisher(
+        mut self,
+        publisher: Arc<dyn EventPublisher>,
+        topic: String,
+    ) -> Self {
+        self.event_publisher = publisher;
+        self.event_topic = topic;
+        self
+    }
+
+    /// Share post watchers, e.g. with a custom channel capacity
+    pub fn with_post_watchers(mut self, watchers: PostWatchers) -> Self {
+        self.watchers = watchers;
+        self
+    }
+
+    // Publish in the background; a failed publish is logged and never fails the RPC.
+    // Subscribers of the post are handed the event first, without waiting.
+    fn publish_event(&self, user_id: &str, post_id: u32, action: LikeAction) {
+        let publisher = self.event_publisher.clone();
+        let topic = self.event_topic.clone();
+        let event = LikeEvent::new_at(user_id.to_string(), post_id, action, self.clock.now());
+        self.watchers.publish(&event);
+
+        tokio::spawn(async move {
+            if let Err(e) = publisher.publish(&topic, event).await {
+                warn!(
+                    "Failed to publish {:?} event for post {}: {}",
+                    action, post_id, e
+                );
+            }
+        });
+    }
+
+    fn like_change_to_proto(watched: WatchedEvent) -> LikeChange {
+        let action = match watched.event.action {
+            LikeAction::Liked => LikeChangeAction::Liked,
+            LikeAction::Unliked => LikeChangeAction::Unliked,
+        };
+
+        LikeChange {
+            user_id: watched.event.user_id,
+            post_id: watched.event.post_id,
+            action: action as i32,
+            occurred_at: Some(Self::datetime_to_timestamp(watched.event.timestamp)),
+            missed: watched.missed,
         }
     }
 
+    // INVALID_ARGUMENT with a BadRequest detail naming the offending field
+    fn invalid_field(field: &str, description: impl Into<String>) -> Status {
+        let description = description.into();
+        Status::with_error_details(
+            Code::InvalidArgument,
+            description.clone(),
+            ErrorDetails::with_bad_request_violation(field, description),
+        )
+    }
+
     fn validate_ids(user_id: &str, post_id: &u32) -> Result<(), Status> {
         if user_id.trim().is_empty() {
-            return Err(Status::invalid_argument("User ID cannot be empty"));
+            return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
         }
 
         if *post_id == 0 {
-            return Err(Status::invalid_argument("Post ID must be greater than 0"));
+            return Err(Self::invalid_field(
+                "post_id",
+                "Post ID must be greater than 0",
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Zero means "use the default"; only negative values are rejected
+    fn validate_pagination(page: i32, limit: i32) -> Result<(), Status> {
+        let mut details = ErrorDetails::new();
+
+        if page < 0 {
+            details.add_bad_request_violation("page", "Page cannot be negative");
+        }
+
+        if limit < 0 {
+            details.add_bad_request_violation("limit", "Limit cannot be negative");
+        }
+
+        if details.has_bad_request_violations() {
+            return Err(Status::with_error_details(
+                Code::InvalidArgument,
+                "Invalid pagination parameters",
+                details,
+            ));
         }
 
         Ok(())
     }
 
-    async fn validate_user(&mut self, user_id: &str) -> Result<bool, Status> {
-        match self.user_client.user_exists(user_id.to_string()).await {
+    // Deep pages cost the database every row they skip
+    fn check_page_depth(
+        params: &PaginationParams,
+        bounds: &PaginationBounds,
+    ) -> Result<(), Status> {
+        if params.within_depth(bounds) {
+            return Ok(());
+        }
+
+        Err(Self::invalid_field(
+            "page",
+            format!(
+                "Page {} starts past row {}, the deepest served; narrow the results or stream them instead",
+                params.page, bounds.max_offset
+            ),
+        ))
+    }
+
+    async fn validate_user(&self, user_id: &str) -> Result<bool, Status> {
+        match self.user_client.user_exists(user_id, None).await {
             Ok(exists) => Ok(exists),
             Err(e) => {
                 error!("Failed to validate user {}: {}", user_id, e);
-                Err(Status::internal("Failed to validate user"))
+                Err(Self::client_error("Failed to validate user", e))
             }
         }
     }
 
     // Helper method to validate if post exists
-    async fn validate_post(&mut self, post_id: u32) -> Result<bool, Status> {
-        match self.post_client.post_exists(post_id).await {
+    async fn validate_post(&self, post_id: u32) -> Result<bool, Status> {
+        match self.post_client.post_exists(post_id, None).await {
             Ok(exists) => Ok(exists),
             Err(e) => {
                 error!("Failed to validate post {}: {}", post_id, e);
-                Err(Status::internal("Failed to validate post"))
+                Err(Self::client_error("Failed to validate post", e))
+            }
+        }
+    }
+
+    // UNAVAILABLE when the dependency is down (so callers may retry),
+    // INTERNAL otherwise
+    fn client_error(context: &str, e: anyhow::Error) -> Status {
+        if is_dependency_failure(&e) {
+            LikesError::Downstream(format!("{}: {}", context, e)).into()
+        } else {
+            Status::internal(format!("{}: {}", context, e))
+        }
+    }
+
+    // Whether a post's likes must be shown without user IDs: only the post's
+    // author sees who liked it. With auth enabled the verified caller is the
+    // viewer, and an anonymous or unknown one is not the author. Without auth
+    // the caller-supplied `viewer_id` is used, and callers omitting it are
+    // trusted with full detail.
+    async fn hide_likers(
+        &self,
+        caller: Option<&Caller>,
+        post_id: u32,
+        viewer_id: &str,
+    ) -> Result<bool, Status> {
+        let db_viewer_id = match caller {
+            Some(Caller::Anonymous) => return Ok(true),
+            Some(Caller::User(subject)) => {
+                match self
+                    .resolve_user_db_id(&self.user_ids.normalize(subject))
+                    .await
+                {
+                    Ok(db_viewer_id) => db_viewer_id,
+                    Err(status) if status.code() == Code::NotFound => return Ok(true),
+                    Err(status) => return Err(status),
+                }
+            }
+            None if viewer_id.trim().is_empty() => return Ok(false),
+            None => self.resolve_user_db_id(viewer_id).await?,
+        };
+
+        let is_author = self
+            .post_client
+            .is_post_owner(post_id, &db_viewer_id, None)
+            .await
+            .map_err(|e| Self::client_error("Post ownership check failed", e))?;
+        Ok(!is_author)
+    }
+
+    // Helper method to translate an external (Clerk) user ID into the DB ID
+    async fn resolve_user_db_id(&self, user_id: &str) -> Result<String, Status> {
+        if !user_id.starts_with("user_") {
+            // Already a DB ID → use as is
+            return Ok(user_id.to_string());
+        }
+
+        let cached = match &self.user_id_cache {
+            Some(cache) => cache.get(user_id).await,
+            None => None,
+        };
+        if let Some(db_user_id) = cached {
+            return Ok(db_user_id);
+        }
+
+        // Clerk ID → fetch actual DB ID
+        let user = self
+            .user_client
+            .get_user(user_id, None)
+            .await
+            .map_err(|e| Self::client_error("Failed to get user details", e))?;
+
+        let db_user_id = user
+            .user
+            .as_ref()
+            .map(|u| u.id.clone())
+            .ok_or_else(|| Status::not_found("User not found"))?;
+
+        if let Some(cache) = &self.user_id_cache {
+            cache.insert(user_id.to_string(), db_user_id.clone()).await;
+        }
+        Ok(db_user_id)
+    }
+
+    // Resolve many user IDs, `validation_concurrency` at a time. Results
+    // follow `user_ids`, so distinct IDs mapping to one DB ID repeat it.
+    // Every failing ID is reported, not just the first one.
+    async fn resolve_user_db_ids(&self, user_ids: &[String]) -> Result<Vec<String>, Status> {
+        let resolutions: Vec<(usize, Result<String, Status>)> =
+            futures::StreamExt::buffer_unordered(
+                futures::stream::iter(
+                    user_ids
+                        .iter()
+                        .enumerate()
+                        .map(|(index, user_id)| async move {
+                            (index, self.resolve_user_db_id(user_id).await)
+                        }),
+                ),
+                self.validation_concurrency,
+            )
+            .collect()
+            .await;
+
+        let mut db_user_ids = vec![String::new(); user_ids.len()];
+        let mut not_found = Vec::new();
+        let mut failed = Vec::new();
+        for (index, resolution) in resolutions {
+            match resolution {
+                Ok(db_user_id) => db_user_ids[index] = db_user_id,
+                Err(status) if status.code() == Code::NotFound => not_found.push(index),
+                Err(status) => failed.push((index, status)),
+            }
+        }
+
+        // A dependency failure outranks unknown users: retrying may succeed
+        if let Some((index, status)) = failed.into_iter().min_by_key(|(index, _)| *index) {
+            return Err(Status::new(
+                status.code(),
+                format!(
+                    "Failed to resolve user_ids[{}] '{}': {}",
+                    index,
+                    user_ids[index],
+                    status.message()
+                ),
+            ));
+        }
+
+        if !not_found.is_empty() {
+            not_found.sort_unstable();
+            let mut details = ErrorDetails::new();
+            for index in &not_found {
+                details.add_bad_request_violation(
+                    format!("user_ids[{}]", index),
+                    format!("User '{}' not found", user_ids[*index]),
+                );
+            }
+            return Err(Status::with_error_details(
+                Code::NotFound,
+                format!("{} of {} users not found", not_found.len(), user_ids.len()),
+                details,
+            ));
+        }
+
+        Ok(db_user_ids)
+    }
+
+    // Check many posts, `validation_concurrency` at a time. Results follow
+    // `post_ids`; on failure the earliest failing post is reported.
+    async fn posts_exist(
+        &self,
+        post_ids: &[u32],
+        timeout: Option<Duration>,
+    ) -> Result<Vec<bool>, Status> {
+        self.check_posts(post_ids, timeout)
+            .await
+            .into_iter()
+            .map(|check| check.map_err(|e| Self::client_error("Post validation failed", e)))
+            .collect()
+    }
+
+    // Existence of each post, in order, each check failing on its own
+    async fn check_posts(
+        &self,
+        post_ids: &[u32],
+        timeout: Option<Duration>,
+    ) -> Vec<anyhow::Result<bool>> {
+        let mut checks: Vec<(usize, anyhow::Result<bool>)> =
+            futures::StreamExt::buffer_unordered(
+                futures::stream::iter(post_ids.iter().enumerate().map(
+                    |(index, post_id)| async move {
+                        (index, self.post_client.post_exists(*post_id, timeout).await)
+                    },
+                )),
+                self.validation_concurrency,
+            )
+            .collect()
+            .await;
+        checks.sort_unstable_by_key(|(index, _)| *index);
+        checks.into_iter().map(|(_, check)| check).collect()
+    }
+
+    /// Check the database and both services at once, giving each service up
+    /// to `timeout` to answer
+    pub async fn check_dependencies(&self, timeout: Duration) -> DependencyStatus {
+        let (database, user_service, post_service) = tokio::join!(
+            self.repository.health_check(),
+            self.user_client.health_check(Some(timeout)),
+            self.post_client.health_check(Some(timeout)),
+        );
+
+        let database = match database {
+            Ok(healthy) => healthy,
+            Err(e) => {
+                error!("Database health check failed: {}", e);
+                false
+            }
+        };
+
+        DependencyStatus {
+            database,
+            user_service,
+            post_service,
+        }
+    }
+
+    /// Re-validate posts that have likes recorded under lenient validation.
+    /// Likes on existing posts are confirmed, likes on missing posts are
+    /// soft-deleted, and posts the Post Service still fails on stay flagged
+    /// for the next run.
+    pub async fn reconcile_unvalidated_likes(&self) -> Result<Reconciliation, Status> {
+        let post_ids = self
+            .repository
+            .get_unvalidated_post_ids(RECONCILE_BATCH_SIZE)
+            .await?;
+        if post_ids.is_empty() {
+            return Ok(Reconciliation::default());
+        }
+
+        let mut existing = Vec::new();
+        let mut missing = Vec::new();
+        let mut pending = 0;
+        for (post_id, check) in post_ids.iter().zip(self.check_posts(&post_ids, None).await) {
+            match check {
+                Ok(true) => existing.push(*post_id),
+                Ok(false) => missing.push(*post_id),
+                Err(e) => {
+                    debug!("Post {} still cannot be validated: {}", post_id, e);
+                    pending += 1;
+                }
             }
         }
+
+        let confirmed = if existing.is_empty() {
+            0
+        } else {
+            self.repository.confirm_unvalidated_likes(&existing).await?
+        };
+        let discarded = if missing.is_empty() {
+            0
+        } else {
+            self.repository.discard_unvalidated_likes(&missing).await?
+        };
+
+        Ok(Reconciliation {
+            confirmed,
+            discarded,
+            pending,
+        })
+    }
+
+    // Drop repeated items, keeping the first occurrence of each
+    fn dedupe<T: Eq + Hash + Clone>(items: Vec<T>) -> Vec<T> {
+        let mut seen = HashSet::with_capacity(items.len());
+        items
+            .into_iter()
+            .filter(|item| seen.insert(item.clone()))
+            .collect()
+    }
+
+    fn reaction_from_proto(reaction: Reaction) -> ReactionType {
+        match reaction {
+            Reaction::Unspecified | Reaction::Like => ReactionType::Like,
+            Reaction::Love => ReactionType::Love,
+            Reaction::Laugh => ReactionType::Laugh,
+            Reaction::Wow => ReactionType::Wow,
+            Reaction::Sad => ReactionType::Sad,
+            Reaction::Angry => ReactionType::Angry,
+        }
+    }
+
+    fn reaction_to_proto(reaction: ReactionType) -> i32 {
+        let reaction = match reaction {
+            ReactionType::Like => Reaction::Like,
+            ReactionType::Love => Reaction::Love,
+            ReactionType::Laugh => Reaction::Laugh,
+            ReactionType::Wow => Reaction::Wow,
+            ReactionType::Sad => Reaction::Sad,
+            ReactionType::Angry => Reaction::Angry,
+        };
+        reaction as i32
+    }
+
+    fn sort_from_proto(field: SortField, order: SortOrder) -> LikeSort {
+        LikeSort {
+            field: match field {
+                SortField::Unspecified | SortField::CreatedAt => LikeSortField::CreatedAt,
+                SortField::LikedAt => LikeSortField::LikedAt,
+            },
+            direction: match order {
+                SortOrder::Unspecified | SortOrder::Desc => SortDirection::Desc,
+                SortOrder::Asc => SortDirection::Asc,
+            },
+        }
+    }
+
+    fn timestamp_to_datetime(
+        field: &str,
+        ts: prost_types::Timestamp,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Status> {
+        chrono::DateTime::from_timestamp(ts.seconds, ts.nanos.max(0) as u32)
+            .ok_or_else(|| Self::invalid_field(field, "Invalid timestamp"))
+    }
+
+    // Lower bound on liked_at for a ranking window; `None` counts all likes
+    fn window_start(&self, window: TimeWindow) -> Option<chrono::DateTime<chrono::Utc>> {
+        let length = match window {
+            TimeWindow::Unspecified | TimeWindow::AllTime => return None,
+            TimeWindow::Day => chrono::Duration::days(1),
+            TimeWindow::Week => chrono::Duration::weeks(1),
+            TimeWindow::Month => chrono::Duration::days(30),
+        };
+        Some(self.clock.now() - length)
     }
 
     fn datetime_to_timestamp(dt: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
@@ -66,423 +703,2099 @@ impl LikesServiceImpl {
             nanos: dt.timestamp_subsec_nanos() as i32,
         }
     }
-}
 
-#[tonic::async_trait]
-impl LikesService for LikesServiceImpl {
-    async fn like_post(
+    // Validate and record a like; the response is what an idempotent retry gets back
+    async fn process_like_post(
         &self,
-        request: Request<LikePostRequest>,
-    ) -> Result<Response<LikePostResponse>, Status> {
-        let req = request.into_inner();
-        info!(
-            "Like post request: user_id={}, post_id={}",
-            req.user_id, req.post_id
-        );
-
-        Self::validate_ids(&req.user_id, &req.post_id)?;
+        req: &LikePostRequest,
+        deadline: Deadline,
+    ) -> Result<LikePostResponse, Status> {
+        let reaction = Self::reaction_from_proto(req.reaction());
 
-        // Clone the clients to make them mutable for this call
-        let mut user_client = self.user_client.clone();
-        let mut post_client = self.post_client.clone();
+        // Validation is bounded by whatever time the caller has left
+        let timeout = deadline.remaining();
 
         // Validate user exists before allowing them to like a post
-        if !user_client
-            .user_exists(req.user_id.clone())
+        if !self
+            .user_client
+            .user_exists(&req.user_id, timeout)
             .await
-            .map_err(|e| Status::internal(format!("User validation failed: {}", e)))?
+            .map_err(|e| Self::client_error("User validation failed", e))?
         {
-            return Ok(Response::new(LikePostResponse {
+            return Ok(LikePostResponse {
                 success: false,
                 message: "User not found".to_string(),
                 liked_at: None,
-            }));
+            });
         }
 
         // Validate post exists before allowing it to be liked
-        if !post_client
-            .post_exists(req.post_id)
-            .await
-            .map_err(|e| Status::internal(format!("Post validation failed: {}", e)))?
-        {
-            return Ok(Response::new(LikePostResponse {
-                success: false,
-                message: "Post not found".to_string(),
-                liked_at: None,
-            }));
-        }
+        let unvalidated = match self.post_client.post_exists(req.post_id, timeout).await {
+            Ok(true) => false,
+            Ok(false) => {
+                return Ok(LikePostResponse {
+                    success: false,
+                    message: "Post not found".to_string(),
+                    liked_at: None,
+                });
+            }
+            Err(e) if self.lenient_post_validation && is_dependency_failure(&e) => {
+                warn!(
+                    "Post Service unavailable, recording like on post {} unvalidated: {}",
+                    req.post_id, e
+                );
+                true
+            }
+            Err(e) => return Err(Self::client_error("Post validation failed", e)),
+        };
 
-        let db_user_id = if req.user_id.starts_with("user_") {
-            // Clerk ID → fetch actual DB ID
-            let user = user_client
-                .get_user(req.user_id.clone())
-                .await
-                .map_err(|e| Status::internal(format!("Failed to get user details: {}", e)))?;
+        let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
 
-            user.user
-                .as_ref()
-                .ok_or_else(|| Status::not_found("User not found"))?
-                .id
-                .clone()
+        let created = if unvalidated {
+            self.repository
+                .create_unvalidated_like(&db_user_id, &req.post_id, reaction)
+                .await
         } else {
-            // Already a DB ID → use as is
-            req.user_id.clone()
+            self.repository
+                .create_like(&db_user_id, &req.post_id, reaction)
+                .await
         };
-
-        match self.repository.create_like(&db_user_id, &req.post_id).await {
+        match created {
             Ok(like) => {
                 info!(
                     "Successfully liked post: user_id={}, post_id={}",
                     req.user_id, req.post_id
                 );
-                Ok(Response::new(LikePostResponse {
+                self.publish_event(&db_user_id, req.post_id, LikeAction::Liked);
+                Ok(LikePostResponse {
                     success: true,
                     message: "Post liked successfully".to_string(),
                     liked_at: Some(Self::datetime_to_timestamp(like.liked_at)),
-                }))
+                })
+            }
+            Err(LikesError::AlreadyExists(_)) if !req.strict => {
+                // Treat a repeated like as success so client retries are idempotent
+                let liked_at = self
+                    .repository
+                    .is_post_liked(&db_user_id, &req.post_id)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to fetch existing like: {}", e);
+                        Status::from(e)
+                    })?;
+
+                info!(
+                    "Post already liked: user_id={}, post_id={}",
+                    req.user_id, req.post_id
+                );
+                Ok(LikePostResponse {
+                    success: true,
+                    message: "Already liked".to_string(),
+                    liked_at: liked_at.map(Self::datetime_to_timestamp),
+                })
             }
             Err(e) => {
                 error!("Failed to like post: {}", e);
-                println!("Failed to like post: {}", e);
                 Err(e.into())
             }
         }
     }
+}
 
-    async fn unlike_post(
+#[tonic::async_trait]
+impl LikesService for LikesServiceImpl {
+    async fn like_post(
         &self,
-        request: Request<UnlikePostRequest>,
-    ) -> Result<Response<UnlikePostResponse>, Status> {
-        let req = request.into_inner();
-        info!(
-            "Unlike post request: user_id={}, post_id={}",
-            req.user_id, req.post_id
-        );
+        request: Request<LikePostRequest>,
+    ) -> Result<Response<LikePostResponse>, Status> {
+        observe_rpc("like_post", async {
+            let _in_flight = self.in_flight.track();
+            authorize(&request, &request.get_ref().user_id, &self.user_ids)?;
+            let deadline = Deadline::from_metadata(request.metadata());
+            let req = self.user_ids.apply(request.into_inner());
+            let reaction = Self::reaction_from_proto(req.reaction());
+            info!(
+                "Like post request: user_id={}, post_id={}, reaction={}",
+                req.user_id,
+                req.post_id,
+                reaction.as_str()
+            );
 
-        let mut user_client = self.user_client.clone();
+            Self::validate_ids(&req.user_id, &req.post_id)?;
+            self.check_rate_limit(&req.user_id)?;
 
-        Self::validate_ids(&req.user_id, &req.post_id)?;
+            let idempotency_key = Some(req.idempotency_key.trim()).filter(|key| !key.is_empty());
+            let stored = match idempotency_key {
+                Some(key) => {
+                    self.repository
+                        .get_idempotent_response(&req.user_id, key)
+                        .await?
+                }
+                None => None,
+            };
 
-        let db_user_id = if req.user_id.starts_with("user_") {
-            // Clerk ID → fetch actual DB ID
-            let user = user_client
-                .get_user(req.user_id.clone())
-                .await
-                .map_err(|e| Status::internal(format!("Failed to get user details: {}", e)))?;
+            if let Some(stored) = stored {
+                info!(
+                    "Replaying like response for idempotency key: user_id={}, post_id={}",
+                    req.user_id, req.post_id
+                );
+                return Ok(Response::new(LikePostResponse {
+                    success: stored.success,
+                    message: stored.message,
+                    liked_at: stored.liked_at.map(Self::datetime_to_timestamp),
+                }));
+            }
 
-            user.user
-                .as_ref()
-                .ok_or_else(|| Status::not_found("User not found"))?
-                .id
-                .clone()
-        } else {
-            // Already a DB ID → use as is
-            req.user_id.clone()
-        };
+            let response = self.process_like_post(&req, deadline).await?;
 
-        match self.repository.delete_like(&db_user_id, &req.post_id).await {
-            Ok(deleted) => {
-                if deleted {
-                    info!(
-                        "Successfully unliked post: user_id={}, post_id={}",
-                        req.user_id, req.post_id
-                    );
-                    Ok(Response::new(UnlikePostResponse {
-                        success: true,
-                        message: "Post unliked successfully".to_string(),
-                    }))
-                } else {
-                    Ok(Response::new(UnlikePostResponse {
-                        success: false,
-                        message: "Like not found".to_string(),
-                    }))
+            if let Some(idempotency_key) = idempotency_key {
+                let stored = IdempotentResponse {
+                    success: response.success,
+                    message: response.message.clone(),
+                    liked_at: response
+                        .liked_at
+                        .map(|ts| Self::timestamp_to_datetime("liked_at", ts))
+                        .transpose()?,
+                };
+                if let Err(e) = self
+                    .repository
+                    .save_idempotent_response(
+                        &req.user_id,
+                        idempotency_key,
+                        &stored,
+                        self.idempotency_ttl,
+                    )
+                    .await
+                {
+                    warn!("Failed to store idempotency key {}: {}", idempotency_key, e);
                 }
             }
-            Err(e) => {
-                error!("Failed to unlike post: {}", e);
-                println!("Failed to unlike post: {}", e);
-                Err(e.into())
-            }
-        }
+
+            Ok(Response::new(response))
+        })
+        .await
     }
 
-    async fn get_user_likes(
+    async fn like_posts(
         &self,
-        request: Request<GetUserLikesRequest>,
-    ) -> Result<Response<GetUserLikesResponse>, Status> {
-        let req = request.into_inner();
-        debug!(
-            "Get user likes request: user_id={}, page={}, limit={}",
-            req.user_id, req.page, req.limit
-        );
+        request: Request<LikePostsRequest>,
+    ) -> Result<Response<LikePostsResponse>, Status> {
+        observe_rpc("like_posts", async {
+            let _in_flight = self.in_flight.track();
+            authorize(&request, &request.get_ref().user_id, &self.user_ids)?;
+            let deadline = Deadline::from_metadata(request.metadata());
+            let req = self.user_ids.apply(request.into_inner());
+            let reaction = Self::reaction_from_proto(req.reaction());
+            info!(
+                "Like posts request: user_id={}, posts={}, skip_validation={}",
+                req.user_id,
+                req.post_ids.len(),
+                req.skip_validation
+            );
 
-        let mut user_client = self.user_client.clone();
+            if req.user_id.trim().is_empty() {
+                return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
+            }
 
-        if req.user_id.trim().is_empty() {
-            return Err(Status::invalid_argument("User ID cannot be empty"));
-        }
+            if req.post_ids.is_empty() {
+                return Err(Self::invalid_field("post_ids", "Post IDs cannot be empty"));
+            }
+
+            if req.post_ids.len() > MAX_BATCH_POST_IDS {
+                return Err(Self::invalid_field(
+                    "post_ids",
+                    format!(
+                        "At most {} post IDs can be liked at once, got {}",
+                        MAX_BATCH_POST_IDS,
+                        req.post_ids.len()
+                    ),
+                ));
+            }
+
+            if let Some(index) = req.post_ids.iter().position(|id| *id == 0) {
+                return Err(Self::invalid_field(
+                    &format!("post_ids[{}]", index),
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            let timeout = deadline.remaining();
 
-        let db_user_id = if req.user_id.starts_with("user_") {
-            // Clerk ID → fetch actual DB ID
-            let user = user_client
-                .get_user(req.user_id.clone())
+            // The user is validated once for the whole batch
+            if !self
+                .user_client
+                .user_exists(&req.user_id, timeout)
                 .await
-                .map_err(|e| Status::internal(format!("Failed to get user details: {}", e)))?;
+                .map_err(|e| Self::client_error("User validation failed", e))?
+            {
+                return Err(Status::not_found("User not found"));
+            }
 
-            user.user
-                .as_ref()
-                .ok_or_else(|| Status::not_found("User not found"))?
-                .id
-                .clone()
-        } else {
-            // Already a DB ID → use as is
-            req.user_id.clone()
-        };
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
 
-        let params = PaginationParams::new(req.page, req.limit);
+            // Results follow the request, one per distinct post ID
+            let post_ids = Self::dedupe(req.post_ids.clone());
+            let exists = if req.skip_validation {
+                vec![true; post_ids.len()]
+            } else {
+                self.posts_exist(&post_ids, timeout).await?
+            };
 
-        match self.repository.get_user_likes(&db_user_id, &params).await {
-            Ok(result) => {
-                let likes: Vec<UserLike> = result
-                    .data
-                    .into_iter()
-                    .map(|like| UserLike {
-                        post_id: like.post_id,
-                        liked_at: Some(Self::datetime_to_timestamp(like.liked_at)),
-                    })
-                    .collect();
+            let to_like: Vec<u32> = post_ids
+                .iter()
+                .zip(&exists)
+                .filter(|(_, exists)| **exists)
+                .map(|(post_id, _)| *post_id)
+                .collect();
 
-                Ok(Response::new(GetUserLikesResponse {
-                    likes,
-                    pagination: Some(PaginationInfo {
-                        current_page: result.current_page,
-                        total_pages: result.total_pages,
-                        total_count: result.total_count,
-                        limit: result.limit,
-                    }),
-                }))
+            let outcomes = if to_like.is_empty() {
+                Default::default()
+            } else {
+                self.repository
+                    .create_likes(&db_user_id, &to_like, reaction)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to like posts: {}", e);
+                        Status::from(e)
+                    })?
+            };
+
+            let mut results = Vec::with_capacity(post_ids.len());
+            for (post_id, exists) in post_ids.into_iter().zip(exists) {
+                if !exists {
+                    results.push(LikePostResult {
+                        post_id,
+                        success: false,
+                        message: "Post not found".to_string(),
+                    });
+                    continue;
+                }
+
+                let liked_now = outcomes.get(&post_id).copied().unwrap_or(false);
+                if liked_now {
+                    self.publish_event(&db_user_id, post_id, LikeAction::Liked);
+                }
+                results.push(LikePostResult {
+                    post_id,
+                    success: true,
+                    message: if liked_now {
+                        "Post liked successfully".to_string()
+                    } else {
+                        "Already liked".to_string()
+                    },
+                });
             }
-            Err(e) => {
-                error!("Failed to get user likes: {}", e);
-                println!("Failed to get user likes: {}", e);
-                Err(e.into())
+
+            info!(
+                "Liked posts for user {}: {} of {} succeeded",
+                req.user_id,
+                results.iter().filter(|result| result.success).count(),
+                results.len()
+            );
+
+            Ok(Response::new(LikePostsResponse { results }))
+        })
+        .await
+    }
+
+    async fn unlike_post(
+        &self,
+        request: Request<UnlikePostRequest>,
+    ) -> Result<Response<UnlikePostResponse>, Status> {
+        observe_rpc("unlike_post", async {
+            let _in_flight = self.in_flight.track();
+            authorize(&request, &request.get_ref().user_id, &self.user_ids)?;
+            let req = self.user_ids.apply(request.into_inner());
+            info!(
+                "Unlike post request: user_id={}, post_id={}",
+                req.user_id, req.post_id
+            );
+
+            Self::validate_ids(&req.user_id, &req.post_id)?;
+            self.check_rate_limit(&req.user_id)?;
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self.repository.delete_like(&db_user_id, &req.post_id).await {
+                Ok(deleted) => {
+                    if deleted {
+                        info!(
+                            "Successfully unliked post: user_id={}, post_id={}",
+                            req.user_id, req.post_id
+                        );
+                        self.publish_event(&db_user_id, req.post_id, LikeAction::Unliked);
+                        Ok(Response::new(UnlikePostResponse {
+                            success: true,
+                            message: "Post unliked successfully".to_string(),
+                            ..Default::default()
+                        }))
+                    } else {
+                        Ok(Response::new(UnlikePostResponse {
+                            success: false,
+                            message: "Like not found".to_string(),
+                            ..Default::default()
+                        }))
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to unlike post: {}", e);
+                    println!("Failed to unlike post: {}", e);
+                    Err(e.into())
+                }
             }
-        }
+        })
+        .await
+    }
+
+    async fn get_user_likes(
+        &self,
+        request: Request<GetUserLikesRequest>,
+    ) -> Result<Response<GetUserLikesResponse>, Status> {
+        observe_rpc("get_user_likes", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Get user likes request: user_id={}, page={}, limit={}",
+                req.user_id, req.page, req.limit
+            );
+
+            if req.user_id.trim().is_empty() {
+                return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
+            }
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            Self::validate_pagination(req.page, req.limit)?;
+            let params =
+                PaginationParams::with_bounds(req.page, req.limit, &self.user_likes_bounds);
+            Self::check_page_depth(&params, &self.user_likes_bounds)?;
+
+            let sort = Self::sort_from_proto(req.sort_by(), req.sort_order());
+
+            let range = LikedAtRange {
+                from: req
+                    .from
+                    .map(|ts| Self::timestamp_to_datetime("from", ts))
+                    .transpose()?,
+                to: req
+                    .to
+                    .map(|ts| Self::timestamp_to_datetime("to", ts))
+                    .transpose()?,
+            };
+            if let (Some(from), Some(to)) = (range.from, range.to) {
+                if from > to {
+                    return Err(Self::invalid_field("from", "from must not be after to"));
+                }
+            }
+
+            let mask = LikeFieldMask::from_paths(&req.field_mask);
+
+            match self
+                .repository
+                .get_user_likes(&db_user_id, &params, sort, range)
+                .await
+            {
+                Ok(result) => {
+                    let likes: Vec<UserLike> = result
+                        .data
+                        .into_iter()
+                        .map(|like| UserLike {
+                            post_id: like.post_id,
+                            liked_at: mask
+                                .liked_at
+                                .then(|| Self::datetime_to_timestamp(like.liked_at)),
+                            created_at: mask
+                                .created_at
+                                .then(|| Self::datetime_to_timestamp(like.created_at)),
+                            reaction: if mask.reaction {
+                                Self::reaction_to_proto(like.reaction)
+                            } else {
+                                Reaction::Unspecified as i32
+                            },
+                        })
+                        .collect();
+
+                    Ok(Response::new(GetUserLikesResponse {
+                        likes,
+                        pagination: Some(PaginationInfo {
+                            current_page: result.current_page,
+                            total_pages: result.total_pages,
+                            total_count: result.total_count,
+                            limit: result.limit,
+                            has_next: result.has_next,
+                            has_previous: result.has_previous,
+                            offset: result.offset,
+                        }),
+                    }))
+                }
+                Err(e) => {
+                    error!("Failed to get user likes: {}", e);
+                    println!("Failed to get user likes: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
     }
 
     async fn get_post_likes(
         &self,
         request: Request<GetPostLikesRequest>,
     ) -> Result<Response<GetPostLikesResponse>, Status> {
-        let req = request.into_inner();
-        debug!(
-            "Get post likes request: post_id={}, page={}, limit={}",
-            req.post_id, req.page, req.limit
-        );
+        observe_rpc("get_post_likes", async {
+            let caller = request.extensions().get::<Caller>().cloned();
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Get post likes request: post_id={}, page={}, limit={}",
+                req.post_id, req.page, req.limit
+            );
 
-        if req.post_id <= 0 {
-            return Err(Status::invalid_argument(
-                "Post ID must be a positive integer",
-            ));
-        }
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
+            }
 
-        let params = PaginationParams::new(req.page, req.limit);
+            Self::validate_pagination(req.page, req.limit)?;
+            let params =
+                PaginationParams::with_bounds(req.page, req.limit, &self.post_likes_bounds);
+            Self::check_page_depth(&params, &self.post_likes_bounds)?;
 
-        match self.repository.get_post_likes(&req.post_id, &params).await {
-            Ok(result) => {
-                let likes: Vec<PostLike> = result
-                    .data
-                    .into_iter()
-                    .map(|like| PostLike {
-                        user_id: like.user_id,
+            let sort = Self::sort_from_proto(req.sort_by(), req.sort_order());
+
+            // A count reveals no one, so it needs no ownership check
+            let anonymized = !req.counts_only
+                && self
+                    .hide_likers(caller.as_ref(), req.post_id, &req.viewer_id)
+                    .await?;
+
+            // Count-only callers skip the page query entirely
+            let result = if req.counts_only {
+                self.repository
+                    .get_likes_count(&req.post_id)
+                    .await
+                    .map(|count| PaginatedResult::new(Vec::new(), count, &params))
+            } else {
+                self.repository
+                    .get_post_likes(&req.post_id, &params, sort)
+                    .await
+            };
+
+            let mask = LikeFieldMask::from_paths(&req.field_mask);
+
+            match result {
+                Ok(result) => {
+                    let likes: Vec<PostLike> = result
+                        .data
+                        .into_iter()
+                        .map(|like| PostLike {
+                            user_id: if anonymized {
+                                String::new()
+                            } else {
+                                like.user_id
+                            },
+                            liked_at: mask
+                                .liked_at
+                                .then(|| Self::datetime_to_timestamp(like.liked_at)),
+                            created_at: mask
+                                .created_at
+                                .then(|| Self::datetime_to_timestamp(like.created_at)),
+                            reaction: if mask.reaction {
+                                Self::reaction_to_proto(like.reaction)
+                            } else {
+                                Reaction::Unspecified as i32
+                            },
+                        })
+                        .collect();
+
+                    // One lookup for the whole page, only when asked for
+                    let post = if req.include_post_metadata {
+                        self.post_client
+                            .get_post_metadata(req.post_id, None)
+                            .await
+                            .map(|metadata| PostSummary {
+                                title: metadata.title,
+                                author_id: metadata.author_id,
+                            })
+                    } else {
+                        None
+                    };
+
+                    Ok(Response::new(GetPostLikesResponse {
+                        likes,
+                        pagination: Some(PaginationInfo {
+                            current_page: result.current_page,
+                            total_pages: result.total_pages,
+                            total_count: result.total_count,
+                            limit: result.limit,
+                            has_next: result.has_next,
+                            has_previous: result.has_previous,
+                            offset: result.offset,
+                        }),
+                        post,
+                        anonymized,
+                    }))
+                }
+                Err(e) => {
+                    error!("Failed to get post likes: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    type StreamPostLikesStream = Pin<Box<dyn Stream<Item = Result<PostLike, Status>> + Send>>;
+
+    async fn stream_post_likes(
+        &self,
+        request: Request<StreamPostLikesRequest>,
+    ) -> Result<Response<Self::StreamPostLikesStream>, Status> {
+        observe_rpc("stream_post_likes", async {
+            let caller = request.extensions().get::<Caller>().cloned();
+            let req = request.into_inner();
+            debug!("Stream post likes request: post_id={}", req.post_id);
+
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            // The same privacy as `get_post_likes`; there is no viewer_id, so
+            // without auth every caller is trusted
+            let anonymized = self.hide_likers(caller.as_ref(), req.post_id, "").await?;
+
+            let stream = self
+                .repository
+                .stream_post_likes(req.post_id, STREAM_BATCH_SIZE)
+                .map(move |like| match like {
+                    Ok(like) => Ok(PostLike {
+                        user_id: if anonymized {
+                            String::new()
+                        } else {
+                            like.user_id
+                        },
+                        liked_at: Some(Self::datetime_to_timestamp(like.liked_at)),
+                        created_at: Some(Self::datetime_to_timestamp(like.created_at)),
+                        reaction: Self::reaction_to_proto(like.reaction),
+                    }),
+                    Err(e) => {
+                        error!("Failed to stream post likes: {}", e);
+                        Err(e.into())
+                    }
+                });
+
+            Ok(Response::new(
+                Box::pin(stream) as Self::StreamPostLikesStream
+            ))
+        })
+        .await
+    }
+
+    type WatchPostLikesStream = Pin<Box<dyn Stream<Item = Result<LikeChange, Status>> + Send>>;
+
+    async fn watch_post_likes(
+        &self,
+        request: Request<WatchPostLikesRequest>,
+    ) -> Result<Response<Self::WatchPostLikesStream>, Status> {
+        observe_rpc("watch_post_likes", async {
+            let req = request.into_inner();
+            debug!("Watch post likes request: post_id={}", req.post_id);
+
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            // Ends when the client closes the stream, which drops the subscription
+            let subscription = self.watchers.subscribe(req.post_id);
+            let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+                let watched = subscription.recv().await?;
+                Some((Ok(Self::like_change_to_proto(watched)), subscription))
+            });
+
+            Ok(Response::new(Box::pin(stream) as Self::WatchPostLikesStream))
+        })
+        .await
+    }
+
+    type ExportUserLikesStream = Pin<Box<dyn Stream<Item = Result<ExportedLike, Status>> + Send>>;
+
+    async fn export_user_likes(
+        &self,
+        request: Request<ExportUserLikesRequest>,
+    ) -> Result<Response<Self::ExportUserLikesStream>, Status> {
+        observe_rpc("export_user_likes", async {
+            // Personal data: only the user may export it
+            authorize(&request, &request.get_ref().user_id, &self.user_ids)?;
+            let req = self.user_ids.apply(request.into_inner());
+            info!("Export user likes request: user_id={}", req.user_id);
+
+            if req.user_id.is_empty() {
+                return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
+            }
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            let stream = self
+                .repository
+                .stream_user_likes(&db_user_id, STREAM_BATCH_SIZE)
+                .map(|like| match like {
+                    Ok(like) => Ok(ExportedLike {
+                        post_id: like.post_id,
+                        reaction: Self::reaction_to_proto(like.reaction),
                         liked_at: Some(Self::datetime_to_timestamp(like.liked_at)),
+                        created_at: Some(Self::datetime_to_timestamp(like.created_at)),
+                        updated_at: Some(Self::datetime_to_timestamp(like.updated_at)),
+                        deleted_at: like.deleted_at.map(Self::datetime_to_timestamp),
+                    }),
+                    Err(e) => {
+                        error!("Failed to export user likes: {}", e);
+                        Err(e.into())
+                    }
+                });
+
+            Ok(Response::new(
+                Box::pin(stream) as Self::ExportUserLikesStream
+            ))
+        })
+        .await
+    }
+
+    async fn is_post_liked(
+        &self,
+        request: Request<IsPostLikedRequest>,
+    ) -> Result<Response<IsPostLikedResponse>, Status> {
+        observe_rpc("is_post_liked", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Is post liked request: user_id={}, post_id={}",
+                req.user_id, req.post_id
+            );
+
+            Self::validate_ids(&req.user_id, &req.post_id)?;
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self
+                .repository
+                .is_post_liked(&db_user_id, &req.post_id)
+                .await
+            {
+                Ok(liked_at) => Ok(Response::new(IsPostLikedResponse {
+                    is_liked: liked_at.is_some(),
+                    liked_at: liked_at.map(Self::datetime_to_timestamp),
+                })),
+                Err(e) => {
+                    error!("Failed to check if post is liked: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn has_ever_liked(
+        &self,
+        request: Request<HasEverLikedRequest>,
+    ) -> Result<Response<HasEverLikedResponse>, Status> {
+        observe_rpc("has_ever_liked", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Has ever liked request: user_id={}, post_id={}",
+                req.user_id, req.post_id
+            );
+
+            Self::validate_ids(&req.user_id, &req.post_id)?;
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self
+                .repository
+                .get_like_including_deleted(&db_user_id, &req.post_id)
+                .await
+            {
+                Ok(like) => Ok(Response::new(HasEverLikedResponse {
+                    ever_liked: like.is_some(),
+                    is_active: like.as_ref().is_some_and(|l| l.deleted_at.is_none()),
+                    liked_at: like
+                        .as_ref()
+                        .map(|l| Self::datetime_to_timestamp(l.liked_at)),
+                    unliked_at: like
+                        .and_then(|l| l.deleted_at)
+                        .map(Self::datetime_to_timestamp),
+                })),
+                Err(e) => {
+                    error!("Failed to check if post was ever liked: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn are_posts_liked(
+        &self,
+        request: Request<ArePostsLikedRequest>,
+    ) -> Result<Response<ArePostsLikedResponse>, Status> {
+        observe_rpc("are_posts_liked", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Are posts liked request: user_id={}, posts={}",
+                req.user_id,
+                req.post_ids.len()
+            );
+
+            if req.user_id.trim().is_empty() {
+                return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
+            }
+
+            if req.post_ids.is_empty() {
+                return Err(Self::invalid_field("post_ids", "Post IDs cannot be empty"));
+            }
+
+            if req.post_ids.len() > MAX_BATCH_POST_IDS {
+                return Err(Self::invalid_field(
+                    "post_ids",
+                    format!(
+                        "At most {} post IDs can be checked at once, got {}",
+                        MAX_BATCH_POST_IDS,
+                        req.post_ids.len()
+                    ),
+                ));
+            }
+
+            if let Some(index) = req.post_ids.iter().position(|id| *id == 0) {
+                return Err(Self::invalid_field(
+                    &format!("post_ids[{}]", index),
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            // Resolve the user once for the whole batch
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self
+                .repository
+                .are_posts_liked(&db_user_id, &req.post_ids)
+                .await
+            {
+                Ok(liked) => {
+                    let likes = liked
+                        .into_iter()
+                        .map(|(post_id, liked_at)| {
+                            (
+                                post_id,
+                                LikedState {
+                                    is_liked: liked_at.is_some(),
+                                    liked_at: liked_at.map(Self::datetime_to_timestamp),
+                                },
+                            )
+                        })
+                        .collect();
+
+                    Ok(Response::new(ArePostsLikedResponse { likes }))
+                }
+                Err(e) => {
+                    error!("Failed to check if posts are liked: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn has_user_liked_any(
+        &self,
+        request: Request<HasUserLikedAnyRequest>,
+    ) -> Result<Response<HasUserLikedAnyResponse>, Status> {
+        observe_rpc("has_user_liked_any", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Has user liked any request: user_id={}, posts={}",
+                req.user_id,
+                req.post_ids.len()
+            );
+
+            if req.user_id.trim().is_empty() {
+                return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
+            }
+
+            if req.post_ids.is_empty() {
+                return Err(Self::invalid_field("post_ids", "Post IDs cannot be empty"));
+            }
+
+            if req.post_ids.len() > MAX_BATCH_POST_IDS {
+                return Err(Self::invalid_field(
+                    "post_ids",
+                    format!(
+                        "At most {} post IDs can be checked at once, got {}",
+                        MAX_BATCH_POST_IDS,
+                        req.post_ids.len()
+                    ),
+                ));
+            }
+
+            if let Some(index) = req.post_ids.iter().position(|id| *id == 0) {
+                return Err(Self::invalid_field(
+                    &format!("post_ids[{}]", index),
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self
+                .repository
+                .has_user_liked_any(&db_user_id, &req.post_ids)
+                .await
+            {
+                Ok(has_liked) => Ok(Response::new(HasUserLikedAnyResponse { has_liked })),
+                Err(e) => {
+                    error!("Failed to check if user liked any post: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn which_users_liked(
+        &self,
+        request: Request<WhichUsersLikedRequest>,
+    ) -> Result<Response<WhichUsersLikedResponse>, Status> {
+        observe_rpc("which_users_liked", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Which users liked request: post_id={}, users={}",
+                req.post_id,
+                req.user_ids.len()
+            );
+
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            let user_ids = Self::dedupe(req.user_ids);
+
+            if user_ids.is_empty() {
+                return Err(Self::invalid_field("user_ids", "User IDs cannot be empty"));
+            }
+
+            if user_ids.len() > MAX_BATCH_USER_IDS {
+                return Err(Self::invalid_field(
+                    "user_ids",
+                    format!(
+                        "At most {} user IDs can be checked at once, got {}",
+                        MAX_BATCH_USER_IDS,
+                        user_ids.len()
+                    ),
+                ));
+            }
+
+            if let Some(index) = user_ids.iter().position(|id| id.trim().is_empty()) {
+                return Err(Self::invalid_field(
+                    &format!("user_ids[{}]", index),
+                    "User ID cannot be empty",
+                ));
+            }
+
+            // db_user_ids[i] is the DB ID of user_ids[i]; aliases of one user
+            // share it, so only the distinct IDs are queried
+            let db_user_ids = self.resolve_user_db_ids(&user_ids).await?;
+            let distinct_db_user_ids = Self::dedupe(db_user_ids.clone());
+
+            match self
+                .repository
+                .which_users_liked(&req.post_id, &distinct_db_user_ids)
+                .await
+            {
+                Ok(likers) => {
+                    let user_ids = user_ids
+                        .into_iter()
+                        .zip(db_user_ids)
+                        .filter(|(_, db_user_id)| likers.contains(db_user_id))
+                        .map(|(user_id, _)| user_id)
+                        .collect();
+
+                    Ok(Response::new(WhichUsersLikedResponse { user_ids }))
+                }
+                Err(e) => {
+                    error!("Failed to check which users liked post: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn get_likes_by_users(
+        &self,
+        request: Request<GetLikesByUsersRequest>,
+    ) -> Result<Response<GetLikesByUsersResponse>, Status> {
+        observe_rpc("get_likes_by_users", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Get likes by users request: {} users, page={}, limit={}",
+                req.user_ids.len(),
+                req.page,
+                req.limit
+            );
+
+            let user_ids = Self::dedupe(req.user_ids);
+
+            if user_ids.is_empty() {
+                return Err(Self::invalid_field("user_ids", "User IDs cannot be empty"));
+            }
+
+            if user_ids.len() > MAX_BATCH_USER_IDS {
+                return Err(Self::invalid_field(
+                    "user_ids",
+                    format!(
+                        "At most {} user IDs can be queried at once, got {}",
+                        MAX_BATCH_USER_IDS,
+                        user_ids.len()
+                    ),
+                ));
+            }
+
+            if let Some(index) = user_ids.iter().position(|id| id.trim().is_empty()) {
+                return Err(Self::invalid_field(
+                    &format!("user_ids[{}]", index),
+                    "User ID cannot be empty",
+                ));
+            }
+
+            Self::validate_pagination(req.page, req.limit)?;
+            let bounds = PaginationBounds::default();
+            let params = PaginationParams::with_bounds(req.page, req.limit, &bounds);
+            Self::check_page_depth(&params, &bounds)?;
+
+            // Distinct external IDs can map to the same DB ID
+            let db_user_ids = Self::dedupe(self.resolve_user_db_ids(&user_ids).await?);
+
+            match self
+                .repository
+                .get_likes_by_users(&db_user_ids, &params)
+                .await
+            {
+                Ok(result) => Ok(Response::new(GetLikesByUsersResponse {
+                    likes: result
+                        .data
+                        .into_iter()
+                        .map(|like| UserPostLike {
+                            user_id: like.user_id,
+                            post_id: like.post_id,
+                            liked_at: Some(Self::datetime_to_timestamp(like.liked_at)),
+                        })
+                        .collect(),
+                    pagination: Some(PaginationInfo {
+                        current_page: result.current_page,
+                        total_pages: result.total_pages,
+                        total_count: result.total_count,
+                        limit: result.limit,
+                        has_next: result.has_next,
+                        has_previous: result.has_previous,
+                        offset: result.offset,
+                    }),
+                })),
+                Err(e) => {
+                    error!("Failed to get likes by users: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn get_likes_count(
+        &self,
+        request: Request<GetLikesCountRequest>,
+    ) -> Result<Response<GetLikesCountResponse>, Status> {
+        observe_rpc("get_likes_count", async {
+            let req = request.into_inner();
+            debug!("Get likes count request: post_id={}", req.post_id);
+
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            let stamp = match self.repository.get_likes_count_stamp(&req.post_id).await {
+                Ok(stamp) => stamp,
+                Err(e) => {
+                    error!("Failed to get likes count: {}", e);
+                    return Err(e.into());
+                }
+            };
+
+            let reactions = if req.include_reactions {
+                match self
+                    .repository
+                    .get_likes_count_by_reaction(&req.post_id)
+                    .await
+                {
+                    Ok(counts) => counts,
+                    Err(e) => {
+                        error!("Failed to get per-reaction likes count: {}", e);
+                        return Err(e.into());
+                    }
+                }
+            } else {
+                Vec::new()
+            };
+
+            let etag = stamp.etag(req.post_id, &reactions);
+            if !req.if_none_match.is_empty() && req.if_none_match == etag {
+                return Ok(Response::new(GetLikesCountResponse {
+                    etag,
+                    not_modified: true,
+                    ..Default::default()
+                }));
+            }
+
+            Ok(Response::new(GetLikesCountResponse {
+                count: stamp.count,
+                reactions: reactions
+                    .into_iter()
+                    .map(|c| ReactionCount {
+                        reaction: Self::reaction_to_proto(c.reaction),
+                        count: c.count,
+                    })
+                    .collect(),
+                etag,
+                not_modified: false,
+            }))
+        })
+        .await
+    }
+
+    async fn get_likes_count_since(
+        &self,
+        request: Request<GetLikesCountSinceRequest>,
+    ) -> Result<Response<GetLikesCountSinceResponse>, Status> {
+        observe_rpc("get_likes_count_since", async {
+            let req = request.into_inner();
+            debug!(
+                "Get likes count since request: post_id={}, since={:?}",
+                req.post_id, req.since
+            );
+
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            let now = self.clock.now();
+            let since = match req.since {
+                Some(since) => Self::timestamp_to_datetime("since", since)?,
+                None => now - DEFAULT_LIKES_COUNT_SINCE,
+            };
+            if since > now {
+                return Err(Self::invalid_field(
+                    "since",
+                    "Since cannot be in the future",
+                ));
+            }
+
+            match self
+                .repository
+                .get_likes_count_since(&req.post_id, since)
+                .await
+            {
+                Ok(count) => Ok(Response::new(GetLikesCountSinceResponse {
+                    count,
+                    since: Some(Self::datetime_to_timestamp(since)),
+                })),
+                Err(e) => {
+                    error!("Failed to get likes count since {}: {}", since, e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn get_likes_histogram(
+        &self,
+        request: Request<GetLikesHistogramRequest>,
+    ) -> Result<Response<GetLikesHistogramResponse>, Status> {
+        observe_rpc("get_likes_histogram", async {
+            let req = request.into_inner();
+            debug!(
+                "Get likes histogram request: post_id={}, from={:?}, to={:?}, bucket={:?}",
+                req.post_id,
+                req.from,
+                req.to,
+                req.bucket()
+            );
+
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            let width = match req.bucket() {
+                HistogramBucket::Unspecified | HistogramBucket::Day => BucketWidth::Day,
+                HistogramBucket::Hour => BucketWidth::Hour,
+            };
+            let to = match req.to {
+                Some(to) => Self::timestamp_to_datetime("to", to)?,
+                None => self.clock.now(),
+            };
+            let from = match req.from {
+                Some(from) => Self::timestamp_to_datetime("from", from)?,
+                None => to - width.duration() * DEFAULT_HISTOGRAM_BUCKETS,
+            };
+            let from = width.floor(from);
+
+            if from >= to {
+                return Err(Self::invalid_field("from", "from must be before to"));
+            }
+            let width_seconds = width.duration().num_seconds();
+            let buckets = ((to - from).num_seconds() + width_seconds - 1) / width_seconds;
+            if buckets > MAX_HISTOGRAM_BUCKETS {
+                return Err(Self::invalid_field(
+                    "from",
+                    format!(
+                        "Range covers {} buckets, at most {} are allowed",
+                        buckets, MAX_HISTOGRAM_BUCKETS
+                    ),
+                ));
+            }
+
+            let counts = self
+                .repository
+                .get_likes_histogram(&req.post_id, from, to, width)
+                .await
+                .map_err(|e| {
+                    error!("Failed to get likes histogram: {}", e);
+                    Status::from(e)
+                })?;
+
+            // Dense series: every bucket in the range, zero when it has no likes
+            let counts: HashMap<_, _> = counts
+                .into_iter()
+                .map(|bucket| (bucket.bucket_start, bucket.count))
+                .collect();
+            let buckets =
+                std::iter::successors(Some(from), |start| Some(*start + width.duration()))
+                    .take_while(|start| *start < to)
+                    .map(|start| LikesHistogramBucket {
+                        bucket_start: Some(Self::datetime_to_timestamp(start)),
+                        count: counts.get(&start).copied().unwrap_or(0),
                     })
                     .collect();
 
-                Ok(Response::new(GetPostLikesResponse {
-                    likes,
+            Ok(Response::new(GetLikesHistogramResponse { buckets }))
+        })
+        .await
+    }
+
+    async fn get_user_likes_count(
+        &self,
+        request: Request<GetUserLikesCountRequest>,
+    ) -> Result<Response<GetUserLikesCountResponse>, Status> {
+        observe_rpc("get_user_likes_count", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!("Get user likes count request: user_id={}", req.user_id);
+
+            if req.user_id.trim().is_empty() {
+                return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
+            }
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self.repository.get_user_likes_count(&db_user_id).await {
+                Ok(count) => Ok(Response::new(GetUserLikesCountResponse { count })),
+                Err(e) => {
+                    error!("Failed to get user likes count: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn get_likes_counts(
+        &self,
+        request: Request<GetLikesCountsRequest>,
+    ) -> Result<Response<GetLikesCountsResponse>, Status> {
+        observe_rpc("get_likes_counts", async {
+            let req = request.into_inner();
+            debug!("Get likes counts request: posts={}", req.post_ids.len());
+
+            if req.post_ids.is_empty() {
+                return Err(Self::invalid_field("post_ids", "Post IDs cannot be empty"));
+            }
+
+            if req.post_ids.len() > MAX_BATCH_POST_IDS {
+                return Err(Self::invalid_field(
+                    "post_ids",
+                    format!(
+                        "At most {} post IDs can be counted at once, got {}",
+                        MAX_BATCH_POST_IDS,
+                        req.post_ids.len()
+                    ),
+                ));
+            }
+
+            if let Some(index) = req.post_ids.iter().position(|id| *id == 0) {
+                return Err(Self::invalid_field(
+                    &format!("post_ids[{}]", index),
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            match self.repository.get_likes_counts(&req.post_ids).await {
+                Ok(counts) => Ok(Response::new(GetLikesCountsResponse { counts })),
+                Err(e) => {
+                    error!("Failed to get likes counts: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn get_most_liked_posts(
+        &self,
+        request: Request<GetMostLikedPostsRequest>,
+    ) -> Result<Response<GetMostLikedPostsResponse>, Status> {
+        observe_rpc("get_most_liked_posts", async {
+            let req = request.into_inner();
+            debug!(
+                "Get most liked posts request: page={}, limit={}, since={:?}, time_window={:?}",
+                req.page,
+                req.limit,
+                req.since,
+                req.time_window()
+            );
+
+            let window_start = self.window_start(req.time_window());
+            if req.since.is_some() && window_start.is_some() {
+                return Err(Self::invalid_field(
+                    "time_window",
+                    "Set either since or time_window, not both",
+                ));
+            }
+            let since = match req.since {
+                Some(since) => Some(Self::timestamp_to_datetime("since", since)?),
+                None => window_start,
+            };
+
+            let bounds = PaginationBounds {
+                default_limit: DEFAULT_RANKING_LIMIT,
+                max_limit: MAX_RANKING_LIMIT,
+                ..PaginationBounds::default()
+            };
+            let params = PaginationParams::with_bounds(req.page, req.limit, &bounds);
+            Self::check_page_depth(&params, &bounds)?;
+
+            match self.repository.get_most_liked_posts(&params, since).await {
+                Ok(result) => Ok(Response::new(GetMostLikedPostsResponse {
+                    posts: result
+                        .data
+                        .into_iter()
+                        .zip(1..)
+                        .map(|(row, position)| PostLikeCount {
+                            post_id: row.post_id,
+                            count: row.count,
+                            rank: result.offset.saturating_add(position),
+                        })
+                        .collect(),
                     pagination: Some(PaginationInfo {
                         current_page: result.current_page,
                         total_pages: result.total_pages,
                         total_count: result.total_count,
                         limit: result.limit,
+                        has_next: result.has_next,
+                        has_previous: result.has_previous,
+                        offset: result.offset,
                     }),
-                }))
+                })),
+                Err(e) => {
+                    error!("Failed to get most liked posts: {}", e);
+                    Err(e.into())
+                }
             }
-            Err(e) => {
-                error!("Failed to get post likes: {}", e);
-                Err(e.into())
+        })
+        .await
+    }
+
+    async fn get_mutual_likers(
+        &self,
+        request: Request<GetMutualLikersRequest>,
+    ) -> Result<Response<GetMutualLikersResponse>, Status> {
+        observe_rpc("get_mutual_likers", async {
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Get mutual likers request: post_id={}, user_id={}, limit={}",
+                req.post_id, req.user_id, req.limit
+            );
+
+            Self::validate_ids(&req.user_id, &req.post_id)?;
+
+            let limit = if req.limit < 1 {
+                DEFAULT_RANKING_LIMIT
+            } else {
+                req.limit.min(MAX_RANKING_LIMIT)
+            };
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self
+                .repository
+                .get_mutual_likers(&req.post_id, &db_user_id, limit)
+                .await
+            {
+                Ok(user_ids) => Ok(Response::new(GetMutualLikersResponse { user_ids })),
+                Err(e) => {
+                    error!("Failed to get mutual likers: {}", e);
+                    Err(e.into())
+                }
             }
-        }
+        })
+        .await
     }
 
-    async fn is_post_liked(
+    async fn get_recent_likers(
         &self,
-        request: Request<IsPostLikedRequest>,
-    ) -> Result<Response<IsPostLikedResponse>, Status> {
-        let req = request.into_inner();
-        debug!(
-            "Is post liked request: user_id={}, post_id={}",
-            req.user_id, req.post_id
-        );
+        request: Request<GetRecentLikersRequest>,
+    ) -> Result<Response<GetRecentLikersResponse>, Status> {
+        observe_rpc("get_recent_likers", async {
+            let req = request.into_inner();
+            debug!(
+                "Get recent likers request: post_id={}, since_seconds={}, limit={}",
+                req.post_id, req.since_seconds, req.limit
+            );
 
-        let mut user_client = self.user_client.clone();
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            if req.since_seconds <= 0 || req.since_seconds > MAX_RECENT_LIKERS_WINDOW_SECONDS {
+                return Err(Self::invalid_field(
+                    "since_seconds",
+                    format!(
+                        "Window must be between 1 and {} seconds",
+                        MAX_RECENT_LIKERS_WINDOW_SECONDS
+                    ),
+                ));
+            }
 
-        Self::validate_ids(&req.user_id, &req.post_id)?;
+            let limit = if req.limit < 1 {
+                DEFAULT_RANKING_LIMIT
+            } else {
+                req.limit.min(MAX_RANKING_LIMIT)
+            };
+            let since = self.clock.now() - chrono::Duration::seconds(req.since_seconds);
 
-        let db_user_id = if req.user_id.starts_with("user_") {
-            // Clerk ID → fetch actual DB ID
-            let user = user_client
-                .get_user(req.user_id.clone())
+            match self
+                .repository
+                .get_recent_likers(&req.post_id, since, limit)
                 .await
-                .map_err(|e| Status::internal(format!("Failed to get user details: {}", e)))?;
+            {
+                Ok(likes) => Ok(Response::new(GetRecentLikersResponse {
+                    likes: likes
+                        .into_iter()
+                        .map(|like| PostLike {
+                            user_id: like.user_id,
+                            liked_at: Some(Self::datetime_to_timestamp(like.liked_at)),
+                            created_at: Some(Self::datetime_to_timestamp(like.created_at)),
+                            reaction: Self::reaction_to_proto(like.reaction),
+                        })
+                        .collect(),
+                })),
+                Err(e) => {
+                    error!("Failed to get recent likers: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
 
-            user.user
-                .as_ref()
-                .ok_or_else(|| Status::not_found("User not found"))?
-                .id
-                .clone()
-        } else {
-            // Already a DB ID → use as is
-            req.user_id.clone()
-        };
+    async fn get_first_liker(
+        &self,
+        request: Request<GetFirstLikerRequest>,
+    ) -> Result<Response<GetFirstLikerResponse>, Status> {
+        observe_rpc("get_first_liker", async {
+            let req = request.into_inner();
+            debug!("Get first liker request: post_id={}", req.post_id);
 
-        match self
-            .repository
-            .is_post_liked(&db_user_id, &req.post_id)
-            .await
-        {
-            Ok(liked_at) => Ok(Response::new(IsPostLikedResponse {
-                is_liked: liked_at.is_some(),
-                liked_at: liked_at.map(Self::datetime_to_timestamp),
-            })),
-            Err(e) => {
-                error!("Failed to check if post is liked: {}", e);
-                Err(e.into())
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
+                    "Post ID must be a positive integer",
+                ));
             }
-        }
+
+            match self.repository.get_first_liker(&req.post_id).await {
+                Ok(Some((user_id, liked_at))) => Ok(Response::new(GetFirstLikerResponse {
+                    user_id,
+                    liked_at: Some(Self::datetime_to_timestamp(liked_at)),
+                })),
+                Ok(None) => Err(Status::not_found("Post has no likes")),
+                Err(e) => {
+                    error!("Failed to get first liker: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
     }
 
-    async fn get_likes_count(
+    async fn get_activity_feed(
         &self,
-        request: Request<GetLikesCountRequest>,
-    ) -> Result<Response<GetLikesCountResponse>, Status> {
-        let req = request.into_inner();
-        debug!("Get likes count request: post_id={}", req.post_id);
+        request: Request<GetActivityFeedRequest>,
+    ) -> Result<Response<GetActivityFeedResponse>, Status> {
+        observe_rpc("get_activity_feed", async {
+            let req = request.into_inner();
+            debug!(
+                "Get activity feed request: posts={}, limit={}",
+                req.post_ids.len(),
+                req.limit
+            );
 
-        if req.post_id <= 0 {
-            return Err(Status::invalid_argument(
-                "Post ID must be a positive integer",
-            ));
-        }
+            let post_ids = Self::dedupe(req.post_ids);
 
-        match self.repository.get_likes_count(&req.post_id).await {
-            Ok(count) => Ok(Response::new(GetLikesCountResponse { count })),
-            Err(e) => {
-                error!("Failed to get likes count: {}", e);
-                Err(e.into())
+            if post_ids.is_empty() {
+                return Err(Self::invalid_field("post_ids", "Post IDs cannot be empty"));
             }
-        }
+
+            if post_ids.len() > MAX_BATCH_POST_IDS {
+                return Err(Self::invalid_field(
+                    "post_ids",
+                    format!(
+                        "At most {} post IDs can be followed at once, got {}",
+                        MAX_BATCH_POST_IDS,
+                        post_ids.len()
+                    ),
+                ));
+            }
+
+            if let Some(index) = post_ids.iter().position(|id| *id == 0) {
+                return Err(Self::invalid_field(
+                    &format!("post_ids[{}]", index),
+                    "Post ID must be a positive integer",
+                ));
+            }
+
+            let after = if req.page_token.is_empty() {
+                None
+            } else {
+                Some(
+                    FeedCursor::decode(&req.page_token)
+                        .ok_or_else(|| Self::invalid_field("page_token", "Invalid page token"))?,
+                )
+            };
+
+            let limit = if req.limit < 1 {
+                DEFAULT_RANKING_LIMIT
+            } else {
+                req.limit.min(MAX_RANKING_LIMIT)
+            };
+
+            // One extra row tells whether another page follows
+            let mut likes = match self
+                .repository
+                .get_activity_feed(&post_ids, after.as_ref(), limit + 1)
+                .await
+            {
+                Ok(likes) => likes,
+                Err(e) => {
+                    error!("Failed to get activity feed: {}", e);
+                    return Err(e.into());
+                }
+            };
+
+            let next_page_token = if likes.len() > limit as usize {
+                likes.truncate(limit as usize);
+                likes
+                    .last()
+                    .and_then(FeedCursor::from_like)
+                    .map(|cursor| cursor.encode())
+                    .unwrap_or_default()
+            } else {
+                String::new()
+            };
+
+            Ok(Response::new(GetActivityFeedResponse {
+                entries: likes
+                    .into_iter()
+                    .map(|like| ActivityFeedEntry {
+                        post_id: like.post_id,
+                        user_id: like.user_id,
+                        liked_at: Some(Self::datetime_to_timestamp(like.liked_at)),
+                        reaction: Self::reaction_to_proto(like.reaction),
+                    })
+                    .collect(),
+                next_page_token,
+            }))
+        })
+        .await
+    }
+
+    async fn get_recent_liked_posts(
+        &self,
+        request: Request<GetRecentLikedPostsRequest>,
+    ) -> Result<Response<GetRecentLikedPostsResponse>, Status> {
+        observe_rpc("get_recent_liked_posts", async {
+            let deadline = Deadline::from_metadata(request.metadata());
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Get recent liked posts request: user_id={}, limit={}",
+                req.user_id, req.limit
+            );
+
+            if req.user_id.trim().is_empty() {
+                return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
+            }
+
+            let limit = if req.limit < 1 {
+                DEFAULT_RECENT_LIKED_POSTS
+            } else {
+                req.limit.min(MAX_RECENT_LIKED_POSTS)
+            };
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+            let sort = LikeSort {
+                field: LikeSortField::LikedAt,
+                direction: SortDirection::Desc,
+            };
+            let likes = self
+                .repository
+                .get_user_likes(
+                    &db_user_id,
+                    &PaginationParams::new(1, limit),
+                    sort,
+                    LikedAtRange::default(),
+                )
+                .await
+                .map_err(|e| {
+                    error!("Failed to get recent likes: {}", e);
+                    Status::from(e)
+                })?
+                .data;
+
+            let post_ids = likes.iter().map(|like| like.post_id).collect();
+            let posts = self
+                .post_client
+                .get_posts_batch(post_ids, deadline.remaining())
+                .await;
+
+            // A post that is gone keeps its place, flagged deleted
+            let posts = likes
+                .into_iter()
+                .zip(posts)
+                .map(|(like, post)| {
+                    let liked_at = Some(Self::datetime_to_timestamp(like.liked_at));
+                    match post.map(PostMetadata::from) {
+                        Some(metadata) => RecentLikedPost {
+                            post_id: like.post_id,
+                            title: metadata.title,
+                            content_preview: metadata.content_preview,
+                            liked_at,
+                            deleted: false,
+                        },
+                        None => RecentLikedPost {
+                            post_id: like.post_id,
+                            liked_at,
+                            deleted: true,
+                            ..Default::default()
+                        },
+                    }
+                })
+                .collect();
+
+            Ok(Response::new(GetRecentLikedPostsResponse { posts }))
+        })
+        .await
+    }
+
+    async fn get_likes_between_users(
+        &self,
+        request: Request<GetLikesBetweenUsersRequest>,
+    ) -> Result<Response<GetLikesBetweenUsersResponse>, Status> {
+        observe_rpc("get_likes_between_users", async {
+            let deadline = Deadline::from_metadata(request.metadata());
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Get likes between users request: liker_id={}, author_id={}",
+                req.liker_id, req.author_id
+            );
+
+            if req.liker_id.trim().is_empty() {
+                return Err(Self::invalid_field("liker_id", "Liker ID cannot be empty"));
+            }
+            if req.author_id.trim().is_empty() {
+                return Err(Self::invalid_field(
+                    "author_id",
+                    "Author ID cannot be empty",
+                ));
+            }
+
+            let db_liker_id = self.resolve_user_db_id(&req.liker_id).await?;
+            let db_author_id = self.resolve_user_db_id(&req.author_id).await?;
+
+            // One extra row tells whether the cap cut the liker's likes short
+            let mut post_ids = self
+                .repository
+                .get_user_liked_post_ids(&db_liker_id, MAX_RECIPROCITY_POSTS + 1)
+                .await
+                .map_err(|e| {
+                    error!("Failed to get liked post IDs: {}", e);
+                    Status::from(e)
+                })?;
+            let truncated = post_ids.len() > MAX_RECIPROCITY_POSTS as usize;
+            post_ids.truncate(MAX_RECIPROCITY_POSTS as usize);
+
+            // N lookups: the likes table does not record who authored a post
+            let posts = self
+                .post_client
+                .get_posts_batch(post_ids, deadline.remaining())
+                .await;
+
+            let authored: Vec<u32> = posts
+                .into_iter()
+                .flatten()
+                .filter(|post| post.user_id == db_author_id || post.user_id == req.author_id)
+                .map(|post| post.id)
+                .collect();
+
+            Ok(Response::new(GetLikesBetweenUsersResponse {
+                count: authored.len() as i64,
+                post_ids: authored,
+                truncated,
+            }))
+        })
+        .await
     }
 
     async fn unlike_posts(
         &self,
         request: Request<UnlikePostsRequest>,
     ) -> Result<Response<UnlikePostResponse>, Status> {
-        let req = request.into_inner();
-        debug!(
-            "Unlike posts request for {} users and {} posts",
-            req.user_ids.len(),
-            req.post_ids.len()
-        );
+        observe_rpc("unlike_posts", async {
+            let _in_flight = self.in_flight.track();
+            let mut req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Unlike posts request for {} users and {} posts",
+                req.user_ids.len(),
+                req.post_ids.len()
+            );
 
-        let mut user_client = self.user_client.clone();
+            // Repeated IDs would only cost extra lookups
+            req.user_ids = Self::dedupe(req.user_ids);
+            req.post_ids = Self::dedupe(req.post_ids);
 
-        if req.user_ids.is_empty() && req.post_ids.is_empty() {
-            return Err(Status::invalid_argument(
-                "User IDs and Post IDs cannot be empty",
-            ));
-        }
+            if req.user_ids.is_empty() && req.post_ids.is_empty() {
+                let mut details = ErrorDetails::new();
+                details
+                    .add_bad_request_violation(
+                        "user_ids",
+                        "User IDs and Post IDs cannot both be empty",
+                    )
+                    .add_bad_request_violation(
+                        "post_ids",
+                        "User IDs and Post IDs cannot both be empty",
+                    );
+                return Err(Status::with_error_details(
+                    Code::InvalidArgument,
+                    "User IDs and Post IDs cannot be empty",
+                    details,
+                ));
+            }
 
-        // Validate user IDs
-        for user_id in &req.user_ids {
-            if user_id.trim().is_empty() {
-                return Err(Status::invalid_argument("User ID cannot be empty"));
+            if req.user_ids.len() > MAX_BATCH_USER_IDS {
+                return Err(Self::invalid_field(
+                    "user_ids",
+                    format!(
+                        "At most {} user IDs can be unliked at once, got {}",
+                        MAX_BATCH_USER_IDS,
+                        req.user_ids.len()
+                    ),
+                ));
             }
-        }
 
-        let mut db_user_ids = Vec::with_capacity(req.user_ids.len());
-        if !req.user_ids.is_empty() {
-            for external_user_id in &req.user_ids {
-                let db_user_id = if external_user_id.starts_with("user_") {
-                    // Clerk ID → fetch DB ID
-                    let user_resp = user_client
-                        .get_user(external_user_id.clone())
-                        .await
-                        .map_err(|e| {
-                            Status::internal(format!("Failed to get user details: {}", e))
-                        })?;
-
-                    user_resp
-                        .user
-                        .as_ref()
-                        .ok_or_else(|| Status::not_found("User not found"))?
-                        .id
-                        .clone()
-                } else {
-                    // Already a DB ID → use as is
-                    external_user_id.clone()
+            if req.post_ids.len() > MAX_BATCH_POST_IDS {
+                return Err(Self::invalid_field(
+                    "post_ids",
+                    format!(
+                        "At most {} post IDs can be unliked at once, got {}",
+                        MAX_BATCH_POST_IDS,
+                        req.post_ids.len()
+                    ),
+                ));
+            }
+
+            // Validate user IDs
+            for (index, user_id) in req.user_ids.iter().enumerate() {
+                if user_id.trim().is_empty() {
+                    return Err(Self::invalid_field(
+                        &format!("user_ids[{}]", index),
+                        "User ID cannot be empty",
+                    ));
+                }
+            }
+
+            // Validate post IDs
+            for (index, post_id) in req.post_ids.iter().enumerate() {
+                if *post_id == 0 {
+                    return Err(Self::invalid_field(
+                        &format!("post_ids[{}]", index),
+                        "Post ID must be a positive integer",
+                    ));
+                }
+            }
+
+            // Distinct external IDs can map to the same DB ID
+            let db_user_ids = Self::dedupe(self.resolve_user_db_ids(&req.user_ids).await?);
+
+            if req.dry_run {
+                return match self
+                    .repository
+                    .count_matching_likes(&db_user_ids, &req.post_ids)
+                    .await
+                {
+                    Ok(matched_count) => Ok(Response::new(UnlikePostResponse {
+                        success: true,
+                        message: format!("Dry run: {} likes would be unliked", matched_count),
+                        matched_count,
+                    })),
+                    Err(e) => {
+                        error!("Failed to count likes to unlike: {}", e);
+                        Err(e.into())
+                    }
                 };
+            }
 
-                db_user_ids.push(db_user_id);
+            match self
+                .repository
+                .unlike_posts(&db_user_ids, &req.post_ids)
+                .await
+            {
+                Ok(deleted) => Ok(Response::new(UnlikePostResponse {
+                    success: deleted,
+                    message: if deleted {
+                        "Posts unliked successfully".to_string()
+                    } else {
+                        "No likes found to unlike".to_string()
+                    },
+                    ..Default::default()
+                })),
+                Err(e) => {
+                    error!("Failed to unlike posts: {}", e);
+                    println!("Failed to unlike posts: {}", e);
+                    Err(e.into())
+                }
             }
-        }
+        })
+        .await
+    }
+
+    async fn delete_post_likes(
+        &self,
+        request: Request<DeletePostLikesRequest>,
+    ) -> Result<Response<DeletePostLikesResponse>, Status> {
+        observe_rpc("delete_post_likes", async {
+            let _in_flight = self.in_flight.track();
+            let req = request.into_inner();
+            debug!("Delete post likes request: post_id={}", req.post_id);
 
-        // Validate post IDs
-        for post_id in &req.post_ids {
-            if *post_id <= 0 {
-                return Err(Status::invalid_argument(
+            if req.post_id == 0 {
+                return Err(Self::invalid_field(
+                    "post_id",
                     "Post ID must be a positive integer",
                 ));
             }
-        }
 
-        match self
-            .repository
-            .unlike_posts(&db_user_ids, &req.post_ids)
-            .await
-        {
-            Ok(deleted) => Ok(Response::new(UnlikePostResponse {
-                success: deleted,
-                message: if deleted {
-                    "Posts unliked successfully".to_string()
-                } else {
-                    "No likes found to unlike".to_string()
-                },
-            })),
-            Err(e) => {
-                error!("Failed to unlike posts: {}", e);
-                println!("Failed to unlike posts: {}", e);
-                Err(e.into())
+            match self.repository.delete_post_likes(&req.post_id).await {
+                Ok(deleted) => {
+                    info!("Deleted {} likes on post {}", deleted, req.post_id);
+                    Ok(Response::new(DeletePostLikesResponse {
+                        deleted_count: deleted as i64,
+                    }))
+                }
+                Err(e) => {
+                    error!("Failed to delete post likes: {}", e);
+                    Err(e.into())
+                }
             }
-        }
+        })
+        .await
+    }
+
+    async fn delete_user_likes(
+        &self,
+        request: Request<DeleteUserLikesRequest>,
+    ) -> Result<Response<DeleteUserLikesResponse>, Status> {
+        observe_rpc("delete_user_likes", async {
+            let _in_flight = self.in_flight.track();
+            let req = self.user_ids.apply(request.into_inner());
+            debug!("Delete user likes request: user_id={}", req.user_id);
+
+            if req.user_id.trim().is_empty() {
+                return Err(Self::invalid_field("user_id", "User ID cannot be empty"));
+            }
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self.repository.delete_user_likes(&db_user_id).await {
+                Ok(deleted) => {
+                    // Audit record of the erasure
+                    info!(
+                        "Erased {} likes for user {} (requested as {})",
+                        deleted, db_user_id, req.user_id
+                    );
+                    self.user_client.invalidate_user(&req.user_id).await;
+                    self.invalidate_user_id(&req.user_id).await;
+
+                    Ok(Response::new(DeleteUserLikesResponse {
+                        deleted_count: deleted as i64,
+                    }))
+                }
+                Err(e) => {
+                    error!("Failed to delete user likes: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn merge_user_likes(
+        &self,
+        request: Request<MergeUserLikesRequest>,
+    ) -> Result<Response<MergeUserLikesResponse>, Status> {
+        observe_rpc("merge_user_likes", async {
+            let _in_flight = self.in_flight.track();
+            let req = self.user_ids.apply(request.into_inner());
+            debug!(
+                "Merge user likes request: from_user_id={}, to_user_id={}, delete_source={}",
+                req.from_user_id, req.to_user_id, req.delete_source
+            );
+
+            if req.from_user_id.trim().is_empty() {
+                return Err(Self::invalid_field(
+                    "from_user_id",
+                    "Source user ID cannot be empty",
+                ));
+            }
+            if req.to_user_id.trim().is_empty() {
+                return Err(Self::invalid_field(
+                    "to_user_id",
+                    "Target user ID cannot be empty",
+                ));
+            }
+
+            let from_db_id = self.resolve_user_db_id(&req.from_user_id).await?;
+            let to_db_id = self.resolve_user_db_id(&req.to_user_id).await?;
+            if from_db_id == to_db_id {
+                return Err(Self::invalid_field(
+                    "to_user_id",
+                    "Source and target are the same user",
+                ));
+            }
+
+            match self
+                .repository
+                .merge_user_likes(&from_db_id, &to_db_id, req.delete_source)
+                .await
+            {
+                Ok(merge) => {
+                    // Audit record of the merge
+                    info!(
+                        "Merged likes of user {} into user {}: {} moved, {} skipped (removed: {})",
+                        from_db_id, to_db_id, merge.moved, merge.skipped, req.delete_source
+                    );
+
+                    Ok(Response::new(MergeUserLikesResponse {
+                        moved_count: merge.moved as i64,
+                        skipped_count: merge.skipped as i64,
+                    }))
+                }
+                Err(e) => {
+                    error!("Failed to merge user likes: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn verify_schema(
+        &self,
+        request: Request<VerifySchemaRequest>,
+    ) -> Result<Response<VerifySchemaResponse>, Status> {
+        observe_rpc("verify_schema", async {
+            let req = request.into_inner();
+            debug!("Verify schema request: repair={}", req.repair);
+
+            match self.repository.verify_schema(req.repair).await {
+                Ok(report) => {
+                    if !report.repaired.is_empty() {
+                        info!("Repaired schema definitions: {:?}", report.repaired);
+                    }
+                    if !report.is_in_sync() {
+                        warn!("Schema drift on likes: {:?}", report);
+                    }
+
+                    Ok(Response::new(VerifySchemaResponse {
+                        in_sync: report.is_in_sync(),
+                        missing_fields: report.missing_fields,
+                        extra_fields: report.extra_fields,
+                        missing_indexes: report.missing_indexes,
+                        extra_indexes: report.extra_indexes,
+                        repaired: report.repaired,
+                    }))
+                }
+                Err(e) => {
+                    error!("Failed to verify schema: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
+    }
+
+    async fn toggle_like(
+        &self,
+        request: Request<ToggleLikeRequest>,
+    ) -> Result<Response<ToggleLikeResponse>, Status> {
+        observe_rpc("toggle_like", async {
+            let _in_flight = self.in_flight.track();
+            authorize(&request, &request.get_ref().user_id, &self.user_ids)?;
+            let deadline = Deadline::from_metadata(request.metadata());
+            let req = self.user_ids.apply(request.into_inner());
+            info!(
+                "Toggle like request: user_id={}, post_id={}",
+                req.user_id, req.post_id
+            );
+
+            Self::validate_ids(&req.user_id, &req.post_id)?;
+            self.check_rate_limit(&req.user_id)?;
+
+            let timeout = deadline.remaining();
+
+            // A toggle may create a like, so validate the same way like_post does
+            if !self
+                .user_client
+                .user_exists(&req.user_id, timeout)
+                .await
+                .map_err(|e| Self::client_error("User validation failed", e))?
+            {
+                return Ok(Response::new(ToggleLikeResponse {
+                    success: false,
+                    message: "User not found".to_string(),
+                    is_liked: false,
+                    liked_at: None,
+                }));
+            }
+
+            if !self
+                .post_client
+                .post_exists(req.post_id, timeout)
+                .await
+                .map_err(|e| Self::client_error("Post validation failed", e))?
+            {
+                return Ok(Response::new(ToggleLikeResponse {
+                    success: false,
+                    message: "Post not found".to_string(),
+                    is_liked: false,
+                    liked_at: None,
+                }));
+            }
+
+            let db_user_id = self.resolve_user_db_id(&req.user_id).await?;
+
+            match self.repository.toggle_like(&db_user_id, &req.post_id).await {
+                Ok(Some(liked_at)) => {
+                    info!(
+                        "Toggled like on: user_id={}, post_id={}",
+                        req.user_id, req.post_id
+                    );
+                    self.publish_event(&db_user_id, req.post_id, LikeAction::Liked);
+                    Ok(Response::new(ToggleLikeResponse {
+                        success: true,
+                        message: "Post liked successfully".to_string(),
+                        is_liked: true,
+                        liked_at: Some(Self::datetime_to_timestamp(liked_at)),
+                    }))
+                }
+                Ok(None) => {
+                    info!(
+                        "Toggled like off: user_id={}, post_id={}",
+                        req.user_id, req.post_id
+                    );
+                    self.publish_event(&db_user_id, req.post_id, LikeAction::Unliked);
+                    Ok(Response::new(ToggleLikeResponse {
+                        success: true,
+                        message: "Post unliked successfully".to_string(),
+                        is_liked: false,
+                        liked_at: None,
+                    }))
+                }
+                Err(e) => {
+                    error!("Failed to toggle like: {}", e);
+                    Err(e.into())
+                }
+            }
+        })
+        .await
     }
 
     async fn health_check(
         &self,
         _request: Request<HealthCheckRequest>,
     ) -> Result<Response<HealthCheckResponse>, Status> {
-        debug!("Health check request");
+        observe_rpc("health_check", async {
+            debug!("Health check request");
 
-        match self.repository.health_check().await {
-            Ok(_) => Ok(Response::new(HealthCheckResponse {
-                status: "healthy".to_string(),
-                timestamp: Some(Self::datetime_to_timestamp(chrono::Utc::now())),
-            })),
-            Err(e) => {
-                error!("Health check failed: {}", e);
-                Err(Status::internal("Service unhealthy"))
+            let dependencies = self.check_dependencies(DEPENDENCY_HEALTH_TIMEOUT).await;
+            if !dependencies.database {
+                return Err(Status::internal("Service unhealthy"));
             }
-        }
+
+            let status = if dependencies.all_healthy() {
+                "healthy"
+            } else {
+                warn!(
+                    "Health check degraded: user_service={}, post_service={}",
+                    dependencies.user_service, dependencies.post_service
+                );
+                "degraded"
+            };
+
+            Ok(Response::new(HealthCheckResponse {
+                status: status.to_string(),
+                timestamp: Some(Self::datetime_to_timestamp(self.clock.now())),
+                dependencies: dependencies
+                    .entries()
+                    .into_iter()
+                    .map(|(name, healthy)| DependencyHealth {
+                        name: name.to_string(),
+                        healthy,
+                    })
+                    .collect(),
+            }))
+        })
+        .await
     }
 }
@@ -0,0 +1,109 @@
+use crate::service::{BoxResponseFuture, is_health_check};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tonic::{
+    Status,
+    codegen::http::{Request, Response},
+};
+use tower::{Layer, Service};
+use tracing::warn;
+
+/// Tower layer giving each RPC a latency budget, looked up by method name
+/// (e.g. `GetLikesCount`), else the default. A handler still running when its
+/// budget runs out is cancelled and the call answered `DEADLINE_EXCEEDED`.
+/// The budget covers producing the response: a server stream only has to
+/// start within it, and may take as long as it needs to drain.
+#[derive(Debug, Clone, Default)]
+pub struct MethodTimeoutLayer {
+    default: Option<Duration>,
+    // None exempts the method from the default
+    methods: Arc<HashMap<String, Option<Duration>>>,
+}
+
+impl MethodTimeoutLayer {
+    /// Time out methods without a budget of their own after `default`, if any
+    pub fn new(default: Option<Duration>) -> Self {
+        Self {
+            default,
+            methods: Arc::default(),
+        }
+    }
+
+    /// Budget for one method; `None` lets it run without a timeout
+    pub fn with_method(mut self, method: impl Into<String>, timeout: Option<Duration>) -> Self {
+        Arc::make_mut(&mut self.methods).insert(method.into(), timeout);
+        self
+    }
+
+    /// Budget applied to requests for `path`, e.g. `/likes.LikesService/GetLikesCount`
+    pub fn timeout_for(&self, path: &str) -> Option<Duration> {
+        if is_health_check(path) {
+            return None;
+        }
+
+        let method = path.rsplit('/').next().unwrap_or_default();
+        match self.methods.get(method) {
+            Some(timeout) => *timeout,
+            None => self.default,
+        }
+    }
+}
+
+impl<S> Layer<S> for MethodTimeoutLayer {
+    type Service = MethodTimeout<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MethodTimeout {
+            inner,
+            timeouts: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodTimeout<S> {
+    inner: S,
+    timeouts: MethodTimeoutLayer,
+}
+
+impl<S, B, ResBody> Service<Request<B>> for MethodTimeout<S>
+where
+    S: Service<Request<B>, Response = Response<ResBody>> + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+    ResBody: Default,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxResponseFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let Some(timeout) = self.timeouts.timeout_for(request.uri().path()) else {
+            return Box::pin(self.inner.call(request));
+        };
+
+        let path = request.uri().path().to_string();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, response).await {
+                Ok(response) => response,
+                Err(_) => {
+                    warn!("{} exceeded its {}ms budget", path, timeout.as_millis());
+                    Ok(Status::deadline_exceeded(format!(
+                        "Request exceeded the {}ms budget of this method",
+                        timeout.as_millis()
+                    ))
+                    .into_http())
+                }
+            }
+        })
+    }
+}
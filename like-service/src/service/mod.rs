@@ -1,2 +1,28 @@
+pub mod concurrency;
+pub mod deadline;
+pub mod field_mask;
+pub mod in_flight;
 pub mod like_service;
-pub use like_service::LikesServiceImpl;
+pub mod method_timeout;
+pub mod normalize;
+pub mod rate_limit;
+
+pub use concurrency::ConcurrencyLimitLayer;
+pub use deadline::Deadline;
+pub use field_mask::LikeFieldMask;
+pub use in_flight::InFlightTracker;
+pub use like_service::{DependencyStatus, LikesServiceImpl, Reconciliation};
+pub use method_timeout::MethodTimeoutLayer;
+pub use normalize::{UserIdFields, UserIdNormalizer};
+pub use rate_limit::RateLimiter;
+
+use std::{future::Future, pin::Pin};
+
+/// Future returned by the tower layers wrapping the gRPC server
+pub(crate) type BoxResponseFuture<R, E> = Pin<Box<dyn Future<Output = Result<R, E>> + Send>>;
+
+/// Whether `path` is a gRPC health check. These bypass the concurrency limit,
+/// so a saturated instance isn't restarted, and are never timed out.
+pub(crate) fn is_health_check(path: &str) -> bool {
+    path.starts_with("/grpc.health.v1.Health/")
+}
@@ -0,0 +1,75 @@
+use crate::proto::*;
+
+/// Normalizes the user IDs a request names before they are resolved or
+/// stored, so `" abc "` and `"abc"` are the same user. IDs are always
+/// trimmed; lowercasing is opt-in because Clerk IDs are case-sensitive.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserIdNormalizer {
+    lowercase: bool,
+}
+
+impl UserIdNormalizer {
+    pub fn new(lowercase: bool) -> Self {
+        Self { lowercase }
+    }
+
+    pub fn normalize(&self, user_id: &str) -> String {
+        let trimmed = user_id.trim();
+        if self.lowercase {
+            trimmed.to_lowercase()
+        } else {
+            trimmed.to_string()
+        }
+    }
+
+    /// Normalize every user ID field of `request`
+    pub fn apply<T: UserIdFields>(&self, mut request: T) -> T {
+        for user_id in request.user_ids_mut() {
+            *user_id = self.normalize(user_id);
+        }
+        request
+    }
+}
+
+/// Requests carrying user IDs, as single fields or lists
+pub trait UserIdFields {
+    fn user_ids_mut(&mut self) -> Vec<&mut String>;
+}
+
+macro_rules! user_id_fields {
+    ($($request:ty { $($field:ident),* } [$($list:ident),*];)*) => {
+        $(
+            impl UserIdFields for $request {
+                #[allow(unused_mut)]
+                fn user_ids_mut(&mut self) -> Vec<&mut String> {
+                    let mut user_ids: Vec<&mut String> = vec![$(&mut self.$field),*];
+                    $(user_ids.extend(self.$list.iter_mut());)*
+                    user_ids
+                }
+            }
+        )*
+    };
+}
+
+user_id_fields! {
+    LikePostRequest { user_id } [];
+    LikePostsRequest { user_id } [];
+    UnlikePostRequest { user_id } [];
+    ToggleLikeRequest { user_id } [];
+    UnlikePostsRequest {} [user_ids];
+    DeleteUserLikesRequest { user_id } [];
+    MergeUserLikesRequest { from_user_id, to_user_id } [];
+    GetUserLikesRequest { user_id } [];
+    GetPostLikesRequest { viewer_id } [];
+    IsPostLikedRequest { user_id } [];
+    ExportUserLikesRequest { user_id } [];
+    HasEverLikedRequest { user_id } [];
+    ArePostsLikedRequest { user_id } [];
+    HasUserLikedAnyRequest { user_id } [];
+    WhichUsersLikedRequest {} [user_ids];
+    GetLikesByUsersRequest {} [user_ids];
+    GetUserLikesCountRequest { user_id } [];
+    GetRecentLikedPostsRequest { user_id } [];
+    GetMutualLikersRequest { user_id } [];
+    GetLikesBetweenUsersRequest { liker_id, author_id } [];
+}
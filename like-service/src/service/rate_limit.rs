@@ -0,0 +1,60 @@
+use dashmap::DashMap;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Per-key token bucket limiter, shared by all clones
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    rate_per_second: f64,
+    burst: f64,
+    buckets: Arc<DashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Allow `burst` requests at once, refilling at `rate_per_second`
+    pub fn new(rate_per_second: f64, burst: u32) -> Self {
+        Self {
+            rate_per_second,
+            burst: f64::from(burst.max(1)),
+            buckets: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Take a token for `key`; false when its bucket is empty
+    pub fn try_acquire(&self, key: &str) -> bool {
+        let now = Instant::now();
+        let mut bucket = self.buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            refilled_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate_per_second).min(self.burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drop buckets idle long enough to have refilled completely; a fresh
+    /// bucket behaves the same. Returns how many were removed.
+    pub fn evict_idle(&self) -> usize {
+        let full_after = Duration::from_secs_f64(self.burst / self.rate_per_second);
+        let before = self.buckets.len();
+        self.buckets
+            .retain(|_, bucket| bucket.refilled_at.elapsed() < full_after);
+        before - self.buckets.len()
+    }
+}
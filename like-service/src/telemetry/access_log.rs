@@ -0,0 +1,105 @@
+use std::time::Duration;
+use tonic::{
+    Code,
+    codegen::http::{HeaderMap, Request, Response},
+    transport::server::{TcpConnectInfo, TlsConnectInfo},
+};
+use tower_http::{
+    classify::{GrpcErrorsAsFailures, SharedClassifier},
+    trace::{MakeSpan, OnEos, OnResponse, TraceLayer},
+};
+use tracing::{Level, Span, field};
+
+/// Tower layer writing one access log line per RPC
+pub type AccessLogLayer =
+    TraceLayer<SharedClassifier<GrpcErrorsAsFailures>, AccessLog, (), AccessLog, (), AccessLog, ()>;
+
+/// Access log with the method, peer address, gRPC status and latency of every
+/// RPC, all at `level`
+pub fn access_log_layer(level: Level) -> AccessLogLayer {
+    let access_log = AccessLog { level };
+    TraceLayer::new_for_grpc()
+        .make_span_with(access_log)
+        .on_request(())
+        .on_response(access_log)
+        .on_body_chunk(())
+        .on_eos(access_log)
+        .on_failure(())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AccessLog {
+    level: Level,
+}
+
+// tracing needs the level as a constant, so dispatch on it
+macro_rules! at_level {
+    ($level:expr, $mac:ident!($($args:tt)*)) => {
+        match $level {
+            Level::ERROR => tracing::$mac!(Level::ERROR, $($args)*),
+            Level::WARN => tracing::$mac!(Level::WARN, $($args)*),
+            Level::INFO => tracing::$mac!(Level::INFO, $($args)*),
+            Level::DEBUG => tracing::$mac!(Level::DEBUG, $($args)*),
+            Level::TRACE => tracing::$mac!(Level::TRACE, $($args)*),
+        }
+    };
+}
+
+impl AccessLog {
+    // tower-http calls back with the request span entered, so the method,
+    // peer and latency fields come from it
+    fn log(&self, status: Code) {
+        at_level!(self.level, event!(status = ?status, "rpc completed"));
+    }
+}
+
+impl<B> MakeSpan<B> for AccessLog {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        let peer = peer_addr(request);
+        at_level!(
+            self.level,
+            span!(
+                "rpc",
+                method = %request.uri().path(),
+                peer = %peer,
+                latency_ms = field::Empty,
+            )
+        )
+    }
+}
+
+impl<B> OnResponse<B> for AccessLog {
+    fn on_response(self, response: &Response<B>, latency: Duration, span: &Span) {
+        span.record("latency_ms", latency.as_millis() as u64);
+
+        // Calls failing before any message are answered trailers-only, with the
+        // status in the headers and no end-of-stream callback
+        if let Some(status) = response.headers().get("grpc-status") {
+            self.log(Code::from_bytes(status.as_bytes()));
+        }
+    }
+}
+
+impl OnEos for AccessLog {
+    fn on_eos(self, trailers: Option<&HeaderMap>, _stream_duration: Duration, _span: &Span) {
+        let status = trailers
+            .and_then(|trailers| trailers.get("grpc-status"))
+            .map(|status| Code::from_bytes(status.as_bytes()))
+            .unwrap_or(Code::Unknown);
+        self.log(status);
+    }
+}
+
+fn peer_addr<B>(request: &Request<B>) -> String {
+    let extensions = request.extensions();
+    extensions
+        .get::<TcpConnectInfo>()
+        .or_else(|| {
+            extensions
+                .get::<TlsConnectInfo<TcpConnectInfo>>()
+                .map(|info| info.get_ref())
+        })
+        .and_then(|info| info.remote_addr())
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
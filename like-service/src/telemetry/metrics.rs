@@ -0,0 +1,93 @@
+use anyhow::{Result, anyhow};
+use metrics::{counter, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+use std::{future::Future, net::SocketAddr, time::Instant};
+use tokio::task::JoinHandle;
+use tonic::{Code, Response, Status};
+use tracing::{error, info};
+
+pub const RPC_REQUESTS_TOTAL: &str = "like_service_rpc_requests_total";
+pub const RPC_DURATION_SECONDS: &str = "like_service_rpc_duration_seconds";
+pub const DB_QUERIES_TOTAL: &str = "like_service_db_queries_total";
+pub const DB_QUERY_DURATION_SECONDS: &str = "like_service_db_query_duration_seconds";
+pub const CACHE_HITS_TOTAL: &str = "like_service_cache_hits_total";
+pub const CACHE_MISSES_TOTAL: &str = "like_service_cache_misses_total";
+pub const WRITE_BATCH_SIZE: &str = "like_service_write_batch_size";
+
+// Latency buckets in seconds, shared by RPC and query histograms
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+// Likes per buffered write batch
+const BATCH_SIZE_BUCKETS: &[f64] = &[1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0];
+
+/// Install the global Prometheus recorder and serve `/metrics` on `addr`
+pub fn install_metrics_exporter(addr: SocketAddr) -> Result<JoinHandle<()>> {
+    let (recorder, exporter) = PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .set_buckets_for_metric(
+            Matcher::Suffix("duration_seconds".to_string()),
+            LATENCY_BUCKETS,
+        )?
+        .set_buckets_for_metric(
+            Matcher::Full(WRITE_BATCH_SIZE.to_string()),
+            BATCH_SIZE_BUCKETS,
+        )?
+        .build()?;
+
+    metrics::set_global_recorder(recorder)
+        .map_err(|e| anyhow!("Failed to install metrics recorder: {}", e))?;
+
+    info!("Metrics endpoint listening on {}", addr);
+    Ok(tokio::spawn(async move {
+        if let Err(e) = exporter.await {
+            error!("Metrics exporter failed: {:?}", e);
+        }
+    }))
+}
+
+/// Run an RPC handler, recording its outcome and latency by method
+pub async fn observe_rpc<T, F>(method: &'static str, handler: F) -> Result<Response<T>, Status>
+where
+    F: Future<Output = Result<Response<T>, Status>>,
+{
+    let started = Instant::now();
+    let result = handler.await;
+
+    let code = match &result {
+        Ok(_) => Code::Ok,
+        Err(status) => status.code(),
+    };
+    let status = format!("{:?}", code);
+
+    counter!(RPC_REQUESTS_TOTAL, "method" => method, "status" => status.clone()).increment(1);
+    histogram!(RPC_DURATION_SECONDS, "method" => method, "status" => status)
+        .record(started.elapsed().as_secs_f64());
+
+    result
+}
+
+/// Record the duration of a SurrealDB query started at `started`
+pub fn record_db_query(started: Instant, success: bool) {
+    let outcome = if success { "ok" } else { "error" };
+    counter!(DB_QUERIES_TOTAL, "outcome" => outcome).increment(1);
+    histogram!(DB_QUERY_DURATION_SECONDS, "outcome" => outcome)
+        .record(started.elapsed().as_secs_f64());
+}
+
+/// Count a lookup in `cache`: the "user" or "post" existence cache, or the
+/// "post_likes" page cache
+pub fn record_cache_lookup(cache: &'static str, hit: bool) {
+    let name = if hit {
+        CACHE_HITS_TOTAL
+    } else {
+        CACHE_MISSES_TOTAL
+    };
+    counter!(name, "cache" => cache).increment(1);
+}
+
+/// Record how many likes a write buffer flush carried
+pub fn record_write_batch(size: usize) {
+    histogram!(WRITE_BATCH_SIZE).record(size as f64);
+}
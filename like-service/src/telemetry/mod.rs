@@ -0,0 +1,11 @@
+pub mod access_log;
+pub mod metrics;
+pub mod otel;
+pub mod request_id;
+
+pub use access_log::access_log_layer;
+pub use metrics::{
+    install_metrics_exporter, observe_rpc, record_cache_lookup, record_db_query, record_write_batch,
+};
+pub use otel::{init_tracing, inject_trace_context, server_span};
+pub use request_id::{RequestIdLayer, inject_request_id};
@@ -0,0 +1,116 @@
+use crate::config::Config;
+use anyhow::{Result, anyhow};
+use opentelemetry::{
+    global,
+    propagation::{Extractor, Injector},
+    trace::TracerProvider as _,
+};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{Resource, propagation::TraceContextPropagator, trace::SdkTracerProvider};
+use tonic::{
+    codegen::http::{HeaderMap, Request},
+    metadata::{MetadataKey, MetadataMap, MetadataValue},
+};
+use tracing::{Span, info_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+const SERVICE_NAME: &str = "likes-service";
+
+/// Install the global tracing subscriber, writing JSON lines when
+/// [`Config::json_logs`] and human-readable text otherwise. When
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set, spans are also exported over OTLP
+/// and W3C trace context is propagated.
+/// The returned provider must be shut down on exit to flush pending spans.
+pub fn init_tracing(config: &Config) -> Result<Option<SdkTracerProvider>> {
+    let provider = match &config.otel_exporter_otlp_endpoint {
+        Some(endpoint) => {
+            let exporter = SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint)
+                .build()
+                .map_err(|e| anyhow!("Failed to build OTLP exporter: {}", e))?;
+
+            let provider = SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+                .build();
+
+            global::set_text_map_propagator(TraceContextPropagator::new());
+            global::set_tracer_provider(provider.clone());
+            Some(provider)
+        }
+        None => None,
+    };
+
+    let otel_layer = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    // One object per line with timestamp, level, target, the event's fields
+    // and those of every enclosing span (request id, RPC method, ...)
+    let (json_layer, text_layer) = if config.json_logs() {
+        let json = tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true);
+        (Some(json), None)
+    } else {
+        (None, Some(tracing_subscriber::fmt::layer()))
+    };
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "likes_service=debug,tower_http=debug".into()),
+        )
+        .with(json_layer)
+        .with(text_layer)
+        .with(otel_layer)
+        .init();
+
+    Ok(provider)
+}
+
+/// Span for one incoming RPC, continuing the caller's trace when it sent one
+pub fn server_span(request: &Request<()>) -> Span {
+    let span = info_span!("grpc.request", rpc.method = %request.uri().path());
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    span.set_parent(parent);
+    span
+}
+
+/// Add the current trace context to an outbound request's metadata
+pub fn inject_trace_context(metadata: &mut MetadataMap) {
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut MetadataInjector(metadata))
+    });
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+struct MetadataInjector<'a>(&'a mut MetadataMap);
+
+impl Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(key), Ok(value)) = (
+            MetadataKey::from_bytes(key.as_bytes()),
+            MetadataValue::try_from(value.as_str()),
+        ) {
+            self.0.insert(key, value);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tonic::{
+    codegen::http::{HeaderValue, Request, Response},
+    metadata::{MetadataMap, MetadataValue},
+};
+use tower::{Layer, Service};
+use tracing::{Instrument, info_span};
+use uuid::Uuid;
+
+/// Header carrying the id attached to a request at the edge
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being served on this task, if any
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Forward the current request id on an outbound request
+pub fn inject_request_id(metadata: &mut MetadataMap) {
+    if let Some(value) = current_request_id().and_then(|id| MetadataValue::try_from(id).ok()) {
+        metadata.insert(REQUEST_ID_HEADER, value);
+    }
+}
+
+/// Tower layer reading `x-request-id` (or generating a UUID when it is
+/// missing) and recording it on a `request` span around the whole call,
+/// so every log line of the request carries it. The id is echoed back on
+/// the response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestId<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestId { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestId<S> {
+    inner: S,
+}
+
+impl<S, B, ResBody> Service<Request<B>> for RequestId<S>
+where
+    S: Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        // Handlers reading the header see a generated id too
+        let header = HeaderValue::from_str(&request_id).ok();
+        if let Some(header) = &header {
+            request
+                .headers_mut()
+                .insert(REQUEST_ID_HEADER, header.clone());
+        }
+
+        let span = info_span!("request", request_id = %request_id);
+        // Spans opened by inner layers, e.g. the access log, nest under this one
+        let response = span.in_scope(|| self.inner.call(request));
+
+        Box::pin(
+            REQUEST_ID.scope(
+                request_id,
+                async move {
+                    let mut response = response.await?;
+                    if let Some(header) = header {
+                        response.headers_mut().insert(REQUEST_ID_HEADER, header);
+                    }
+                    Ok(response)
+                }
+                .instrument(span),
+            ),
+        )
+    }
+}
@@ -0,0 +1,239 @@
+mod common;
+
+use common::{MockPosts, MockUsers};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use like_service::{
+    auth::{AuthInterceptor, Caller, JwtVerifier},
+    models::ReactionType,
+    proto::{
+        GetPostLikesRequest, LikePostRequest, StreamPostLikesRequest,
+        likes_service_server::LikesService,
+    },
+};
+use serde_json::json;
+use tokio_stream::StreamExt;
+use tonic::{Code, Request, service::Interceptor};
+
+const SECRET: &[u8] = b"test-secret";
+
+fn token(secret: &[u8], subject: &str, expires_in_seconds: i64) -> String {
+    let claims = json!({
+        "sub": subject,
+        "exp": chrono::Utc::now().timestamp() + expires_in_seconds,
+    });
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret),
+    )
+    .unwrap()
+}
+
+fn intercept(
+    interceptor: &mut AuthInterceptor,
+    authorization: Option<String>,
+) -> Result<Option<Caller>, Code> {
+    let mut request = Request::new(());
+    if let Some(value) = authorization {
+        request
+            .metadata_mut()
+            .insert("authorization", value.parse().unwrap());
+    }
+    interceptor
+        .call(request)
+        .map(|request| request.extensions().get::<Caller>().cloned())
+        .map_err(|status| status.code())
+}
+
+fn enabled() -> AuthInterceptor {
+    AuthInterceptor::new(Some(JwtVerifier::with_secret(SECRET)))
+}
+
+#[test]
+fn disabled_interceptor_passes_calls_through() {
+    let mut interceptor = AuthInterceptor::default();
+
+    assert_eq!(intercept(&mut interceptor, None), Ok(None));
+    assert_eq!(
+        intercept(&mut interceptor, Some("Bearer garbage".to_string())),
+        Ok(None)
+    );
+}
+
+#[test]
+fn valid_token_identifies_the_caller() {
+    let bearer = format!("Bearer {}", token(SECRET, "user_alice", 60));
+
+    assert_eq!(
+        intercept(&mut enabled(), Some(bearer)),
+        Ok(Some(Caller::User("user_alice".to_string())))
+    );
+}
+
+#[test]
+fn missing_token_is_anonymous_unless_required() {
+    assert_eq!(intercept(&mut enabled(), None), Ok(Some(Caller::Anonymous)));
+    assert_eq!(
+        intercept(&mut enabled().require_token(true), None),
+        Err(Code::Unauthenticated)
+    );
+}
+
+#[test]
+fn bad_tokens_are_rejected() {
+    let forged = format!("Bearer {}", token(b"another-secret", "user_alice", 60));
+    let expired = format!("Bearer {}", token(SECRET, "user_alice", -3600));
+    let basic = "Basic dXNlcjpwYXNz".to_string();
+
+    for authorization in [forged, expired, basic] {
+        assert_eq!(
+            intercept(&mut enabled(), Some(authorization)),
+            Err(Code::Unauthenticated)
+        );
+    }
+}
+
+async fn like_post_as(caller: Option<Caller>) -> Result<bool, Code> {
+    let (service, _) = common::service(
+        MockUsers::default().with_user("user_alice", "alice"),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+
+    let mut request = Request::new(LikePostRequest {
+        user_id: "user_alice".to_string(),
+        post_id: 1,
+        ..Default::default()
+    });
+    if let Some(caller) = caller {
+        request.extensions_mut().insert(caller);
+    }
+
+    service
+        .like_post(request)
+        .await
+        .map(|response| response.into_inner().success)
+        .map_err(|status| status.code())
+}
+
+#[tokio::test]
+async fn like_post_requires_the_token_subject_to_match() {
+    assert_eq!(
+        like_post_as(Some(Caller::User("user_mallory".to_string()))).await,
+        Err(Code::PermissionDenied)
+    );
+    assert_eq!(
+        like_post_as(Some(Caller::Anonymous)).await,
+        Err(Code::Unauthenticated)
+    );
+    assert_eq!(
+        like_post_as(Some(Caller::User("user_alice".to_string()))).await,
+        Ok(true)
+    );
+    // Without the interceptor, e.g. when auth is disabled, calls are trusted
+    assert_eq!(like_post_as(None).await, Ok(true));
+}
+
+#[tokio::test]
+async fn token_subject_is_normalized_like_the_user_id() {
+    let (service, _) = common::service(
+        MockUsers::default().with_user("user_2abc", "alice"),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+    let service = service.with_lowercase_user_ids(true);
+
+    let mut request = Request::new(LikePostRequest {
+        user_id: "user_2abc".to_string(),
+        post_id: 1,
+        ..Default::default()
+    });
+    request
+        .extensions_mut()
+        .insert(Caller::User(" user_2AbC".to_string()));
+
+    let response = service.like_post(request).await.unwrap().into_inner();
+    assert!(response.success);
+}
+
+// Whether the likers of bob's post 1, liked by alice, are hidden from the
+// caller, and the user ID shown for alice's like
+async fn post_likers_seen_by(caller: Option<Caller>, viewer_id: &str) -> (bool, String) {
+    let (service, repository) = common::service(
+        MockUsers::default()
+            .with_user("user_alice", "alice")
+            .with_user("user_bob", "bob"),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+    repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+
+    let mut request = Request::new(GetPostLikesRequest {
+        post_id: 1,
+        viewer_id: viewer_id.to_string(),
+        ..Default::default()
+    });
+    if let Some(caller) = caller {
+        request.extensions_mut().insert(caller);
+    }
+
+    let response = service.get_post_likes(request).await.unwrap().into_inner();
+    (response.anonymized, response.likes[0].user_id.clone())
+}
+
+#[tokio::test]
+async fn with_auth_only_the_authenticated_author_sees_the_likers() {
+    let bob = || Some(Caller::User("user_bob".to_string()));
+    let alice = || Some(Caller::User("user_alice".to_string()));
+
+    assert_eq!(
+        post_likers_seen_by(bob(), "").await,
+        (false, "alice".to_string())
+    );
+    // A caller-supplied viewer_id cannot claim to be the author
+    assert_eq!(
+        post_likers_seen_by(alice(), "user_bob").await,
+        (true, String::new())
+    );
+    assert_eq!(
+        post_likers_seen_by(Some(Caller::Anonymous), "").await,
+        (true, String::new())
+    );
+    // Without auth, viewer_id still decides and omitting it is trusted
+    assert_eq!(
+        post_likers_seen_by(None, "user_alice").await,
+        (true, String::new())
+    );
+    assert_eq!(
+        post_likers_seen_by(None, "").await,
+        (false, "alice".to_string())
+    );
+}
+
+#[tokio::test]
+async fn streamed_likers_are_hidden_from_anonymous_callers() {
+    let (service, repository) = common::service(
+        MockUsers::default(),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+    repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+
+    let mut request = Request::new(StreamPostLikesRequest { post_id: 1 });
+    request.extensions_mut().insert(Caller::Anonymous);
+    let mut likes = service
+        .stream_post_likes(request)
+        .await
+        .unwrap()
+        .into_inner();
+
+    let like = likes.next().await.unwrap().unwrap();
+    assert!(like.user_id.is_empty());
+    assert!(like.liked_at.is_some());
+}
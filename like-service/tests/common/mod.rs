@@ -0,0 +1,173 @@
+// Each test crate uses a different part of these helpers
+#![allow(dead_code)]
+
+use anyhow::Result;
+use like_service::{
+    clients::{PostMetadata, PostValidator, UserValidator},
+    database::{Database, DatabaseOptions},
+    proto::{
+        post::Post,
+        user::{GetUserResponse, User},
+    },
+    repository::LikesRepository,
+    service::LikesServiceImpl,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tonic::Status;
+
+/// A fresh in-memory database with every migration applied. Each call gets
+/// its own database, so tests never see each other's likes.
+pub async fn database() -> Database {
+    Database::new(&DatabaseOptions::default())
+        .await
+        .expect("in-memory database should start")
+}
+
+/// A repository over a fresh in-memory database
+pub async fn repository() -> LikesRepository {
+    LikesRepository::new(database().await)
+}
+
+/// A service backed by a fresh in-memory database and the given doubles,
+/// with the repository it writes to
+pub async fn service(users: MockUsers, posts: MockPosts) -> (LikesServiceImpl, LikesRepository) {
+    let repository = repository().await;
+    let service = LikesServiceImpl::new(repository.clone(), Arc::new(users), Arc::new(posts));
+    (service, repository)
+}
+
+/// User Service double. Known Clerk IDs resolve to their database IDs, and
+/// those database IDs exist too.
+#[derive(Debug, Default)]
+pub struct MockUsers {
+    users: HashMap<String, String>,
+    down: bool,
+}
+
+impl MockUsers {
+    /// Fail health checks, as if the User Service were unreachable
+    pub fn down(mut self) -> Self {
+        self.down = true;
+        self
+    }
+
+    pub fn with_user(mut self, clerk_id: &str, db_id: &str) -> Self {
+        self.users.insert(clerk_id.to_string(), db_id.to_string());
+        self
+    }
+}
+
+#[tonic::async_trait]
+impl UserValidator for MockUsers {
+    async fn user_exists(&self, user_id: &str, _timeout: Option<Duration>) -> Result<bool> {
+        Ok(self.users.contains_key(user_id) || self.users.values().any(|id| id == user_id))
+    }
+
+    async fn get_user(&self, user_id: &str, _timeout: Option<Duration>) -> Result<GetUserResponse> {
+        match self.users.get(user_id) {
+            Some(db_id) => Ok(GetUserResponse {
+                success: true,
+                message: String::new(),
+                user: Some(User {
+                    id: db_id.clone(),
+                    ..Default::default()
+                }),
+            }),
+            None => Err(anyhow::Error::new(Status::not_found("User not found"))
+                .context("Failed to get user")),
+        }
+    }
+
+    async fn invalidate_user(&self, _user_id: &str) {}
+
+    async fn health_check(&self, _timeout: Option<Duration>) -> bool {
+        !self.down
+    }
+}
+
+/// Post Service double; each known post has an author (a database user ID)
+#[derive(Debug, Default)]
+pub struct MockPosts {
+    posts: HashMap<u32, String>,
+    down: bool,
+    latency: Duration,
+}
+
+impl MockPosts {
+    /// Take `latency` to answer each existence check
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Fail health and existence checks, as if the Post Service were unreachable
+    pub fn down(mut self) -> Self {
+        self.down = true;
+        self
+    }
+
+    pub fn with_post(mut self, post_id: u32, author_id: &str) -> Self {
+        self.posts.insert(post_id, author_id.to_string());
+        self
+    }
+
+    fn post(&self, post_id: u32) -> Option<Post> {
+        self.posts.get(&post_id).map(|author_id| Post {
+            id: post_id,
+            user_id: author_id.clone(),
+            title: format!("Post {}", post_id),
+            ..Default::default()
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl PostValidator for MockPosts {
+    async fn post_exists(&self, post_id: u32, _timeout: Option<Duration>) -> Result<bool> {
+        tokio::time::sleep(self.latency).await;
+        if self.down {
+            return Err(Status::unavailable("post service is down").into());
+        }
+        Ok(self.posts.contains_key(&post_id))
+    }
+
+    async fn is_post_owner(
+        &self,
+        post_id: u32,
+        user_id: &str,
+        _timeout: Option<Duration>,
+    ) -> Result<bool> {
+        Ok(self
+            .posts
+            .get(&post_id)
+            .is_some_and(|author| author == user_id))
+    }
+
+    async fn get_post_metadata(
+        &self,
+        post_id: u32,
+        _timeout: Option<Duration>,
+    ) -> Option<PostMetadata> {
+        self.post(post_id).map(|post| PostMetadata {
+            id: post.id,
+            title: post.title,
+            author_id: post.user_id,
+            content_preview: post.content,
+        })
+    }
+
+    async fn get_posts_batch(
+        &self,
+        post_ids: Vec<u32>,
+        _timeout: Option<Duration>,
+    ) -> Vec<Option<Post>> {
+        post_ids
+            .into_iter()
+            .map(|post_id| self.post(post_id))
+            .collect()
+    }
+
+    async fn health_check(&self, _timeout: Option<Duration>) -> bool {
+        !self.down
+    }
+}
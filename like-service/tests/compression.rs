@@ -0,0 +1,94 @@
+mod common;
+
+use common::{MockPosts, MockUsers};
+use like_service::{
+    models::ReactionType,
+    proto::{
+        GetPostLikesRequest, likes_service_client::LikesServiceClient,
+        likes_service_server::LikesServiceServer,
+    },
+    repository::LikesRepository,
+};
+use std::{net::SocketAddr, time::Duration};
+use tonic::{
+    Request,
+    codec::CompressionEncoding,
+    transport::{Channel, Server},
+};
+
+const LIKES: usize = 100;
+
+/// Serve the service with gzip enabled, the way `main` does when
+/// `GRPC_COMPRESSION` is set, and connect a gzip-capable client to it
+async fn gzip_client(posts: MockPosts) -> (LikesServiceClient<Channel>, LikesRepository) {
+    let (service, repository) = common::service(MockUsers::default(), posts).await;
+    let server = LikesServiceServer::new(service)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .send_compressed(CompressionEncoding::Gzip);
+
+    // Reserve a free port, then hand it to the server
+    let addr: SocketAddr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("a local port should be free");
+    tokio::spawn(Server::builder().add_service(server).serve(addr));
+
+    let mut attempts = 0;
+    let channel = loop {
+        match Channel::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+        {
+            Ok(channel) => break channel,
+            Err(e) if attempts >= 50 => panic!("server did not start: {e}"),
+            Err(_) => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        }
+    };
+
+    let client = LikesServiceClient::new(channel)
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Gzip);
+    (client, repository)
+}
+
+#[tokio::test]
+async fn large_post_likes_page_round_trips_gzipped() {
+    let (mut client, repository) = gzip_client(MockPosts::default().with_post(1, "author")).await;
+
+    // Long, repetitive IDs make a page worth compressing
+    let user_ids: Vec<String> = (0..LIKES)
+        .map(|i| format!("user_{i:04}_{}", "x".repeat(200)))
+        .collect();
+    for user_id in &user_ids {
+        repository
+            .create_like(user_id, &1, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let response = client
+        .get_post_likes(Request::new(GetPostLikesRequest {
+            post_id: 1,
+            limit: LIKES as i32,
+            ..Default::default()
+        }))
+        .await
+        .expect("get_post_likes should succeed");
+
+    assert_eq!(
+        response
+            .metadata()
+            .get("grpc-encoding")
+            .and_then(|v| v.to_str().ok()),
+        Some("gzip")
+    );
+
+    let likes = response.into_inner().likes;
+    assert_eq!(likes.len(), LIKES);
+    let mut returned: Vec<String> = likes.into_iter().map(|like| like.user_id).collect();
+    returned.sort();
+    assert_eq!(returned, user_ids);
+}
@@ -0,0 +1,99 @@
+use like_service::service::ConcurrencyLimitLayer;
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tonic::{
+    Code, Status,
+    codegen::http::{Request, Response},
+};
+use tower::{Layer, Service};
+
+/// Answers every request with OK after `delay`
+#[derive(Clone)]
+struct Slow {
+    delay: Duration,
+}
+
+impl Service<Request<()>> for Slow {
+    type Response = Response<String>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _request: Request<()>) -> Self::Future {
+        let delay = self.delay;
+        Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            Ok(Response::new("ok".to_string()))
+        })
+    }
+}
+
+fn request(path: &str) -> Request<()> {
+    Request::builder().uri(path).body(()).unwrap()
+}
+
+fn code(response: &Response<String>) -> Code {
+    Status::from_header_map(response.headers())
+        .map(|status| status.code())
+        .unwrap_or(Code::Ok)
+}
+
+#[tokio::test]
+async fn requests_over_the_limit_are_exhausted_after_queueing() {
+    let layer = ConcurrencyLimitLayer::new(1, Duration::from_millis(50));
+    let mut service = layer.layer(Slow {
+        delay: Duration::from_millis(300),
+    });
+
+    let first = tokio::spawn(service.call(request("/likes.LikesService/LikePost")));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(layer.available(), Some(0));
+
+    // Queues past the timeout while the first request holds the only slot
+    let rejected = service
+        .call(request("/likes.LikesService/LikePost"))
+        .await
+        .unwrap();
+    assert_eq!(code(&rejected), Code::ResourceExhausted);
+
+    // Health checks are never limited
+    let health = service
+        .call(request("/grpc.health.v1.Health/Check"))
+        .await
+        .unwrap();
+    assert_eq!(code(&health), Code::Ok);
+
+    let first = first.await.unwrap().unwrap();
+    assert_eq!(code(&first), Code::Ok);
+    assert_eq!(layer.available(), Some(1));
+}
+
+#[tokio::test]
+async fn queued_requests_run_once_a_slot_frees_up() {
+    let layer = ConcurrencyLimitLayer::new(1, Duration::from_secs(2));
+    let mut service = layer.layer(Slow {
+        delay: Duration::from_millis(50),
+    });
+
+    let first = tokio::spawn(service.call(request("/likes.LikesService/LikePost")));
+    let second = service
+        .call(request("/likes.LikesService/LikePost"))
+        .await
+        .unwrap();
+    assert_eq!(code(&second), Code::Ok);
+    assert_eq!(code(&first.await.unwrap().unwrap()), Code::Ok);
+}
+
+#[tokio::test]
+async fn zero_disables_the_limit() {
+    let layer = ConcurrencyLimitLayer::new(0, Duration::from_millis(1));
+    assert_eq!(layer.available(), None);
+}
@@ -0,0 +1,780 @@
+mod common;
+
+use common::{MockPosts, MockUsers};
+use like_service::{
+    models::{PaginationBounds, ReactionType},
+    proto::{
+        GetLikesByUsersRequest, GetLikesCountRequest, GetLikesCountResponse,
+        GetLikesCountSinceRequest, GetLikesHistogramRequest, GetPostLikesRequest,
+        GetRecentLikedPostsRequest, HasEverLikedRequest, HealthCheckRequest, HistogramBucket,
+        LikeChangeAction, LikePostRequest, LikePostResponse, LikePostsRequest,
+        MergeUserLikesRequest, Reaction, UnlikePostRequest, WatchPostLikesRequest,
+        WhichUsersLikedRequest, likes_service_server::LikesService,
+    },
+    service::{LikesServiceImpl, Reconciliation},
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio_stream::StreamExt;
+use tonic::{Code, Request};
+
+async fn like_post(service: &impl LikesService, user_id: &str, post_id: u32) -> LikePostResponse {
+    let request = LikePostRequest {
+        user_id: user_id.to_string(),
+        post_id,
+        ..Default::default()
+    };
+    service
+        .like_post(Request::new(request))
+        .await
+        .expect("like_post should not fail")
+        .into_inner()
+}
+
+#[tokio::test]
+async fn like_post_reports_an_unknown_user() {
+    let (service, repository) = common::service(
+        MockUsers::default(),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+
+    let response = like_post(&service, "user_alice", 1).await;
+
+    assert!(!response.success);
+    assert_eq!(response.message, "User not found");
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn like_post_reports_an_unknown_post() {
+    let (service, repository) = common::service(
+        MockUsers::default().with_user("user_alice", "alice"),
+        MockPosts::default(),
+    )
+    .await;
+
+    let response = like_post(&service, "user_alice", 1).await;
+
+    assert!(!response.success);
+    assert_eq!(response.message, "Post not found");
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn like_post_records_the_like_under_the_database_id() {
+    let (service, repository) = common::service(
+        MockUsers::default().with_user("user_alice", "alice"),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+
+    let response = like_post(&service, "user_alice", 1).await;
+
+    assert!(response.success);
+    assert_eq!(response.message, "Post liked successfully");
+    assert!(response.liked_at.is_some());
+    assert!(
+        repository
+            .is_post_liked("alice", &1)
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    // Liking again is not an error unless the request is strict
+    let again = like_post(&service, "user_alice", 1).await;
+    assert!(again.success);
+    assert_eq!(again.message, "Already liked");
+    assert_eq!(again.liked_at, response.liked_at);
+}
+
+async fn health(users: MockUsers, posts: MockPosts) -> (String, Vec<(String, bool)>) {
+    let (service, _) = common::service(users, posts).await;
+    let response = service
+        .health_check(Request::new(HealthCheckRequest {}))
+        .await
+        .expect("health_check should not fail while the database is up")
+        .into_inner();
+    let dependencies = response
+        .dependencies
+        .into_iter()
+        .map(|dependency| (dependency.name, dependency.healthy))
+        .collect();
+    (response.status, dependencies)
+}
+
+#[tokio::test]
+async fn health_check_reports_each_dependency() {
+    let (status, dependencies) = health(MockUsers::default(), MockPosts::default()).await;
+
+    assert_eq!(status, "healthy");
+    assert_eq!(
+        dependencies,
+        [
+            ("database".to_string(), true),
+            ("user_service".to_string(), true),
+            ("post_service".to_string(), true),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn health_check_is_degraded_while_a_dependency_is_down() {
+    let (status, dependencies) = health(MockUsers::default(), MockPosts::default().down()).await;
+
+    assert_eq!(status, "degraded");
+    assert!(dependencies.contains(&("post_service".to_string(), false)));
+    assert!(dependencies.contains(&("user_service".to_string(), true)));
+}
+
+#[tokio::test]
+async fn like_posts_validates_a_batch_concurrently_in_order() {
+    const LATENCY: Duration = Duration::from_millis(20);
+
+    // Even posts exist; odd ones do not
+    let posts = (1..=50)
+        .filter(|post_id| post_id % 2 == 0)
+        .fold(MockPosts::default(), |posts, post_id| {
+            posts.with_post(post_id, "bob")
+        })
+        .with_latency(LATENCY);
+    let (service, repository) =
+        common::service(MockUsers::default().with_user("user_alice", "alice"), posts).await;
+
+    // Out of order and with a repeat, to check results follow the request
+    let post_ids: Vec<u32> = (1..=50).rev().chain([7]).collect();
+    let started = Instant::now();
+    let results = service
+        .like_posts(Request::new(LikePostsRequest {
+            user_id: "user_alice".to_string(),
+            post_ids,
+            ..Default::default()
+        }))
+        .await
+        .expect("like_posts should not fail")
+        .into_inner()
+        .results;
+    let elapsed = started.elapsed();
+
+    // Serial checks would take 50 × LATENCY
+    assert!(elapsed < LATENCY * 25, "took {:?}", elapsed);
+
+    let expected: Vec<u32> = (1..=50).rev().collect();
+    assert_eq!(
+        results.iter().map(|r| r.post_id).collect::<Vec<_>>(),
+        expected
+    );
+    for result in &results {
+        assert_eq!(result.success, result.post_id % 2 == 0, "{:?}", result);
+    }
+    assert_eq!(repository.get_user_likes_count("alice").await.unwrap(), 25);
+}
+
+#[tokio::test]
+async fn likes_count_since_defaults_to_the_last_hour() {
+    let (service, repository) = common::service(MockUsers::default(), MockPosts::default()).await;
+    for user_id in ["alice", "bob"] {
+        repository
+            .create_like(user_id, &1, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let count_since = |since: Option<chrono::DateTime<chrono::Utc>>| {
+        service.get_likes_count_since(Request::new(GetLikesCountSinceRequest {
+            post_id: 1,
+            since: since.map(|since| prost_types::Timestamp {
+                seconds: since.timestamp(),
+                nanos: since.timestamp_subsec_nanos() as i32,
+            }),
+        }))
+    };
+
+    let response = count_since(None).await.unwrap().into_inner();
+    assert_eq!(response.count, 2);
+    let since = response.since.expect("the default since should be echoed");
+    let expected = chrono::Utc::now() - chrono::Duration::hours(1);
+    assert!((expected.timestamp() - since.seconds).abs() <= 5);
+
+    let now = chrono::Utc::now();
+    assert_eq!(count_since(Some(now)).await.unwrap().into_inner().count, 0);
+
+    let status = count_since(Some(now + chrono::Duration::minutes(5)))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn lenient_validation_records_likes_while_posts_are_down() {
+    let users = || Arc::new(MockUsers::default().with_user("user_alice", "alice"));
+    let repository = common::repository().await;
+
+    let strict = LikesServiceImpl::new(
+        repository.clone(),
+        users(),
+        Arc::new(MockPosts::default().down()),
+    );
+    let status = strict
+        .like_post(Request::new(LikePostRequest {
+            user_id: "user_alice".to_string(),
+            post_id: 1,
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+
+    let lenient = strict.with_lenient_post_validation(true);
+    for post_id in [1, 2] {
+        assert!(like_post(&lenient, "user_alice", post_id).await.success);
+    }
+    assert_eq!(
+        repository.get_unvalidated_post_ids(10).await.unwrap().len(),
+        2
+    );
+
+    // Still down: nothing is resolved
+    assert_eq!(
+        lenient.reconcile_unvalidated_likes().await.unwrap(),
+        Reconciliation {
+            pending: 2,
+            ..Default::default()
+        }
+    );
+
+    // Back up, where only post 1 exists
+    let recovered = LikesServiceImpl::new(
+        repository.clone(),
+        users(),
+        Arc::new(MockPosts::default().with_post(1, "bob")),
+    );
+    assert_eq!(
+        recovered.reconcile_unvalidated_likes().await.unwrap(),
+        Reconciliation {
+            confirmed: 1,
+            discarded: 1,
+            pending: 0,
+        }
+    );
+    assert!(
+        repository
+            .get_unvalidated_post_ids(10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 1);
+    assert_eq!(repository.get_likes_count(&2).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn counts_only_returns_the_total_without_likes() {
+    let (service, repository) = common::service(MockUsers::default(), MockPosts::default()).await;
+    for user_id in ["alice", "bob", "carol"] {
+        repository
+            .create_like(user_id, &1, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let response = service
+        .get_post_likes(Request::new(GetPostLikesRequest {
+            post_id: 1,
+            limit: 2,
+            counts_only: true,
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert!(response.likes.is_empty());
+    let pagination = response.pagination.expect("pagination should be set");
+    assert_eq!(pagination.total_count, 3);
+    assert_eq!(pagination.total_pages, 2);
+    assert!(pagination.has_next);
+}
+
+async fn likes_count(service: &impl LikesService, if_none_match: &str) -> GetLikesCountResponse {
+    let request = GetLikesCountRequest {
+        post_id: 1,
+        if_none_match: if_none_match.to_string(),
+        ..Default::default()
+    };
+    service
+        .get_likes_count(Request::new(request))
+        .await
+        .expect("get_likes_count should not fail")
+        .into_inner()
+}
+
+#[tokio::test]
+async fn likes_count_is_not_modified_while_the_etag_matches() {
+    let (service, repository) = common::service(MockUsers::default(), MockPosts::default()).await;
+    repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+
+    let first = likes_count(&service, "").await;
+    assert_eq!(first.count, 1);
+    assert!(!first.not_modified && !first.etag.is_empty());
+
+    let unchanged = likes_count(&service, &first.etag).await;
+    assert!(unchanged.not_modified);
+    assert_eq!((unchanged.count, unchanged.etag), (0, first.etag.clone()));
+
+    repository
+        .create_like("bob", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    let changed = likes_count(&service, &first.etag).await;
+    assert!(!changed.not_modified);
+    assert_eq!(changed.count, 2);
+    assert_ne!(changed.etag, first.etag);
+}
+
+#[tokio::test]
+async fn field_mask_leaves_out_unrequested_fields() {
+    let (service, repository) = common::service(MockUsers::default(), MockPosts::default()).await;
+    repository
+        .create_like("alice", &1, ReactionType::Love)
+        .await
+        .unwrap();
+
+    let masked = service
+        .get_post_likes(Request::new(GetPostLikesRequest {
+            post_id: 1,
+            field_mask: vec!["reaction".to_string(), "unknown".to_string()],
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    let like = &masked.likes[0];
+    assert_eq!(like.user_id, "alice");
+    assert_eq!(like.reaction(), Reaction::Love);
+    assert!(like.liked_at.is_none() && like.created_at.is_none());
+
+    let full = service
+        .get_post_likes(Request::new(GetPostLikesRequest {
+            post_id: 1,
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(full.likes[0].liked_at.is_some() && full.likes[0].created_at.is_some());
+}
+
+#[tokio::test]
+async fn recent_liked_posts_flag_deleted_posts() {
+    let (service, repository) = common::service(
+        MockUsers::default().with_user("user_alice", "alice"),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+    for post_id in [1, 2] {
+        repository
+            .create_like("alice", &post_id, ReactionType::Like)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let posts = service
+        .get_recent_liked_posts(Request::new(GetRecentLikedPostsRequest {
+            user_id: "user_alice".to_string(),
+            limit: 500,
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .posts;
+
+    // Newest first; post 2 no longer exists
+    assert_eq!(posts.len(), 2);
+    assert_eq!((posts[0].post_id, posts[0].deleted), (2, true));
+    assert!(posts[0].title.is_empty());
+    assert_eq!((posts[1].post_id, posts[1].deleted), (1, false));
+    assert_eq!(posts[1].title, "Post 1");
+    assert!(posts.iter().all(|post| post.liked_at.is_some()));
+}
+
+#[tokio::test]
+async fn padded_and_differently_cased_user_ids_share_one_like() {
+    let (service, repository) = common::service(
+        MockUsers::default().with_user("user_alice", "alice"),
+        MockPosts::default().with_post(1, "bob").with_post(2, "bob"),
+    )
+    .await;
+
+    assert_eq!(
+        like_post(&service, " alice ", 1).await.message,
+        "Post liked successfully"
+    );
+    assert_eq!(
+        like_post(&service, "alice", 1).await.message,
+        "Already liked"
+    );
+    assert_eq!(
+        like_post(&service, "\tuser_alice\n", 1).await.message,
+        "Already liked"
+    );
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 1);
+
+    // Case is only folded when configured
+    let service = service.with_lowercase_user_ids(true);
+    assert!(like_post(&service, "ALICE", 2).await.success);
+    assert!(
+        repository
+            .is_post_liked("alice", &2)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert_eq!(repository.get_user_likes_count("alice").await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn likes_histogram_is_a_dense_series() {
+    let (service, repository) = common::service(MockUsers::default(), MockPosts::default()).await;
+    for user_id in ["alice", "bob"] {
+        repository
+            .create_like(user_id, &1, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let timestamp = |at: chrono::DateTime<chrono::Utc>| prost_types::Timestamp {
+        seconds: at.timestamp(),
+        nanos: 0,
+    };
+    let histogram = |from: chrono::DateTime<chrono::Utc>, to: chrono::DateTime<chrono::Utc>| {
+        let mut request = GetLikesHistogramRequest {
+            post_id: 1,
+            from: Some(timestamp(from)),
+            to: Some(timestamp(to)),
+            ..Default::default()
+        };
+        request.set_bucket(HistogramBucket::Hour);
+        service.get_likes_histogram(Request::new(request))
+    };
+
+    let now = chrono::Utc::now();
+    let buckets = histogram(
+        now - chrono::Duration::hours(3),
+        now + chrono::Duration::hours(1),
+    )
+    .await
+    .unwrap()
+    .into_inner()
+    .buckets;
+
+    // Consecutive hours, with both likes in the current one
+    assert!(buckets.len() >= 4);
+    for pair in buckets.windows(2) {
+        let (earlier, later) = (pair[0].bucket_start.unwrap(), pair[1].bucket_start.unwrap());
+        assert_eq!(later.seconds - earlier.seconds, 3600);
+    }
+    assert_eq!(buckets.iter().map(|bucket| bucket.count).sum::<i64>(), 2);
+    let current = buckets
+        .iter()
+        .find(|bucket| {
+            let start = bucket.bucket_start.unwrap().seconds;
+            start <= now.timestamp() && now.timestamp() < start + 3600
+        })
+        .expect("the current hour should be in the range");
+    assert_eq!(current.count, 2);
+
+    // Two months of hourly buckets is too many
+    let status = histogram(now - chrono::Duration::days(60), now)
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn has_ever_liked_tells_unliked_from_never_liked() {
+    let (service, repository) = common::service(
+        MockUsers::default().with_user("user_alice", "alice"),
+        MockPosts::default(),
+    )
+    .await;
+    repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    repository
+        .create_like("alice", &2, ReactionType::Like)
+        .await
+        .unwrap();
+    repository.delete_like("alice", &2).await.unwrap();
+
+    let has_ever_liked = |post_id| {
+        service.has_ever_liked(Request::new(HasEverLikedRequest {
+            user_id: "user_alice".to_string(),
+            post_id,
+        }))
+    };
+
+    let active = has_ever_liked(1).await.unwrap().into_inner();
+    assert!(active.ever_liked && active.is_active);
+    assert!(active.liked_at.is_some());
+    assert!(active.unliked_at.is_none());
+
+    let unliked = has_ever_liked(2).await.unwrap().into_inner();
+    assert!(unliked.ever_liked && !unliked.is_active);
+    assert!(unliked.liked_at.is_some());
+    assert!(unliked.unliked_at.is_some());
+
+    let never = has_ever_liked(3).await.unwrap().into_inner();
+    assert!(!never.ever_liked && !never.is_active);
+    assert!(never.liked_at.is_none());
+}
+
+#[tokio::test]
+async fn watch_post_likes_streams_likes_and_unlikes_of_the_post() {
+    let (service, _repository) = common::service(
+        MockUsers::default().with_user("user_alice", "alice"),
+        MockPosts::default().with_post(1, "bob").with_post(2, "bob"),
+    )
+    .await;
+
+    let mut changes = service
+        .watch_post_likes(Request::new(WatchPostLikesRequest { post_id: 1 }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    // Likes of other posts are not sent
+    assert!(like_post(&service, "user_alice", 2).await.success);
+    assert!(like_post(&service, "user_alice", 1).await.success);
+    service
+        .unlike_post(Request::new(UnlikePostRequest {
+            user_id: "user_alice".to_string(),
+            post_id: 1,
+        }))
+        .await
+        .unwrap();
+
+    let liked = changes.next().await.unwrap().unwrap();
+    assert_eq!(liked.post_id, 1);
+    assert_eq!(liked.user_id, "alice");
+    assert_eq!(liked.action, LikeChangeAction::Liked as i32);
+    assert_eq!(liked.missed, 0);
+    let unliked = changes.next().await.unwrap().unwrap();
+    assert_eq!(unliked.action, LikeChangeAction::Unliked as i32);
+}
+
+#[tokio::test]
+async fn deep_pages_are_rejected() {
+    let (service, _repository) = common::service(
+        MockUsers::default(),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+
+    let get_page = |page| {
+        service.get_post_likes(Request::new(GetPostLikesRequest {
+            post_id: 1,
+            page,
+            limit: 10,
+            ..Default::default()
+        }))
+    };
+
+    // The default bounds serve pages starting up to row 10,000
+    assert!(get_page(1001).await.is_ok());
+    let status = get_page(1002).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn deepest_served_page_reports_the_totals() {
+    let (service, repository) = common::service(
+        MockUsers::default(),
+        MockPosts::default().with_post(1, "bob"),
+    )
+    .await;
+    let bounds = PaginationBounds {
+        max_offset: 20,
+        ..PaginationBounds::default()
+    };
+    let service = service.with_pagination_bounds(bounds, bounds);
+    for user_id in ["alice", "bob", "carol"] {
+        repository
+            .create_like(user_id, &1, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let get_page = |page| {
+        service.get_post_likes(Request::new(GetPostLikesRequest {
+            post_id: 1,
+            page,
+            limit: 10,
+            ..Default::default()
+        }))
+    };
+
+    // Page 3 starts at row 20, the deepest served, and lies past the end
+    let response = get_page(3).await.unwrap().into_inner();
+    assert!(response.likes.is_empty());
+    let pagination = response.pagination.unwrap();
+    assert_eq!((pagination.total_count, pagination.total_pages), (3, 1));
+    assert_eq!(pagination.offset, 20);
+
+    let status = get_page(4).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn check_dependencies_names_the_unreachable_ones() {
+    let (healthy, _repository) = common::service(MockUsers::default(), MockPosts::default()).await;
+    let status = healthy.check_dependencies(Duration::from_secs(1)).await;
+    assert!(status.all_healthy());
+    assert!(status.unhealthy().is_empty());
+
+    let (degraded, _repository) =
+        common::service(MockUsers::default(), MockPosts::default().down()).await;
+    let status = degraded.check_dependencies(Duration::from_secs(1)).await;
+    assert!(status.database && status.user_service);
+    assert!(!status.all_healthy());
+    assert_eq!(status.unhealthy(), vec!["post_service"]);
+}
+
+#[tokio::test]
+async fn get_likes_by_users_spans_the_given_users_newest_first() {
+    let (service, repository) = common::service(
+        MockUsers::default()
+            .with_user("user_alice", "alice")
+            .with_user("user_bob", "bob")
+            .with_user("user_carol", "carol"),
+        MockPosts::default(),
+    )
+    .await;
+    for (user_id, post_id) in [("alice", 1), ("bob", 2), ("carol", 3), ("alice", 4)] {
+        repository
+            .create_like(user_id, &post_id, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let response = service
+        .get_likes_by_users(Request::new(GetLikesByUsersRequest {
+            user_ids: vec!["user_alice".to_string(), "user_bob".to_string()],
+            page: 1,
+            limit: 2,
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let likes: Vec<(String, u32)> = response
+        .likes
+        .into_iter()
+        .map(|like| (like.user_id, like.post_id))
+        .collect();
+    assert_eq!(likes, [("alice".to_string(), 4), ("bob".to_string(), 2)]);
+    let pagination = response.pagination.unwrap();
+    assert_eq!(pagination.total_count, 3);
+    assert!(pagination.has_next);
+
+    let status = service
+        .get_likes_by_users(Request::new(GetLikesByUsersRequest {
+            user_ids: vec!["user_alice".to_string(), "user_dave".to_string()],
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn merge_user_likes_moves_likes_and_skips_posts_the_target_has() {
+    let (service, repository) = common::service(
+        MockUsers::default()
+            .with_user("user_alice", "alice")
+            .with_user("user_bob", "bob"),
+        MockPosts::default(),
+    )
+    .await;
+    for (user_id, post_id) in [("alice", 1), ("alice", 2), ("bob", 2)] {
+        repository
+            .create_like(user_id, &post_id, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let response = service
+        .merge_user_likes(Request::new(MergeUserLikesRequest {
+            from_user_id: "user_alice".to_string(),
+            to_user_id: "user_bob".to_string(),
+            delete_source: true,
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert_eq!((response.moved_count, response.skipped_count), (1, 1));
+    assert!(repository.is_post_liked("bob", &1).await.unwrap().is_some());
+    assert!(repository.is_post_liked("bob", &2).await.unwrap().is_some());
+    assert!(
+        repository
+            .is_post_liked("alice", &2)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(repository.get_likes_count(&2).await.unwrap(), 1);
+
+    let status = service
+        .merge_user_likes(Request::new(MergeUserLikesRequest {
+            from_user_id: "user_bob".to_string(),
+            to_user_id: "user_bob".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn which_users_liked_maps_aliases_of_one_user_back_to_each_id() {
+    let (service, repository) = common::service(
+        MockUsers::default()
+            .with_user("user_alice", "alice")
+            .with_user("user_alice_old", "alice")
+            .with_user("user_bob", "bob")
+            .with_user("user_carol", "carol"),
+        MockPosts::default().with_post(1, "dave"),
+    )
+    .await;
+    for user_id in ["alice", "carol"] {
+        repository
+            .create_like(user_id, &1, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let response = service
+        .which_users_liked(Request::new(WhichUsersLikedRequest {
+            post_id: 1,
+            user_ids: ["user_alice", "user_alice_old", "user_bob", "user_carol"]
+                .map(String::from)
+                .to_vec(),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(
+        response.user_ids,
+        ["user_alice", "user_alice_old", "user_carol"]
+    );
+}
@@ -0,0 +1,95 @@
+use like_service::service::MethodTimeoutLayer;
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tonic::{
+    Code, Status,
+    codegen::http::{Request, Response},
+};
+use tower::{Layer, Service};
+
+/// Answers every request with OK after `delay`
+#[derive(Clone)]
+struct Slow {
+    delay: Duration,
+}
+
+impl Service<Request<()>> for Slow {
+    type Response = Response<String>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _request: Request<()>) -> Self::Future {
+        let delay = self.delay;
+        Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            Ok(Response::new("ok".to_string()))
+        })
+    }
+}
+
+fn request(path: &str) -> Request<()> {
+    Request::builder().uri(path).body(()).unwrap()
+}
+
+fn code(response: &Response<String>) -> Code {
+    Status::from_header_map(response.headers())
+        .map(|status| status.code())
+        .unwrap_or(Code::Ok)
+}
+
+#[tokio::test]
+async fn methods_over_their_budget_exceed_the_deadline() {
+    let layer = MethodTimeoutLayer::new(Some(Duration::from_millis(500)))
+        .with_method("GetLikesCount", Some(Duration::from_millis(20)))
+        .with_method("ExportUserLikes", None);
+    let mut service = layer.layer(Slow {
+        delay: Duration::from_millis(100),
+    });
+
+    let count = service
+        .call(request("/likes.LikesService/GetLikesCount"))
+        .await
+        .unwrap();
+    assert_eq!(code(&count), Code::DeadlineExceeded);
+
+    // Within the default budget
+    let like = service
+        .call(request("/likes.LikesService/LikePost"))
+        .await
+        .unwrap();
+    assert_eq!(code(&like), Code::Ok);
+}
+
+#[tokio::test]
+async fn budgets_are_looked_up_by_method_name() {
+    let layer = MethodTimeoutLayer::new(Some(Duration::from_secs(5)))
+        .with_method("GetLikesCount", Some(Duration::from_millis(200)))
+        .with_method("ExportUserLikes", None);
+
+    assert_eq!(
+        layer.timeout_for("/likes.LikesService/GetLikesCount"),
+        Some(Duration::from_millis(200))
+    );
+    assert_eq!(
+        layer.timeout_for("/likes.LikesService/LikePost"),
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(
+        layer.timeout_for("/likes.LikesService/ExportUserLikes"),
+        None
+    );
+    assert_eq!(layer.timeout_for("/grpc.health.v1.Health/Check"), None);
+    assert_eq!(
+        MethodTimeoutLayer::new(None).timeout_for("/likes.LikesService/LikePost"),
+        None
+    );
+}
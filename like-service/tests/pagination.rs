@@ -0,0 +1,129 @@
+use like_service::models::{FeedCursor, PaginatedResult, PaginationBounds, PaginationParams};
+
+fn page(page: i32, limit: i32, total_count: i64) -> PaginatedResult<()> {
+    PaginatedResult::new(Vec::new(), total_count, &PaginationParams::new(page, limit))
+}
+
+#[test]
+fn first_of_several_pages_has_only_a_next_page() {
+    let result = page(1, 10, 25);
+    assert_eq!(result.total_pages, 3);
+    assert!(result.has_next);
+    assert!(!result.has_previous);
+    assert_eq!(result.offset, 0);
+}
+
+#[test]
+fn last_page_has_only_a_previous_page() {
+    let result = page(3, 10, 25);
+    assert!(!result.has_next);
+    assert!(result.has_previous);
+    assert_eq!(result.offset, 20);
+}
+
+#[test]
+fn middle_page_has_both_neighbours() {
+    let result = page(2, 10, 25);
+    assert!(result.has_next);
+    assert!(result.has_previous);
+    assert_eq!(result.offset, 10);
+}
+
+#[test]
+fn single_page_has_no_neighbours() {
+    let result = page(1, 10, 10);
+    assert_eq!(result.total_pages, 1);
+    assert!(!result.has_next);
+    assert!(!result.has_previous);
+}
+
+#[test]
+fn page_past_the_end_points_back() {
+    let result = page(5, 10, 25);
+    assert!(!result.has_next);
+    assert!(result.has_previous);
+}
+
+#[test]
+fn empty_result_is_one_empty_page() {
+    let result = page(1, 10, 0);
+    assert_eq!(result.total_pages, 1);
+    assert_eq!(result.current_page, 1);
+    assert!(!result.has_next);
+    assert!(!result.has_previous);
+}
+
+#[test]
+fn exact_multiple_has_no_trailing_empty_page() {
+    let result = page(3, 10, 30);
+    assert_eq!(result.total_pages, 3);
+    assert!(!result.has_next);
+}
+
+#[test]
+fn partial_last_page_is_counted() {
+    assert_eq!(page(1, 10, 21).total_pages, 3);
+    assert_eq!(page(1, 10, 1).total_pages, 1);
+}
+
+#[test]
+fn zero_limit_counts_as_one_per_page() {
+    let params = PaginationParams { page: 1, limit: 0 };
+    let result = PaginatedResult::<()>::new(Vec::new(), 5, &params);
+    assert_eq!(result.total_pages, 5);
+}
+
+#[test]
+fn limits_are_defaulted_and_clamped() {
+    let bounds = PaginationBounds {
+        min_limit: 1,
+        default_limit: 20,
+        max_limit: 50,
+        ..PaginationBounds::default()
+    };
+    assert_eq!(PaginationParams::with_bounds(0, 0, &bounds).limit, 20);
+    assert_eq!(PaginationParams::with_bounds(0, 0, &bounds).page, 1);
+    assert_eq!(PaginationParams::with_bounds(1, 500, &bounds).limit, 50);
+    assert_eq!(PaginationParams::with_bounds(1, 30, &bounds).limit, 30);
+}
+
+#[test]
+fn pages_past_the_maximum_offset_are_too_deep() {
+    let bounds = PaginationBounds {
+        max_offset: 100,
+        ..PaginationBounds::default()
+    };
+
+    assert!(PaginationParams::with_bounds(11, 10, &bounds).within_depth(&bounds));
+    assert!(!PaginationParams::with_bounds(12, 10, &bounds).within_depth(&bounds));
+    // Saturates rather than wrapping back into range
+    assert!(!PaginationParams::with_bounds(i32::MAX, 100, &bounds).within_depth(&bounds));
+}
+
+#[test]
+fn deepest_served_page_still_reports_the_totals() {
+    let bounds = PaginationBounds {
+        max_offset: 100,
+        ..PaginationBounds::default()
+    };
+    let params = PaginationParams::with_bounds(11, 10, &bounds);
+    assert!(params.within_depth(&bounds));
+
+    let result = PaginatedResult::<()>::new(Vec::new(), 25, &params);
+    assert_eq!(result.offset, 100);
+    assert_eq!(result.total_count, 25);
+    assert_eq!(result.total_pages, 3);
+    assert!(!result.has_next);
+}
+
+#[test]
+fn feed_cursor_round_trips_through_its_token() {
+    let cursor = FeedCursor {
+        liked_at: chrono::DateTime::from_timestamp(1_700_000_000, 123_456_789).unwrap(),
+        id: "abc123".to_string(),
+    };
+
+    assert_eq!(FeedCursor::decode(&cursor.encode()), Some(cursor));
+    assert_eq!(FeedCursor::decode("not a token"), None);
+    assert_eq!(FeedCursor::decode("2024-01-01T00:00:00Z|"), None);
+}
@@ -0,0 +1,49 @@
+use like_service::events::{LikeAction, LikeEvent, PostWatchers};
+
+fn event(user_id: &str, post_id: u32) -> LikeEvent {
+    LikeEvent::new(user_id.to_string(), post_id, LikeAction::Liked)
+}
+
+#[tokio::test]
+async fn slow_subscribers_miss_the_oldest_events() {
+    let watchers = PostWatchers::new(2);
+    let mut subscription = watchers.subscribe(1);
+
+    // Publishing never waits for the subscriber
+    for user_id in ["alice", "bob", "carol", "dave"] {
+        watchers.publish(&event(user_id, 1));
+    }
+
+    let first = subscription.recv().await.unwrap();
+    assert_eq!(first.event.user_id, "carol");
+    assert_eq!(first.missed, 2);
+    let second = subscription.recv().await.unwrap();
+    assert_eq!(second.event.user_id, "dave");
+    assert_eq!(second.missed, 0);
+}
+
+#[tokio::test]
+async fn channels_are_removed_with_their_last_subscriber() {
+    let watchers = PostWatchers::default();
+    let first = watchers.subscribe(1);
+    let second = watchers.subscribe(1);
+    assert_eq!(watchers.watched_posts(), 1);
+
+    drop(first);
+    assert_eq!(watchers.watched_posts(), 1);
+    drop(second);
+    assert_eq!(watchers.watched_posts(), 0);
+
+    // Nobody is watching, so nothing is kept
+    watchers.publish(&event("alice", 2));
+    assert_eq!(watchers.watched_posts(), 0);
+}
+
+#[tokio::test]
+async fn closing_ends_open_subscriptions() {
+    let watchers = PostWatchers::default();
+    let mut subscription = watchers.subscribe(1);
+
+    watchers.close();
+    assert!(subscription.recv().await.is_none());
+}
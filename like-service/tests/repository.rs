@@ -0,0 +1,687 @@
+mod common;
+
+use like_service::{
+    clock::{Clock, MockClock},
+    database::{Database, DatabaseOptions, RocksDbOptions},
+    error::LikesError,
+    models::{IdempotentResponse, LikeSort, LikedAtRange, PaginationParams, ReactionType},
+    repository::{LikeWriteBuffer, LikesRepository},
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn create_like_is_visible_to_reads() {
+    let repository = common::repository().await;
+
+    let like = repository
+        .create_like("alice", &1, ReactionType::Love)
+        .await
+        .unwrap();
+    assert_eq!(like.user_id, "alice");
+    assert_eq!(like.post_id, 1);
+    assert_eq!(like.reaction, ReactionType::Love);
+
+    let liked_at = repository.is_post_liked("alice", &1).await.unwrap();
+    assert_eq!(liked_at, Some(like.liked_at));
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn duplicate_like_is_rejected() {
+    let repository = common::repository().await;
+
+    repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    let duplicate = repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await;
+
+    assert!(matches!(duplicate, Err(LikesError::AlreadyExists(_))));
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn liking_with_another_reaction_updates_the_like() {
+    let repository = common::repository().await;
+    let first = repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+
+    let changed = repository
+        .create_like("alice", &1, ReactionType::Love)
+        .await
+        .unwrap();
+
+    assert_eq!(changed.id, first.id);
+    assert_eq!(changed.reaction, ReactionType::Love);
+    assert_eq!(changed.created_at, first.created_at);
+    assert!(changed.liked_at > first.liked_at);
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 1);
+
+    // The same reaction again is still a duplicate
+    let duplicate = repository
+        .create_like("alice", &1, ReactionType::Love)
+        .await;
+    assert!(matches!(duplicate, Err(LikesError::AlreadyExists(_))));
+}
+
+#[tokio::test]
+async fn delete_like_removes_it_once() {
+    let repository = common::repository().await;
+    repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+
+    assert!(repository.delete_like("alice", &1).await.unwrap());
+    assert!(!repository.delete_like("alice", &1).await.unwrap());
+
+    assert_eq!(repository.is_post_liked("alice", &1).await.unwrap(), None);
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn relike_revives_the_original_row() {
+    let repository = common::repository().await;
+    let first = repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    repository.delete_like("alice", &1).await.unwrap();
+
+    let second = repository
+        .create_like("alice", &1, ReactionType::Wow)
+        .await
+        .unwrap();
+
+    assert_eq!(second.id, first.id);
+    assert_eq!(second.created_at, first.created_at);
+    assert!(second.liked_at > first.liked_at);
+    assert_eq!(second.reaction, ReactionType::Wow);
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn get_user_likes_pages_through_active_likes() {
+    let repository = common::repository().await;
+    for post_id in 1..=5 {
+        repository
+            .create_like("alice", &post_id, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+    repository.delete_like("alice", &5).await.unwrap();
+    repository
+        .create_like("bob", &1, ReactionType::Like)
+        .await
+        .unwrap();
+
+    let first = repository
+        .get_user_likes(
+            "alice",
+            &PaginationParams::new(1, 3),
+            LikeSort::default(),
+            LikedAtRange::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(first.total_count, 4);
+    assert_eq!(first.total_pages, 2);
+    assert_eq!(first.data.len(), 3);
+    assert!(first.has_next);
+    assert!(!first.has_previous);
+
+    let second = repository
+        .get_user_likes(
+            "alice",
+            &PaginationParams::new(2, 3),
+            LikeSort::default(),
+            LikedAtRange::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(second.data.len(), 1);
+    assert_eq!(second.offset, 3);
+    assert!(!second.has_next);
+    assert!(second.has_previous);
+
+    // Newest first by default, and never another user's likes
+    let post_ids: Vec<u32> = first
+        .data
+        .iter()
+        .chain(&second.data)
+        .map(|like| like.post_id)
+        .collect();
+    assert_eq!(post_ids, vec![4, 3, 2, 1]);
+}
+
+#[tokio::test]
+async fn get_user_likes_filters_by_liked_at_range() {
+    let repository = common::repository().await;
+    let mut liked_at = Vec::new();
+    for post_id in 1..=4 {
+        let like = repository
+            .create_like("alice", &post_id, ReactionType::Like)
+            .await
+            .unwrap();
+        liked_at.push(like.liked_at);
+    }
+
+    let likes_within = |range: LikedAtRange| {
+        let repository = repository.clone();
+        async move {
+            let page = repository
+                .get_user_likes(
+                    "alice",
+                    &PaginationParams::new(1, 10),
+                    LikeSort::default(),
+                    range,
+                )
+                .await
+                .unwrap();
+            let post_ids: Vec<u32> = page.data.iter().map(|like| like.post_id).collect();
+            (page.total_count, post_ids)
+        }
+    };
+
+    // Both bounds are inclusive
+    let between = LikedAtRange {
+        from: Some(liked_at[1]),
+        to: Some(liked_at[2]),
+    };
+    assert_eq!(likes_within(between).await, (2, vec![3, 2]));
+
+    let since = LikedAtRange {
+        from: Some(liked_at[2]),
+        to: None,
+    };
+    assert_eq!(likes_within(since).await, (2, vec![4, 3]));
+
+    let until = LikedAtRange {
+        from: None,
+        to: Some(liked_at[0]),
+    };
+    assert_eq!(likes_within(until).await, (1, vec![1]));
+}
+
+#[tokio::test]
+async fn get_likes_count_only_counts_the_post() {
+    let repository = common::repository().await;
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 0);
+
+    for user_id in ["alice", "bob", "carol"] {
+        repository
+            .create_like(user_id, &1, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+    repository
+        .create_like("alice", &2, ReactionType::Like)
+        .await
+        .unwrap();
+
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 3);
+    assert_eq!(repository.get_likes_count(&2).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn unlike_posts_matches_users_and_posts() {
+    let repository = common::repository().await;
+    for user_id in ["alice", "bob", "carol"] {
+        for post_id in [1, 2] {
+            repository
+                .create_like(user_id, &post_id, ReactionType::Like)
+                .await
+                .unwrap();
+        }
+    }
+
+    let users = vec!["alice".to_string(), "bob".to_string()];
+    assert_eq!(
+        repository.count_matching_likes(&users, &[1]).await.unwrap(),
+        2
+    );
+    assert!(repository.unlike_posts(&users, &[1]).await.unwrap());
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 1);
+    assert_eq!(repository.get_likes_count(&2).await.unwrap(), 3);
+
+    // Only post IDs: every remaining like on the post
+    assert!(repository.unlike_posts(&[], &[2]).await.unwrap());
+    assert_eq!(repository.get_likes_count(&2).await.unwrap(), 0);
+
+    // Nothing left to unlike
+    assert!(!repository.unlike_posts(&users, &[1, 2]).await.unwrap());
+    assert!(repository.unlike_posts(&[], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn read_your_writes_sees_a_like_immediately() {
+    let repository = common::repository().await.with_read_your_writes(true);
+
+    repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    assert!(
+        repository
+            .is_post_liked("alice", &1)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert_eq!(repository.get_user_likes_count("alice").await.unwrap(), 1);
+
+    repository.delete_like("alice", &1).await.unwrap();
+    assert!(
+        repository
+            .is_post_liked("alice", &1)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn most_liked_posts_page_in_a_stable_order() {
+    let repository = common::repository().await;
+    // Post 3 has three likes; posts 1, 2 and 4 tie with one each
+    for (user_id, post_id) in [
+        ("alice", 3),
+        ("bob", 3),
+        ("carol", 3),
+        ("alice", 4),
+        ("bob", 2),
+        ("carol", 1),
+    ] {
+        repository
+            .create_like(user_id, &post_id, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+
+    let first = repository
+        .get_most_liked_posts(&PaginationParams::new(1, 2), None)
+        .await
+        .unwrap();
+    let second = repository
+        .get_most_liked_posts(&PaginationParams::new(2, 2), None)
+        .await
+        .unwrap();
+
+    let ranked: Vec<(u32, i64)> = first
+        .data
+        .iter()
+        .chain(&second.data)
+        .map(|row| (row.post_id, row.count))
+        .collect();
+    assert_eq!(ranked, [(3, 3), (1, 1), (2, 1), (4, 1)]);
+    assert_eq!(first.total_count, 4);
+    assert!(first.has_next && !second.has_next);
+
+    let since = chrono::Utc::now() + chrono::Duration::hours(1);
+    let future = repository
+        .get_most_liked_posts(&PaginationParams::new(1, 2), Some(since))
+        .await
+        .unwrap();
+    assert!(future.data.is_empty());
+    assert_eq!(future.total_count, 0);
+}
+
+#[test]
+fn rocksdb_options_map_to_engine_variables() {
+    let options = RocksDbOptions {
+        block_cache_mb: Some(512),
+        write_buffer_mb: Some(64),
+        max_write_buffers: None,
+        compression: Some("lz4".to_string()),
+    };
+
+    // Unset and unsupported options are left out
+    assert_eq!(
+        options.engine_variables(),
+        vec![
+            (
+                "SURREAL_ROCKSDB_BLOCK_CACHE_SIZE",
+                (512 * 1024 * 1024).to_string()
+            ),
+            (
+                "SURREAL_ROCKSDB_WRITE_BUFFER_SIZE",
+                (64 * 1024 * 1024).to_string()
+            ),
+        ]
+    );
+    assert!(RocksDbOptions::default().engine_variables().is_empty());
+}
+
+#[tokio::test]
+async fn post_id_is_stored_and_matched_as_an_integer() {
+    let database = common::database().await;
+    let repository = LikesRepository::new(database.clone());
+
+    repository
+        .create_like("alice", &5, ReactionType::Like)
+        .await
+        .unwrap();
+    repository
+        .create_like("bob", &5, ReactionType::Like)
+        .await
+        .unwrap();
+
+    let mut response = database
+        .query("SELECT VALUE post_id FROM likes")
+        .await
+        .unwrap();
+    let stored: Vec<serde_json::Value> = response.take(0).unwrap();
+    assert_eq!(stored, vec![json!(5), json!(5)]);
+
+    // Every read and write finds the like by the id it was created with
+    assert!(
+        repository
+            .is_post_liked("alice", &5)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert_eq!(repository.get_likes_count(&5).await.unwrap(), 2);
+    let page = repository
+        .get_post_likes(&5, &PaginationParams::new(1, 10), LikeSort::default())
+        .await
+        .unwrap();
+    assert_eq!(page.data.len(), 2);
+
+    assert!(repository.delete_like("alice", &5).await.unwrap());
+    assert!(
+        repository
+            .unlike_posts(&["bob".to_string()], &[5])
+            .await
+            .unwrap()
+    );
+    assert_eq!(repository.get_likes_count(&5).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn user_likes_stream_across_batches_with_removed_likes() {
+    use tokio_stream::StreamExt;
+
+    let repository = common::repository().await;
+    for post_id in 1..=7 {
+        repository
+            .create_like("alice", &post_id, ReactionType::Like)
+            .await
+            .unwrap();
+    }
+    repository
+        .create_like("bob", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    repository.delete_like("alice", &4).await.unwrap();
+
+    // A batch size that doesn't divide the row count exercises the last, short page
+    let likes: Vec<_> = repository
+        .stream_user_likes("alice", 3)
+        .collect::<Result<_, _>>()
+        .await
+        .unwrap();
+
+    let mut post_ids: Vec<u32> = likes.iter().map(|like| like.post_id).collect();
+    post_ids.sort_unstable();
+    assert_eq!(post_ids, (1..=7).collect::<Vec<_>>());
+    assert!(likes.iter().all(|like| like.user_id == "alice"));
+
+    let removed: Vec<u32> = likes
+        .iter()
+        .filter(|like| like.deleted_at.is_some())
+        .map(|like| like.post_id)
+        .collect();
+    assert_eq!(removed, vec![4]);
+}
+
+#[tokio::test]
+async fn every_write_to_a_like_bumps_its_version() {
+    let repository = common::repository().await;
+    let version = |repository: &LikesRepository| {
+        let repository = repository.clone();
+        async move {
+            repository
+                .get_like_including_deleted("alice", &1)
+                .await
+                .unwrap()
+                .map(|like| like.version)
+        }
+    };
+
+    let like = repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    assert_eq!(like.version, 0);
+
+    // Changing the reaction, unliking, re-liking and toggling each move it on
+    repository
+        .create_like("alice", &1, ReactionType::Love)
+        .await
+        .unwrap();
+    assert_eq!(version(&repository).await, Some(1));
+    assert!(repository.delete_like("alice", &1).await.unwrap());
+    assert_eq!(version(&repository).await, Some(2));
+    let relike = repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    assert_eq!(relike.version, 3);
+    assert_eq!(repository.toggle_like("alice", &1).await.unwrap(), None);
+    assert_eq!(version(&repository).await, Some(4));
+
+    // Unliking what isn't liked writes nothing
+    assert!(!repository.delete_like("alice", &1).await.unwrap());
+    assert_eq!(version(&repository).await, Some(4));
+}
+
+#[test]
+fn version_conflicts_are_retriable() {
+    let status = tonic::Status::from(LikesError::Conflict("changed concurrently".to_string()));
+    assert_eq!(status.code(), tonic::Code::Aborted);
+}
+
+#[tokio::test]
+async fn demo_data_only_seeds_an_empty_table() {
+    let repository = common::repository().await;
+
+    assert_eq!(repository.seed_demo_data(120).await.unwrap(), 120);
+    // 50 users like post 1, then post 2, then 20 of them post 3
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 50);
+    assert_eq!(repository.get_likes_count(&3).await.unwrap(), 20);
+    assert_eq!(
+        repository
+            .get_user_likes_count("demo_user_1")
+            .await
+            .unwrap(),
+        3
+    );
+
+    // A second boot leaves existing data alone
+    assert_eq!(repository.seed_demo_data(120).await.unwrap(), 0);
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 50);
+}
+
+#[tokio::test]
+async fn reads_go_to_the_replica_when_one_is_configured() {
+    // A second, empty in-memory datastore stands in for a replica that
+    // has not caught up yet
+    let database = Database::new(&DatabaseOptions {
+        read_url: Some(String::new()),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let repository = LikesRepository::new(database);
+    repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 0);
+    assert_eq!(repository.is_post_liked("alice", &1).await.unwrap(), None);
+
+    // With read-your-writes, a user's own reads stay on the primary
+    let repository = repository.with_read_your_writes(true);
+    assert!(
+        repository
+            .is_post_liked("alice", &1)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert_eq!(repository.get_likes_count(&1).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn cached_post_likes_pages_are_dropped_by_writes() {
+    let database = common::database().await;
+    let cached = LikesRepository::new(database.clone())
+        .with_post_likes_cache(std::time::Duration::from_secs(60), 100);
+    // Stands in for another instance, whose writes this cache can't see
+    let other = LikesRepository::new(database);
+    let first_page = |repository: &LikesRepository| {
+        let repository = repository.clone();
+        async move {
+            repository
+                .get_post_likes(&1, &PaginationParams::new(1, 10), LikeSort::default())
+                .await
+                .unwrap()
+                .total_count
+        }
+    };
+
+    cached
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    assert_eq!(first_page(&cached).await, 1);
+
+    // Served from the cache until the TTL
+    other
+        .create_like("bob", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    assert_eq!(first_page(&cached).await, 1);
+
+    // A write through the caching repository drops the post's pages
+    cached.delete_like("alice", &1).await.unwrap();
+    assert_eq!(first_page(&cached).await, 1);
+    assert_eq!(
+        cached
+            .get_post_likes(&1, &PaginationParams::new(1, 10), LikeSort::default())
+            .await
+            .unwrap()
+            .data[0]
+            .user_id,
+        "bob"
+    );
+    cached
+        .create_like("carol", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    assert_eq!(first_page(&cached).await, 2);
+}
+
+#[tokio::test]
+async fn buffered_likes_report_their_own_outcome() {
+    let direct = common::repository().await;
+    direct
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    direct
+        .create_like("bob", &2, ReactionType::Like)
+        .await
+        .unwrap();
+    direct.delete_like("bob", &2).await.unwrap();
+    direct
+        .create_like("carol", &3, ReactionType::Like)
+        .await
+        .unwrap();
+
+    let (buffer, _task) =
+        LikeWriteBuffer::spawn(direct.clone(), std::time::Duration::from_millis(50), 100);
+    let buffered = direct.clone().with_write_buffer(buffer);
+
+    // All land in one batch
+    let (again, revived, changed, created, repeated) = tokio::join!(
+        buffered.create_like("alice", &1, ReactionType::Like),
+        buffered.create_like("bob", &2, ReactionType::Love),
+        buffered.create_like("carol", &3, ReactionType::Laugh),
+        buffered.create_like("dave", &4, ReactionType::Like),
+        buffered.create_like("dave", &4, ReactionType::Like),
+    );
+
+    assert!(matches!(again, Err(LikesError::AlreadyExists(_))));
+    assert_eq!(revived.unwrap().reaction, ReactionType::Love);
+    assert_eq!(changed.unwrap().reaction, ReactionType::Laugh);
+    assert_eq!(created.unwrap().user_id, "dave");
+    assert!(matches!(repeated, Err(LikesError::AlreadyExists(_))));
+
+    assert!(matches!(
+        buffered.create_like("", &5, ReactionType::Like).await,
+        Err(LikesError::InvalidInput(_))
+    ));
+    for post_id in 1..=4 {
+        assert_eq!(direct.get_likes_count(&post_id).await.unwrap(), 1);
+    }
+}
+
+#[tokio::test]
+async fn writes_and_expiries_follow_the_injected_clock() {
+    let start = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let clock = MockClock::new(start);
+    let repository = common::repository()
+        .await
+        .with_clock(std::sync::Arc::new(clock.clone()));
+
+    let like = repository
+        .create_like("alice", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    assert_eq!(like.liked_at, start);
+
+    clock.advance(chrono::Duration::days(2));
+    repository
+        .create_like("bob", &1, ReactionType::Like)
+        .await
+        .unwrap();
+    let since = start + chrono::Duration::days(1);
+    assert_eq!(
+        repository.get_likes_count_since(&1, since).await.unwrap(),
+        1
+    );
+
+    let response = IdempotentResponse {
+        success: true,
+        message: "Post liked successfully".to_string(),
+        liked_at: Some(clock.now()),
+    };
+    repository
+        .save_idempotent_response("alice", "key-1", &response, chrono::Duration::hours(1))
+        .await
+        .unwrap();
+    assert!(
+        repository
+            .get_idempotent_response("alice", "key-1")
+            .await
+            .unwrap()
+            .is_some()
+    );
+    clock.advance(chrono::Duration::hours(2));
+    assert!(
+        repository
+            .get_idempotent_response("alice", "key-1")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use like_service::repository::LikesRepository;
+
+#[tokio::test]
+async fn migrated_database_is_in_sync() {
+    let repository = common::repository().await;
+
+    let report = repository.verify_schema(false).await.unwrap();
+
+    assert!(report.is_in_sync(), "unexpected drift: {report:?}");
+    assert!(report.repaired.is_empty());
+}
+
+#[tokio::test]
+async fn verify_schema_reports_and_repairs_drift() {
+    let database = common::database().await;
+    let repository = LikesRepository::new(database.clone());
+    database
+        .query(
+            "REMOVE INDEX likes_post_id ON TABLE likes;
+             DEFINE INDEX likes_reaction ON TABLE likes COLUMNS reaction;",
+        )
+        .await
+        .unwrap();
+
+    let report = repository.verify_schema(false).await.unwrap();
+    assert!(!report.is_in_sync());
+    assert_eq!(report.missing_indexes, ["likes_post_id"]);
+    assert_eq!(report.extra_indexes, ["likes_reaction"]);
+    assert!(report.missing_fields.is_empty());
+    assert!(report.repaired.is_empty());
+
+    // Repair restores the missing index and leaves the extra one alone
+    let report = repository.verify_schema(true).await.unwrap();
+    assert_eq!(report.repaired, ["likes_post_id"]);
+    assert!(report.missing_indexes.is_empty());
+    assert_eq!(report.extra_indexes, ["likes_reaction"]);
+
+    let report = repository.verify_schema(false).await.unwrap();
+    assert!(report.missing_indexes.is_empty());
+}